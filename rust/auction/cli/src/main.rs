@@ -6,7 +6,7 @@ use {
         input_validators::{is_url, is_valid_pubkey, is_valid_signer},
     },
     solana_client::rpc_client::RpcClient,
-    solana_program::{borsh::try_from_slice_unchecked, hash, program_pack::Pack},
+    solana_program::{hash, program_pack::Pack},
     solana_sdk::{
         pubkey::Pubkey,
         signature::{read_keypair_file, Keypair, Signer},
@@ -137,8 +137,7 @@ fn inspect_auction(app_matches: &ArgMatches, _payer: Keypair, client: RpcClient)
 
     // Load Auction data.
     let auction_data = client.get_account(&auction_pubkey).unwrap();
-    let auction: spl_auction::processor::AuctionData =
-        try_from_slice_unchecked(&auction_data.data).unwrap();
+    let auction = spl_auction::processor::AuctionData::from_slice(&auction_data.data).unwrap();
 
     println!(
         "\n\
@@ -310,6 +309,9 @@ fn place_bid(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) {
             mint.pubkey(),   // Token Mint
             transfer_authority.pubkey(), // Account Approved to Move Tokens
             payer.pubkey(),  // Pays for Transactions
+            None,
+            None,
+            None,
             PlaceBidArgs { amount, resource },
         ),
     ];
@@ -354,8 +356,7 @@ fn claim_bid(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) {
     ];
     let (bidpot, _) = Pubkey::find_program_address(seeds, &program_key);
     let bidpot_data = client.get_account(&bidpot).unwrap();
-    let bidpot: spl_auction::processor::BidderPot =
-        try_from_slice_unchecked(&bidpot_data.data).unwrap();
+    let bidpot = spl_auction::processor::BidderPot::from_slice(&bidpot_data.data).unwrap();
 
     println!(
         "Claiming Bid:\n\
@@ -447,8 +448,7 @@ fn cancel_bid(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) {
     ];
     let (bidpot, _) = Pubkey::find_program_address(seeds, &program_key);
     let bidpot_data = client.get_account(&bidpot).unwrap();
-    let bidpot: spl_auction::processor::BidderPot =
-        try_from_slice_unchecked(&bidpot_data.data).unwrap();
+    let bidpot = spl_auction::processor::BidderPot::from_slice(&bidpot_data.data).unwrap();
 
     let instructions = [instruction::cancel_bid_instruction(
         program_key,
@@ -456,6 +456,7 @@ fn cancel_bid(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) {
         bidder.pubkey(), // SPL Token Account (Source)
         bidpot.bidder_pot, // SPL Token Account (Destination)
         mint.pubkey(),   // Token Mint
+        None,
         CancelBidArgs { resource },
     )];
 
@@ -106,6 +106,142 @@ pub enum AuctionError {
     /// The pot for this bid is already empty.
     #[error("The pot for this bid is already empty.")]
     BidderPotEmpty,
+
+    /// Dutch auction start price must be above its floor, and its decay must progress.
+    #[error("Dutch auction start price must be above its floor, and its decay must progress.")]
+    InvalidDutchAuctionConfig,
+
+    /// Sealed bid reveal period must be positive and its forfeit at most 100%.
+    #[error("Sealed bid reveal period must be positive and its forfeit at most 100%.")]
+    InvalidSealedBidConfig,
+
+    /// Sealed bid auctions only take sealed bids, and sealed bids are only for them.
+    #[error("Sealed bid auctions only take sealed bids, and sealed bids are only for them.")]
+    SealedBidMismatch,
+
+    /// Bidding on this auction has closed.
+    #[error("Bidding on this auction has closed.")]
+    BiddingClosed,
+
+    /// Sealed bids can only be revealed after bidding closes and before the auction ends.
+    #[error("Sealed bids can only be revealed after bidding closes and before the auction ends.")]
+    NotInRevealPhase,
+
+    /// This sealed bid has already been revealed.
+    #[error("This sealed bid has already been revealed.")]
+    BidAlreadyRevealed,
+
+    /// The revealed bid exceeds the deposit in the bidder pot.
+    #[error("The revealed bid exceeds the deposit in the bidder pot.")]
+    RevealExceedsDeposit,
+
+    /// Unrevealed sealed bids are settled through ClaimForfeitedBid.
+    #[error("Unrevealed sealed bids are settled through ClaimForfeitedBid.")]
+    SealedBidNotRevealed,
+
+    /// The auction account is too small to hold this auction's settings.
+    #[error("The auction account is too small to hold this auction's settings.")]
+    AuctionAccountTooSmall,
+
+    /// Second price settlement needs a capped English or sealed bid auction, and uniform price a multi-unit one.
+    #[error("Second price settlement needs a capped English or sealed bid auction, and uniform price a multi-unit one.")]
+    InvalidSettlementMode,
+
+    /// The end auction gap must be positive, and can only be capped when set.
+    #[error("The end auction gap must be positive, and can only be capped when set.")]
+    InvalidEndAuctionGap,
+
+    /// Bid increments must be positive, with at most four tiers sorted from zero up.
+    #[error("Bid increments must be positive, with at most four tiers sorted from zero up.")]
+    InvalidBidIncrement,
+
+    /// Bid history capacity must be between 1 and 200 events.
+    #[error("Bid history capacity must be between 1 and 200 events.")]
+    InvalidBidHistoryCapacity,
+
+    /// This auction records its bid history, its account must be passed last.
+    #[error("This auction records its bid history, its account must be passed last.")]
+    BidHistoryMissing,
+
+    /// This auction already has a bid history.
+    #[error("This auction already has a bid history.")]
+    BidHistoryAlreadyExists,
+
+    /// The auction has no scheduled start, or its start time has not been reached.
+    #[error("The auction has no scheduled start, or its start time has not been reached.")]
+    ScheduledStartNotReached,
+
+    /// The auction has not reached its end time.
+    #[error("The auction has not reached its end time.")]
+    AuctionNotEnded,
+
+    /// Bid quantities must be between one and the number of units, and only on multi-unit auctions.
+    #[error("Bid quantities must be between one and the number of units, and only on multi-unit auctions.")]
+    InvalidBidQuantity,
+
+    /// Multi-unit auctions need capped winners, and cannot be Dutch or sealed bid auctions.
+    #[error(
+        "Multi-unit auctions need capped winners, and cannot be Dutch or sealed bid auctions."
+    )]
+    InvalidMultiUnitConfig,
+
+    /// Native bids need the native mint, and are not available on sealed bid auctions.
+    #[error("Native bids need the native mint, and are not available on sealed bid auctions.")]
+    InvalidNativeBidConfig,
+
+    /// Fee basis points and the referral share must be at most 100%.
+    #[error("Fee basis points and the referral share must be at most 100%.")]
+    InvalidFeeSchedule,
+
+    /// The fee config does not match the one the auction was created with.
+    #[error("The fee config does not match the one the auction was created with.")]
+    FeeConfigMismatch,
+
+    /// The bidder pot was created before the data being recorded in it existed.
+    #[error("The bidder pot was created before the data being recorded in it existed.")]
+    BidderPotTooSmall,
+
+    /// The referral registry is invalid.
+    #[error("The referral registry is invalid.")]
+    InvalidReferralRegistry,
+
+    /// The referrer account does not belong to the referrer recorded with the bid.
+    #[error("The referrer account does not belong to the referrer recorded with the bid.")]
+    ReferrerMismatch,
+
+    /// There is no active bid to increase.
+    #[error("There is no active bid to increase.")]
+    NoActiveBid,
+
+    /// Only single unit English and open edition bids can be increased.
+    #[error("Only single unit English and open edition bids can be increased.")]
+    CannotIncreaseBid,
+
+    /// The transfer authority is not approved to move the amount bid.
+    #[error("The transfer authority is not approved to move the amount bid.")]
+    AllowanceTooLow,
+
+    /// The auction mint does not have the decimals bids are refunded with.
+    #[error("The auction mint does not have the decimals bids are refunded with.")]
+    InvalidMintDecimals,
+
+    /// The bidder does not pass the auction's gate.
+    #[error("The bidder does not pass the auction's gate.")]
+    BidderNotAllowed,
+
+    /// The proof does not show the bidder is on the auction's allowlist.
+    #[error("The proof does not show the bidder is on the auction's allowlist.")]
+    InvalidAllowlistProof,
+
+    /// The auction has bids that could still win.
+    #[error("The auction has bids that could still win.")]
+    AuctionHasBids,
+
+    /// A buy now price needs a capped English or multi-unit auction with first price settlement.
+    #[error(
+        "A buy now price needs a capped English or multi-unit auction with first price settlement."
+    )]
+    InvalidBuyNowConfig,
 }
 
 impl PrintProgramError for AuctionError {
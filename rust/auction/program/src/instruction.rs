@@ -1,14 +1,21 @@
-use crate::PREFIX;
+use crate::{processor::SealedBid, BUY_NOW, PREFIX};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
+    hash::Hash,
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
     sysvar,
 };
 
 pub use crate::processor::{
-    cancel_bid::CancelBidArgs, claim_bid::ClaimBidArgs, create_auction::CreateAuctionArgs,
-    end_auction::EndAuctionArgs, place_bid::PlaceBidArgs, start_auction::StartAuctionArgs,
+    cancel_auction::CancelAuctionArgs, cancel_bid::CancelBidArgs, claim_bid::ClaimBidArgs,
+    claim_forfeited_bid::ClaimForfeitedBidArgs, create_auction::CreateAuctionArgs,
+    create_bid_history::CreateBidHistoryArgs, create_fee_config::CreateFeeConfigArgs,
+    end_auction::EndAuctionArgs, join_allowlist::JoinAllowlistArgs, place_bid::PlaceBidArgs,
+    place_bid::PlaceMultiUnitBidArgs, place_bid::PlaceSealedBidArgs,
+    refund_losers::RefundLosersArgs, reveal_bid::RevealBidArgs, settle_auction::SettleAuctionArgs,
+    start_auction::StartAuctionArgs, update_auction::UpdateAuctionArgs,
+    update_fee_config::UpdateFeeConfigArgs,
 };
 
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
@@ -25,6 +32,9 @@ pub enum AuctionInstruction {
     ///   8. `[]` Rent sysvar
     ///   9. `[]` System program
     ///   10. `[]` SPL Token Program
+    ///   11. `[]` The sealed bid account, required on sealed bid auctions.
+    ///   Last. `[writable]` The bid history account, required when the auction keeps one.
+    /// Native bids are refunded in lamports, 1 is then the bidder wallet and 3 the pot itself.
     CancelBid(CancelBidArgs),
 
     /// Create a new auction account bound to a resource, initially in a pending state.
@@ -32,6 +42,8 @@ pub enum AuctionInstruction {
     ///   1. `[writable]` Uninitialized auction account.
     ///   2. `[]` Rent sysvar
     ///   3. `[]` System account
+    ///   4. `[signer]` The authority on the auction
+    ///   5. `[]` The fee config, required when set in the args
     CreateAuction(CreateAuctionArgs),
 
     /// Move SPL tokens from winning bid to the destination account.
@@ -39,16 +51,23 @@ pub enum AuctionInstruction {
     ///   1. `[writable]` The bidder pot token account
     ///   2. `[]` The bidder pot pda account [seed of ['auction', program_id, auction key, bidder key]]
     ///   3. `[signer]` The authority on the auction
-    ///   4. `[]` The auction
+    ///   4. `[writable]` The auction
     ///   5. `[]` The bidder wallet
     ///   6. `[]` Token mint of the auction
     ///   7. `[]` Clock sysvar
     ///   8. `[]` Token program
-    ///   9. `[]` Bonfida vault account
-    ///   10. `[writable]` Optional referrer FIDA account
+    ///   9. `[writable]` Fee recipient token account
+    ///   10. `[writable]` Fee recipient wallet
+    ///   11. `[]` The fee config of the auction, ignored when it has none
+    ///   12. `[writable]` The bidders token account for the refund, second price, uniform price and multi-unit auctions only
+    ///   13. `[writable]` The referrer token account, required when the bid was placed with a referrer, takes the place of 12 on first price auctions
+    ///   Last. `[writable]` Optional buy now account of an auction still in a buy now state [seed of ['buy now', program_id, resource]], its lamports go to 10, which has to be the auction authority when the auction has no fee config
+    /// Native bids are paid out in lamports, 1 is then the pot itself, fees go to 10, and the
+    /// destination, refund and referrer accounts are wallets.
     ClaimBid(ClaimBidArgs),
 
-    /// Ends an auction, regardless of end timing conditions
+    /// Ends an auction, regardless of end timing conditions. An auction no bid beat the revealed
+    /// price floor of ends as ReserveNotMet instead.
     EndAuction(EndAuctionArgs),
 
     /// Start an inactive auction.
@@ -74,6 +93,11 @@ pub enum AuctionInstruction {
     ///   10. `[]` Rent sysvar
     ///   11. `[]` System program
     ///   12. `[]` SPL Token Program
+    ///   13. `[]` The buy now account, required on auctions still in a buy now state [seed of ['buy now', program_id, resource]]
+    ///   14. `[]` The gate pass, required on gated auctions: the bidder's allowlist pass [seed of ['auction', program_id, auction key, bidder key, 'allowlist']], or a token account of theirs holding the gate mint
+    ///   15. `[writable]` Optional referral registry of the referrer [seed of ['auction', program_id, referrer key, 'referral']]
+    ///   Last. `[writable]` The bid history account, required when the auction keeps one.
+    /// Native bids are paid in lamports from 0, 1 is then the bidder wallet and 3 the pot itself.
     PlaceBid(PlaceBidArgs),
 
     // Create a bidder pot account
@@ -87,10 +111,143 @@ pub enum AuctionInstruction {
     CreateBidderPot,
 
     // Close the bidder pot and auction account.
-    // The exclusive auction authority needs to sign.
-    CloseAuctionPot {
-        resource: Pubkey,
-    },
+    // The admin of the auction's fee config needs to sign, or its authority when it has none.
+    ///   0. `[writable]` The auction
+    ///   1. `[writable]` The bidder pot pda account
+    ///   2. `[writable]` The bidder wallet
+    ///   3. `[writable]` Destination of the lamports
+    ///   4. `[]` System program
+    ///   5. `[signer]` The fee config admin or the authority on the auction
+    ///   6. `[]` Token program
+    ///   7. `[writable]` The bidder pot token account
+    ///   8. `[]` The fee config of the auction
+    CloseAuctionPot { resource: Pubkey },
+
+    /// Commit to a hidden bid on a running sealed bid auction, depositing tokens to cover it.
+    ///   0-12. Same accounts as PlaceBid.
+    ///   13. `[writable]` The sealed bid account [seed of ['auction', program_id, auction key, bidder key, 'sealed']]
+    ///   14. `[]` The gate pass, required on gated auctions, see PlaceBid
+    ///   15. `[writable]` Optional referral registry of the referrer
+    ///   Last. `[writable]` The bid history account, required when the auction keeps one.
+    PlaceSealedBid(PlaceSealedBidArgs),
+
+    /// Reveal a sealed bid once bidding has closed, the deposit above the bid is refunded.
+    ///   0. `[signer]` The bidders primary account.
+    ///   1. `[writable]` The bidders token account they'll receive the excess deposit with
+    ///   2. `[]` The pot, containing a reference to the stored SPL token account.
+    ///   3. `[writable]` The pot SPL account.
+    ///   4. `[writable]` The sealed bid account.
+    ///   5. `[writable]` Auction account.
+    ///   6. `[]` Token mint, for transfer instructions and verification.
+    ///   7. `[]` Clock sysvar
+    ///   8. `[]` SPL Token Program
+    RevealBid(RevealBidArgs),
+
+    /// Settle a sealed bid that was never revealed, once the reveal phase is over. The forfeited
+    /// share of the deposit goes to the destination, the rest back to the bidder.
+    ///   0. `[writable]` The destination account
+    ///   1. `[writable]` The bidders token account
+    ///   2. `[writable]` The bidder pot token account
+    ///   3. `[writable]` The bidder pot pda account
+    ///   4. `[]` The sealed bid account
+    ///   5. `[writable]` The metadata account
+    ///   6. `[signer]` The authority on the auction
+    ///   7. `[]` The auction
+    ///   8. `[]` The bidder wallet
+    ///   9. `[]` Token mint of the auction
+    ///   10. `[]` Clock sysvar
+    ///   11. `[]` Token program
+    ClaimForfeitedBid(ClaimForfeitedBidArgs),
+
+    /// Add a bid history to an auction that hasn't started, recording every bid and cancellation.
+    ///   0. `[signer]` The authority on the auction
+    ///   1. `[writable, signer]` Payer
+    ///   2. `[writable]` The auction
+    ///   3. `[writable]` Uninitialized bid history account [seed of ['auction', program_id, auction key, 'history']]
+    ///   4. `[]` Rent sysvar
+    ///   5. `[]` System program
+    CreateBidHistory(CreateBidHistoryArgs),
+
+    /// Start an auction whose scheduled start time has passed, anyone can call this.
+    ///   0. `[writable]` Initialized auction account.
+    ///   1. `[]` Clock sysvar
+    StartScheduledAuction(StartAuctionArgs),
+
+    /// End an auction whose end time has passed, anyone can call this. Auctions with a blinded
+    /// price floor are ended by the authority with EndAuction.
+    ///   0. `[writable]` Initialized auction account.
+    ///   1. `[]` Clock sysvar
+    SettleAuction(SettleAuctionArgs),
+
+    /// Bid a price per unit for several units of a running multi-unit auction.
+    ///   0-12. Same accounts as PlaceBid.
+    ///   13. `[]` The gate pass, required on gated auctions, see PlaceBid
+    ///   14. `[writable]` Optional referral registry of the referrer
+    ///   Last. `[writable]` The bid history account, required when the auction keeps one.
+    PlaceMultiUnitBid(PlaceMultiUnitBidArgs),
+
+    /// Create the fee config of an operator, auctions created with it pay its fees on claim.
+    ///   0. `[signer]` The admin of the fee config
+    ///   1. `[writable, signer]` Payer
+    ///   2. `[writable]` Uninitialized fee config account [seed of ['auction', program_id, admin key, 'fee_config']]
+    ///   3. `[]` Rent sysvar
+    ///   4. `[]` System program
+    CreateFeeConfig(CreateFeeConfigArgs),
+
+    /// Change the admin, recipient or fee schedule of a fee config.
+    ///   0. `[signer]` The current admin of the fee config
+    ///   1. `[writable]` The fee config
+    UpdateFeeConfig(UpdateFeeConfigArgs),
+
+    /// Register a referrer, bids placed with the registry pay them a share of the fees.
+    ///   0. `[signer]` The referrer wallet
+    ///   1. `[writable, signer]` Payer
+    ///   2. `[writable]` Uninitialized referral registry [seed of ['auction', program_id, referrer key, 'referral']]
+    ///   3. `[]` Rent sysvar
+    ///   4. `[]` System program
+    RegisterReferrer,
+
+    /// Raise an active bid to the given amount, the pot is topped up by the difference.
+    ///   0-15. Same accounts as PlaceBid.
+    ///   Last. `[writable]` The bid history account, required when the auction keeps one.
+    IncreaseBid(PlaceBidArgs),
+
+    /// Refund losing bids of an ended auction to the accounts they were paid from, anyone can call
+    /// this. Winners and bids already cancelled are skipped.
+    ///   0. `[]` The auction
+    ///   1. `[]` Token mint of the auction
+    ///   2. `[]` Clock sysvar
+    ///   3. `[]` Token program
+    ///   Then for each bid:
+    ///   - `[writable]` The bidder pot pda account
+    ///   - `[writable]` The bidder pot token account, the pot itself for native bids
+    ///   - `[writable]` The bidder metadata account
+    ///   - `[writable]` The token account recorded in the pot, the bidder wallet for native bids
+    ///   Last. `[writable]` The bid history account, required when the auction keeps one.
+    RefundLosers(RefundLosersArgs),
+
+    /// Prove a wallet is on an auction's allowlist, creating the pass its bids need.
+    ///   0. `[signer]` The bidder wallet
+    ///   1. `[writable, signer]` Payer
+    ///   2. `[]` The auction
+    ///   3. `[writable]` Uninitialized allowlist pass [seed of ['auction', program_id, auction key, bidder key, 'allowlist']]
+    ///   4. `[]` Rent sysvar
+    ///   5. `[]` System program
+    JoinAllowlist(JoinAllowlistArgs),
+
+    /// Cancel an auction that has no bid that could still win, started or not. Losing and
+    /// cancelled bids can then be pulled out or refunded with RefundLosers.
+    ///   0. `[signer]` The authority on the auction
+    ///   1. `[writable]` Initialized auction account.
+    ///   2. `[]` Clock sysvar
+    CancelAuction(CancelAuctionArgs),
+
+    /// Replace the end time, gap, price floor and buy now price of an auction that hasn't started.
+    ///   0. `[signer]` The authority on the auction, writable when 3 is passed
+    ///   1. `[writable]` Initialized auction account.
+    ///   2. `[]` Clock sysvar
+    ///   3. `[writable]` The buy now account, required on auctions still in a buy now state [seed of ['buy now', program_id, resource]], its lamports go to 0
+    UpdateAuction(UpdateAuctionArgs),
 }
 
 /// Creates an CreateAuction instruction.
@@ -102,20 +259,20 @@ pub fn close_auction_pot(
     destination: Pubkey,
     system: Pubkey,
     authority: Pubkey,
-    bonfida_vault: Pubkey,
+    fee_config: Pubkey,
     bidder_pot_token: Pubkey,
     resource: Pubkey,
 ) -> Instruction {
     let mut accounts = vec![
         AccountMeta::new(auction, false),
         AccountMeta::new(bidder_pot, false),
-        AccountMeta::new_readonly(bidder, false),
+        AccountMeta::new(bidder, false),
         AccountMeta::new(destination, false),
         AccountMeta::new_readonly(system, false),
         AccountMeta::new_readonly(authority, true),
         AccountMeta::new_readonly(spl_token::ID, false),
         AccountMeta::new(bidder_pot_token, false),
-        AccountMeta::new(bonfida_vault, false),
+        AccountMeta::new_readonly(fee_config, false),
     ];
 
     Instruction {
@@ -132,7 +289,6 @@ pub fn create_auction_instruction(
     program_id: Pubkey,
     creator_pubkey: Pubkey,
     authority_pubkey: Pubkey,
-    buy_now_pubkey: Option<Pubkey>,
     args: CreateAuctionArgs,
 ) -> Instruction {
     let seeds = &[
@@ -148,8 +304,8 @@ pub fn create_auction_instruction(
         AccountMeta::new_readonly(solana_program::system_program::id(), false),
         AccountMeta::new_readonly(authority_pubkey, true),
     ];
-    if buy_now_pubkey.is_some() {
-        accounts.push(AccountMeta::new(buy_now_pubkey.unwrap(), false));
+    if let Some(fee_config) = args.fee_config {
+        accounts.push(AccountMeta::new_readonly(fee_config, false));
     }
     Instruction {
         program_id,
@@ -214,7 +370,9 @@ pub fn place_bid_instruction(
     token_mint_pubkey: Pubkey,
     transfer_authority: Pubkey,
     payer: Pubkey,
-    buy_now_pubkey: Option<Pubkey>,
+    gate_pass_pubkey: Option<Pubkey>,
+    referrer_pubkey: Option<Pubkey>,
+    bid_history_pubkey: Option<Pubkey>,
     args: PlaceBidArgs,
 ) -> Instruction {
     // Derive Auction Key
@@ -260,8 +418,15 @@ pub fn place_bid_instruction(
         AccountMeta::new_readonly(spl_token::id(), false),
     ];
 
-    if buy_now_pubkey.is_some() {
-        accounts.push(AccountMeta::new(buy_now_pubkey.unwrap(), false));
+    if let Some(gate_pass_pubkey) = gate_pass_pubkey {
+        accounts.push(AccountMeta::new_readonly(gate_pass_pubkey, false));
+    }
+    if let Some(referrer_pubkey) = referrer_pubkey {
+        let registry_pubkey = find_referral_registry_address(&program_id, &referrer_pubkey);
+        accounts.push(AccountMeta::new(registry_pubkey, false));
+    }
+    if let Some(bid_history_pubkey) = bid_history_pubkey {
+        accounts.push(AccountMeta::new(bid_history_pubkey, false));
     }
 
     Instruction {
@@ -278,6 +443,7 @@ pub fn cancel_bid_instruction(
     bidder_token_pubkey: Pubkey,
     bidder_pot_token_pubkey: Pubkey,
     token_mint_pubkey: Pubkey,
+    bid_history_pubkey: Option<Pubkey>,
     args: CancelBidArgs,
 ) -> Instruction {
     // Derive Auction Key
@@ -307,21 +473,26 @@ pub fn cancel_bid_instruction(
     ];
     let (bidder_meta_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
 
+    let mut accounts = vec![
+        AccountMeta::new(bidder_pubkey, true),
+        AccountMeta::new(bidder_token_pubkey, false),
+        AccountMeta::new(bidder_pot_pubkey, false),
+        AccountMeta::new(bidder_pot_token_pubkey, false),
+        AccountMeta::new(bidder_meta_pubkey, false),
+        AccountMeta::new(auction_pubkey, false),
+        AccountMeta::new(token_mint_pubkey, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    if let Some(bid_history_pubkey) = bid_history_pubkey {
+        accounts.push(AccountMeta::new(bid_history_pubkey, false));
+    }
+
     Instruction {
         program_id,
-        accounts: vec![
-            AccountMeta::new(bidder_pubkey, true),
-            AccountMeta::new(bidder_token_pubkey, false),
-            AccountMeta::new(bidder_pot_pubkey, false),
-            AccountMeta::new(bidder_pot_token_pubkey, false),
-            AccountMeta::new(bidder_meta_pubkey, false),
-            AccountMeta::new(auction_pubkey, false),
-            AccountMeta::new(token_mint_pubkey, false),
-            AccountMeta::new_readonly(sysvar::clock::id(), false),
-            AccountMeta::new_readonly(sysvar::rent::id(), false),
-            AccountMeta::new_readonly(solana_program::system_program::id(), false),
-            AccountMeta::new_readonly(spl_token::id(), false),
-        ],
+        accounts,
         data: AuctionInstruction::CancelBid(args).try_to_vec().unwrap(),
     }
 }
@@ -357,9 +528,10 @@ pub fn claim_bid_instruction(
     bidder_pubkey: Pubkey,
     bidder_pot_token_pubkey: Pubkey,
     token_mint_pubkey: Pubkey,
-    bonfida_vault: Pubkey,
-    buy_now: Pubkey,
-    bonfida_sol_vault: Pubkey,
+    fee_recipient_token: Pubkey,
+    fee_recipient: Pubkey,
+    fee_config: Pubkey,
+    bidder_token: Option<Pubkey>,
     referrer: Option<Pubkey>,
     args: ClaimBidArgs,
 ) -> Instruction {
@@ -385,16 +557,19 @@ pub fn claim_bid_instruction(
         AccountMeta::new(bidder_pot_token_pubkey, false),
         AccountMeta::new(bidder_pot_pubkey, false),
         AccountMeta::new_readonly(authority_pubkey, true),
-        AccountMeta::new_readonly(auction_pubkey, false),
+        AccountMeta::new(auction_pubkey, false),
         AccountMeta::new_readonly(bidder_pubkey, false),
         AccountMeta::new_readonly(token_mint_pubkey, false),
         AccountMeta::new_readonly(sysvar::clock::id(), false),
         AccountMeta::new_readonly(spl_token::id(), false),
-        AccountMeta::new(bonfida_vault, false),
-        AccountMeta::new(buy_now, false),
-        AccountMeta::new(bonfida_sol_vault, false),
+        AccountMeta::new(fee_recipient_token, false),
+        AccountMeta::new(fee_recipient, false),
+        AccountMeta::new_readonly(fee_config, false),
     ];
 
+    if let Some(bidder_token) = bidder_token {
+        accounts.push(AccountMeta::new(bidder_token, false))
+    }
     if let Some(referrer) = referrer {
         accounts.push(AccountMeta::new(referrer, false))
     }
@@ -405,3 +580,601 @@ pub fn claim_bid_instruction(
         data: AuctionInstruction::ClaimBid(args).try_to_vec().unwrap(),
     }
 }
+
+/// Derives the auction, bidder pot, bidder metadata and sealed bid keys of a bidder.
+fn find_bidder_addresses(
+    program_id: &Pubkey,
+    resource: &Pubkey,
+    bidder_pubkey: &Pubkey,
+) -> (Pubkey, Pubkey, Pubkey, Pubkey) {
+    let seeds = &[PREFIX.as_bytes(), program_id.as_ref(), resource.as_ref()];
+    let (auction_pubkey, _) = Pubkey::find_program_address(seeds, program_id);
+
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        bidder_pubkey.as_ref(),
+    ];
+    let (bidder_pot_pubkey, _) = Pubkey::find_program_address(seeds, program_id);
+
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        bidder_pubkey.as_ref(),
+        "metadata".as_bytes(),
+    ];
+    let (bidder_meta_pubkey, _) = Pubkey::find_program_address(seeds, program_id);
+
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        bidder_pubkey.as_ref(),
+        "sealed".as_bytes(),
+    ];
+    let (sealed_bid_pubkey, _) = Pubkey::find_program_address(seeds, program_id);
+
+    (
+        auction_pubkey,
+        bidder_pot_pubkey,
+        bidder_meta_pubkey,
+        sealed_bid_pubkey,
+    )
+}
+
+/// Commitment to pass in PlaceSealedBidArgs for a bid of `amount` on the auction of `resource`,
+/// revealed later with the same amount and salt.
+pub fn sealed_bid_commitment(
+    program_id: &Pubkey,
+    resource: &Pubkey,
+    bidder_pubkey: &Pubkey,
+    amount: u64,
+    salt: u64,
+) -> Hash {
+    let seeds = &[PREFIX.as_bytes(), program_id.as_ref(), resource.as_ref()];
+    let (auction_pubkey, _) = Pubkey::find_program_address(seeds, program_id);
+    SealedBid::commit(&auction_pubkey, bidder_pubkey, amount, salt)
+}
+
+/// Creates a PlaceSealedBid instruction.
+pub fn place_sealed_bid_instruction(
+    program_id: Pubkey,
+    bidder_pubkey: Pubkey,
+    bidder_token_pubkey: Pubkey,
+    bidder_pot_token_pubkey: Pubkey,
+    token_mint_pubkey: Pubkey,
+    transfer_authority: Pubkey,
+    payer: Pubkey,
+    gate_pass_pubkey: Option<Pubkey>,
+    referrer_pubkey: Option<Pubkey>,
+    bid_history_pubkey: Option<Pubkey>,
+    args: PlaceSealedBidArgs,
+) -> Instruction {
+    let (auction_pubkey, bidder_pot_pubkey, bidder_meta_pubkey, sealed_bid_pubkey) =
+        find_bidder_addresses(&program_id, &args.resource, &bidder_pubkey);
+
+    let mut accounts = vec![
+        AccountMeta::new(bidder_pubkey, true),
+        AccountMeta::new(bidder_token_pubkey, false),
+        AccountMeta::new(bidder_pot_pubkey, false),
+        AccountMeta::new(bidder_pot_token_pubkey, false),
+        AccountMeta::new(bidder_meta_pubkey, false),
+        AccountMeta::new(auction_pubkey, false),
+        AccountMeta::new(token_mint_pubkey, false),
+        AccountMeta::new_readonly(transfer_authority, true),
+        AccountMeta::new_readonly(payer, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(sealed_bid_pubkey, false),
+    ];
+    if let Some(gate_pass_pubkey) = gate_pass_pubkey {
+        accounts.push(AccountMeta::new_readonly(gate_pass_pubkey, false));
+    }
+    if let Some(referrer_pubkey) = referrer_pubkey {
+        let registry_pubkey = find_referral_registry_address(&program_id, &referrer_pubkey);
+        accounts.push(AccountMeta::new(registry_pubkey, false));
+    }
+    if let Some(bid_history_pubkey) = bid_history_pubkey {
+        accounts.push(AccountMeta::new(bid_history_pubkey, false));
+    }
+
+    Instruction {
+        program_id,
+        accounts,
+        data: AuctionInstruction::PlaceSealedBid(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Creates a RevealBid instruction.
+pub fn reveal_bid_instruction(
+    program_id: Pubkey,
+    bidder_pubkey: Pubkey,
+    bidder_token_pubkey: Pubkey,
+    bidder_pot_token_pubkey: Pubkey,
+    token_mint_pubkey: Pubkey,
+    args: RevealBidArgs,
+) -> Instruction {
+    let (auction_pubkey, bidder_pot_pubkey, _, sealed_bid_pubkey) =
+        find_bidder_addresses(&program_id, &args.resource, &bidder_pubkey);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(bidder_pubkey, true),
+            AccountMeta::new(bidder_token_pubkey, false),
+            AccountMeta::new_readonly(bidder_pot_pubkey, false),
+            AccountMeta::new(bidder_pot_token_pubkey, false),
+            AccountMeta::new(sealed_bid_pubkey, false),
+            AccountMeta::new(auction_pubkey, false),
+            AccountMeta::new_readonly(token_mint_pubkey, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: AuctionInstruction::RevealBid(args).try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a CancelBid instruction for a sealed bid auction.
+pub fn cancel_sealed_bid_instruction(
+    program_id: Pubkey,
+    bidder_pubkey: Pubkey,
+    bidder_token_pubkey: Pubkey,
+    bidder_pot_token_pubkey: Pubkey,
+    token_mint_pubkey: Pubkey,
+    bid_history_pubkey: Option<Pubkey>,
+    args: CancelBidArgs,
+) -> Instruction {
+    let (_, _, _, sealed_bid_pubkey) =
+        find_bidder_addresses(&program_id, &args.resource, &bidder_pubkey);
+    let mut instruction = cancel_bid_instruction(
+        program_id,
+        bidder_pubkey,
+        bidder_token_pubkey,
+        bidder_pot_token_pubkey,
+        token_mint_pubkey,
+        None,
+        args,
+    );
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(sealed_bid_pubkey, false));
+    if let Some(bid_history_pubkey) = bid_history_pubkey {
+        instruction
+            .accounts
+            .push(AccountMeta::new(bid_history_pubkey, false));
+    }
+    instruction
+}
+
+/// Creates a ClaimForfeitedBid instruction.
+pub fn claim_forfeited_bid_instruction(
+    program_id: Pubkey,
+    destination_pubkey: Pubkey,
+    bidder_token_pubkey: Pubkey,
+    bidder_pot_token_pubkey: Pubkey,
+    authority_pubkey: Pubkey,
+    bidder_pubkey: Pubkey,
+    token_mint_pubkey: Pubkey,
+    args: ClaimForfeitedBidArgs,
+) -> Instruction {
+    let (auction_pubkey, bidder_pot_pubkey, bidder_meta_pubkey, sealed_bid_pubkey) =
+        find_bidder_addresses(&program_id, &args.resource, &bidder_pubkey);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(destination_pubkey, false),
+            AccountMeta::new(bidder_token_pubkey, false),
+            AccountMeta::new(bidder_pot_token_pubkey, false),
+            AccountMeta::new(bidder_pot_pubkey, false),
+            AccountMeta::new_readonly(sealed_bid_pubkey, false),
+            AccountMeta::new(bidder_meta_pubkey, false),
+            AccountMeta::new_readonly(authority_pubkey, true),
+            AccountMeta::new_readonly(auction_pubkey, false),
+            AccountMeta::new_readonly(bidder_pubkey, false),
+            AccountMeta::new_readonly(token_mint_pubkey, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: AuctionInstruction::ClaimForfeitedBid(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Derives the bid history key of an auction.
+pub fn find_bid_history_address(program_id: &Pubkey, auction_pubkey: &Pubkey) -> Pubkey {
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        "history".as_bytes(),
+    ];
+    Pubkey::find_program_address(seeds, program_id).0
+}
+
+/// Creates a CreateBidHistory instruction.
+pub fn create_bid_history_instruction(
+    program_id: Pubkey,
+    authority_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+    args: CreateBidHistoryArgs,
+) -> Instruction {
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+    ];
+    let (auction_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+    let bid_history_pubkey = find_bid_history_address(&program_id, &auction_pubkey);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(authority_pubkey, true),
+            AccountMeta::new(payer_pubkey, true),
+            AccountMeta::new(auction_pubkey, false),
+            AccountMeta::new(bid_history_pubkey, false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ],
+        data: AuctionInstruction::CreateBidHistory(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Creates a StartScheduledAuction instruction.
+pub fn start_scheduled_auction_instruction(
+    program_id: Pubkey,
+    args: StartAuctionArgs,
+) -> Instruction {
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+    ];
+    let (auction_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(auction_pubkey, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: AuctionInstruction::StartScheduledAuction(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Creates a SettleAuction instruction.
+pub fn settle_auction_instruction(program_id: Pubkey, args: SettleAuctionArgs) -> Instruction {
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+    ];
+    let (auction_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(auction_pubkey, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: AuctionInstruction::SettleAuction(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Creates a PlaceMultiUnitBid instruction.
+pub fn place_multi_unit_bid_instruction(
+    program_id: Pubkey,
+    bidder_pubkey: Pubkey,
+    bidder_token_pubkey: Pubkey,
+    bidder_pot_token_pubkey: Pubkey,
+    token_mint_pubkey: Pubkey,
+    transfer_authority: Pubkey,
+    payer: Pubkey,
+    gate_pass_pubkey: Option<Pubkey>,
+    referrer_pubkey: Option<Pubkey>,
+    bid_history_pubkey: Option<Pubkey>,
+    args: PlaceMultiUnitBidArgs,
+) -> Instruction {
+    let mut instruction = place_bid_instruction(
+        program_id,
+        bidder_pubkey,
+        bidder_token_pubkey,
+        bidder_pot_token_pubkey,
+        token_mint_pubkey,
+        transfer_authority,
+        payer,
+        gate_pass_pubkey,
+        referrer_pubkey,
+        bid_history_pubkey,
+        PlaceBidArgs {
+            amount: args.amount,
+            resource: args.resource,
+        },
+    );
+    instruction.data = AuctionInstruction::PlaceMultiUnitBid(args)
+        .try_to_vec()
+        .unwrap();
+    instruction
+}
+
+/// Derives the fee config key of the admin that creates it.
+pub fn find_fee_config_address(program_id: &Pubkey, admin_pubkey: &Pubkey) -> Pubkey {
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        admin_pubkey.as_ref(),
+        "fee_config".as_bytes(),
+    ];
+    Pubkey::find_program_address(seeds, program_id).0
+}
+
+/// Creates a CreateFeeConfig instruction.
+pub fn create_fee_config_instruction(
+    program_id: Pubkey,
+    admin_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+    args: CreateFeeConfigArgs,
+) -> Instruction {
+    let fee_config_pubkey = find_fee_config_address(&program_id, &admin_pubkey);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin_pubkey, true),
+            AccountMeta::new(payer_pubkey, true),
+            AccountMeta::new(fee_config_pubkey, false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ],
+        data: AuctionInstruction::CreateFeeConfig(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Creates an UpdateFeeConfig instruction.
+pub fn update_fee_config_instruction(
+    program_id: Pubkey,
+    admin_pubkey: Pubkey,
+    fee_config_pubkey: Pubkey,
+    args: UpdateFeeConfigArgs,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin_pubkey, true),
+            AccountMeta::new(fee_config_pubkey, false),
+        ],
+        data: AuctionInstruction::UpdateFeeConfig(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Derives the buy now account key of an auction created before the buy now price moved into
+/// AuctionData.
+pub fn find_buy_now_address(program_id: &Pubkey, resource: &Pubkey) -> Pubkey {
+    let seeds = &[BUY_NOW.as_bytes(), program_id.as_ref(), resource.as_ref()];
+    Pubkey::find_program_address(seeds, program_id).0
+}
+
+/// Derives the referral registry key of a referrer.
+pub fn find_referral_registry_address(program_id: &Pubkey, referrer_pubkey: &Pubkey) -> Pubkey {
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        referrer_pubkey.as_ref(),
+        "referral".as_bytes(),
+    ];
+    Pubkey::find_program_address(seeds, program_id).0
+}
+
+/// Creates a RegisterReferrer instruction.
+pub fn register_referrer_instruction(
+    program_id: Pubkey,
+    referrer_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+) -> Instruction {
+    let registry_pubkey = find_referral_registry_address(&program_id, &referrer_pubkey);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(referrer_pubkey, true),
+            AccountMeta::new(payer_pubkey, true),
+            AccountMeta::new(registry_pubkey, false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ],
+        data: AuctionInstruction::RegisterReferrer.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates an IncreaseBid instruction.
+pub fn increase_bid_instruction(
+    program_id: Pubkey,
+    bidder_pubkey: Pubkey,
+    bidder_token_pubkey: Pubkey,
+    bidder_pot_token_pubkey: Pubkey,
+    token_mint_pubkey: Pubkey,
+    transfer_authority: Pubkey,
+    payer: Pubkey,
+    gate_pass_pubkey: Option<Pubkey>,
+    referrer_pubkey: Option<Pubkey>,
+    bid_history_pubkey: Option<Pubkey>,
+    args: PlaceBidArgs,
+) -> Instruction {
+    let mut instruction = place_bid_instruction(
+        program_id,
+        bidder_pubkey,
+        bidder_token_pubkey,
+        bidder_pot_token_pubkey,
+        token_mint_pubkey,
+        transfer_authority,
+        payer,
+        gate_pass_pubkey,
+        referrer_pubkey,
+        bid_history_pubkey,
+        args.clone(),
+    );
+    instruction.data = AuctionInstruction::IncreaseBid(args).try_to_vec().unwrap();
+    instruction
+}
+
+/// Creates a RefundLosers instruction, each refund names the bidder wallet, their pot token
+/// account and the account their bid goes back to.
+pub fn refund_losers_instruction(
+    program_id: Pubkey,
+    token_mint_pubkey: Pubkey,
+    refunds: &[(Pubkey, Pubkey, Pubkey)],
+    bid_history_pubkey: Option<Pubkey>,
+    args: RefundLosersArgs,
+) -> Instruction {
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+    ];
+    let (auction_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(auction_pubkey, false),
+        AccountMeta::new_readonly(token_mint_pubkey, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    for (bidder_pubkey, bidder_pot_token_pubkey, destination_pubkey) in refunds {
+        let (_, bidder_pot_pubkey, bidder_meta_pubkey, _) =
+            find_bidder_addresses(&program_id, &args.resource, bidder_pubkey);
+        accounts.push(AccountMeta::new(bidder_pot_pubkey, false));
+        accounts.push(AccountMeta::new(*bidder_pot_token_pubkey, false));
+        accounts.push(AccountMeta::new(bidder_meta_pubkey, false));
+        accounts.push(AccountMeta::new(*destination_pubkey, false));
+    }
+    if let Some(bid_history_pubkey) = bid_history_pubkey {
+        accounts.push(AccountMeta::new(bid_history_pubkey, false));
+    }
+
+    Instruction {
+        program_id,
+        accounts,
+        data: AuctionInstruction::RefundLosers(args).try_to_vec().unwrap(),
+    }
+}
+
+/// Derives the allowlist pass key of a bidder on an auction.
+pub fn find_allowlist_pass_address(
+    program_id: &Pubkey,
+    auction_pubkey: &Pubkey,
+    bidder_pubkey: &Pubkey,
+) -> Pubkey {
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        auction_pubkey.as_ref(),
+        bidder_pubkey.as_ref(),
+        "allowlist".as_bytes(),
+    ];
+    Pubkey::find_program_address(seeds, program_id).0
+}
+
+/// Creates a JoinAllowlist instruction.
+pub fn join_allowlist_instruction(
+    program_id: Pubkey,
+    bidder_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+    args: JoinAllowlistArgs,
+) -> Instruction {
+    let (auction_pubkey, _, _, _) =
+        find_bidder_addresses(&program_id, &args.resource, &bidder_pubkey);
+    let pass_pubkey = find_allowlist_pass_address(&program_id, &auction_pubkey, &bidder_pubkey);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(bidder_pubkey, true),
+            AccountMeta::new(payer_pubkey, true),
+            AccountMeta::new_readonly(auction_pubkey, false),
+            AccountMeta::new(pass_pubkey, false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ],
+        data: AuctionInstruction::JoinAllowlist(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Creates a CancelAuction instruction.
+pub fn cancel_auction_instruction(
+    program_id: Pubkey,
+    authority_pubkey: Pubkey,
+    args: CancelAuctionArgs,
+) -> Instruction {
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+    ];
+    let (auction_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(authority_pubkey, true),
+            AccountMeta::new(auction_pubkey, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: AuctionInstruction::CancelAuction(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Creates an UpdateAuction instruction, passing the buy now account of an auction still in a buy
+/// now state.
+pub fn update_auction_instruction(
+    program_id: Pubkey,
+    authority_pubkey: Pubkey,
+    buy_now_pubkey: Option<Pubkey>,
+    args: UpdateAuctionArgs,
+) -> Instruction {
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+    ];
+    let (auction_pubkey, _) = Pubkey::find_program_address(seeds, &program_id);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(authority_pubkey, true),
+        AccountMeta::new(auction_pubkey, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    if let Some(buy_now_pubkey) = buy_now_pubkey {
+        accounts[0].is_writable = true;
+        accounts.push(AccountMeta::new(buy_now_pubkey, false));
+    }
+
+    Instruction {
+        program_id,
+        accounts,
+        data: AuctionInstruction::UpdateAuction(args)
+            .try_to_vec()
+            .unwrap(),
+    }
+}
@@ -1,6 +1,6 @@
 #![allow(warnings)]
 
-mod errors;
+pub mod errors;
 pub mod utils;
 
 pub mod entrypoint;
@@ -9,8 +9,9 @@ pub mod processor;
 
 /// Prefix used in PDA derivations to avoid collisions with other programs.
 pub const PREFIX: &str = "auction";
+/// Prefix of the buy now accounts of auctions created before the price moved into AuctionData.
 pub const BUY_NOW: &str = "buy now";
-pub const BONFIDA_SOL_VAULT: &str = "GcWEQ9K78FV7LEHteFVciYApERk5YvQuFDQPk1yYJVXi";
-pub const REF_SHARE: u64 = 20;
+/// Decimals of the mints bids are taken in, refunds are transfers checked against them.
+pub const MINT_DECIMALS: u8 = 6; // USDC and FIDA decimals are 6
 
 solana_program::declare_id!("AVWV7vdWbLqXiLKFaP19GhYurhwxaLp2qRBSjT5tR5vT");
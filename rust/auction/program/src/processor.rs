@@ -1,38 +1,67 @@
-use crate::errors::AuctionError;
+use crate::{
+    errors::AuctionError,
+    utils::{assert_derivation, assert_initialized, assert_owned_by, transfer_lamports},
+    BUY_NOW, PREFIX,
+};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::AccountInfo,
+    borsh::try_from_slice_unchecked,
     clock::{Slot, UnixTimestamp},
     entrypoint::ProgramResult,
-    hash::Hash,
+    hash::{self, Hash},
     msg,
     program_error::ProgramError,
     pubkey::Pubkey,
 };
+use spl_token::state::Account;
+use std::mem;
 pub mod close_auction_pot;
 use crate::processor::close_auction_pot::close_auction_pot;
 
 // Declare submodules, each contains a single handler for each instruction variant in the program.
+pub mod cancel_auction;
 pub mod cancel_bid;
 pub mod claim_bid;
+pub mod claim_forfeited_bid;
 pub mod create_auction;
+pub mod create_bid_history;
 pub mod create_bidder_pot;
+pub mod create_fee_config;
 pub mod end_auction;
+pub mod join_allowlist;
 pub mod place_bid;
+pub mod refund_losers;
+pub mod register_referrer;
+pub mod reveal_bid;
 pub mod set_authority;
+pub mod settle_auction;
 pub mod start_auction;
+pub mod start_scheduled_auction;
+pub mod update_auction;
+pub mod update_fee_config;
 
 // Re-export submodules handlers + associated types for other programs to consume.
+pub use cancel_auction::*;
 pub use cancel_bid::*;
 pub use claim_bid::*;
+pub use claim_forfeited_bid::*;
 pub use create_auction::*;
+pub use create_bid_history::*;
 pub use create_bidder_pot::*;
+pub use create_fee_config::*;
 pub use end_auction::*;
+pub use join_allowlist::*;
 pub use place_bid::*;
+pub use refund_losers::*;
+pub use register_referrer::*;
+pub use reveal_bid::*;
 pub use set_authority::*;
+pub use settle_auction::*;
 pub use start_auction::*;
-
-pub const EXCLUSIVE_AUCTION_AUTHORITY: &str = "jCebN34bUfdeUYJT13J1yG16XWQpt5PDx6Mse9GUqhR";
+pub use start_scheduled_auction::*;
+pub use update_auction::*;
+pub use update_fee_config::*;
 
 pub fn process_instruction(
     program_id: &Pubkey,
@@ -52,6 +81,29 @@ pub fn process_instruction(
         AuctionInstruction::CloseAuctionPot { resource } => {
             close_auction_pot(program_id, accounts, resource)
         }
+        AuctionInstruction::PlaceSealedBid(args) => place_sealed_bid(program_id, accounts, args),
+        AuctionInstruction::RevealBid(args) => reveal_bid(program_id, accounts, args),
+        AuctionInstruction::ClaimForfeitedBid(args) => {
+            claim_forfeited_bid(program_id, accounts, args)
+        }
+        AuctionInstruction::CreateBidHistory(args) => {
+            create_bid_history(program_id, accounts, args)
+        }
+        AuctionInstruction::StartScheduledAuction(args) => {
+            start_scheduled_auction(program_id, accounts, args)
+        }
+        AuctionInstruction::SettleAuction(args) => settle_auction(program_id, accounts, args),
+        AuctionInstruction::PlaceMultiUnitBid(args) => {
+            place_multi_unit_bid(program_id, accounts, args)
+        }
+        AuctionInstruction::CreateFeeConfig(args) => create_fee_config(program_id, accounts, args),
+        AuctionInstruction::UpdateFeeConfig(args) => update_fee_config(program_id, accounts, args),
+        AuctionInstruction::RegisterReferrer => register_referrer(program_id, accounts),
+        AuctionInstruction::IncreaseBid(args) => increase_bid(program_id, accounts, args),
+        AuctionInstruction::RefundLosers(args) => refund_losers(program_id, accounts, args),
+        AuctionInstruction::JoinAllowlist(args) => join_allowlist(program_id, accounts, args),
+        AuctionInstruction::CancelAuction(args) => cancel_auction(program_id, accounts, args),
+        AuctionInstruction::UpdateAuction(args) => update_auction(program_id, accounts, args),
     }
 }
 
@@ -73,7 +125,13 @@ pub enum PriceFloor {
 // usize in bid state.
 pub const BASE_AUCTION_DATA_SIZE: usize = 32 + 32 + 32 + 9 + 9 + 9 + 9 + 1 + 32 + 1 + 8 + 8;
 
+// Room reserved after the bid state for fields appended to AuctionData. Auctions created without
+// it read those fields as their defaults, and fields added later read the untouched room as zeros.
+pub const AUCTION_DATA_EXTENSION_SIZE: usize = 256;
+
+// Time after the last bid that auctions created before gaps moved their end time stay open for.
 pub const HARDCODED_GAP_TIME: i64 = 600;
+
 #[repr(C)]
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
 pub struct AuctionData {
@@ -102,41 +160,410 @@ pub struct AuctionData {
     pub state: AuctionState,
     /// Auction Bids, each user may have one bid open at a time.
     pub bid_state: BidState,
+    /// How winners are charged, see SettlementMode.
+    pub settlement: SettlementMode,
+    /// Highest bid pushed out of the winners, only tracked for second price settlement.
+    pub highest_losing_bid: u64,
+    /// Price paid by the top winner under second price settlement, or for every unit under
+    /// uniform price settlement, recorded on the first claim.
+    pub clearing_price: Option<u64>,
+    /// Most the gap can push the end past its original time, unlimited when not set.
+    pub max_gap_extension: Option<UnixTimestamp>,
+    /// Latest the end can be pushed to, set on start when the gap extension is capped.
+    pub hard_end_at: Option<UnixTimestamp>,
+    /// How much a new bid must beat the top bid by.
+    pub bid_increment: BidIncrement,
+    /// Whether bids and cancellations are recorded in the auction's BidHistory account.
+    pub has_bid_history: bool,
+    /// Time from which the auction counts as started without a StartAuction call, if scheduled.
+    pub start_at: Option<UnixTimestamp>,
+    /// Bids are paid in lamports held by the bidder pots themselves rather than in SPL tokens.
+    pub native_bids: bool,
+    /// FeeConfig of the operator running this auction, no fees are taken without one.
+    pub fee_config: Option<Pubkey>,
+    /// Schedule of the fee config when the auction was created, winners are charged by it even
+    /// if the config changes later.
+    pub fee_schedule: Option<FeeSchedule>,
+    /// Who may bid, anyone can when not set.
+    pub gate: Option<BidderGate>,
+    /// Price at which bids buy outright, higher bids are taken at it. The auction ends as soon as
+    /// such bids take everything on sale, the one item or every unit, and only they win.
+    pub buy_now_price: Option<u64>,
+    /// Whether bids within the gap push `ended_at` out. Auctions created before they did keep
+    /// their end time, and stay open until HARDCODED_GAP_TIME after the last bid past it.
+    pub extends_end: bool,
+}
+
+/// How much a winner pays out of their bid.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub enum SettlementMode {
+    /// Winners pay their own bid.
+    FirstPrice,
+    /// Vickrey settlement, each winner pays the next lower bid and is refunded the rest of their
+    /// bid. The lowest winner pays the highest losing bid, and no one pays below the price floor.
+    SecondPrice,
+    /// Every unit of a multi-unit auction sells at the same clearing price, set by the rule.
+    UniformPrice(ClearingRule),
+}
+
+/// Which bid sets the clearing price of a uniform price auction.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub enum ClearingRule {
+    /// The lowest bid that won a unit.
+    LowestWinningBid,
+    /// The highest bid that was pushed out of the winners, or the price floor if none was.
+    HighestLosingBid,
 }
 
 impl AuctionData {
+    pub fn from_account_info(a: &AccountInfo) -> Result<AuctionData, ProgramError> {
+        AuctionData::from_slice(&a.data.borrow())
+    }
+
+    /// Decodes an auction, fields appended after the bid state take their defaults on accounts
+    /// created before they existed.
+    pub fn from_slice(data: &[u8]) -> Result<AuctionData, ProgramError> {
+        let buf = &mut &data[..];
+        let mut auction = AuctionData {
+            authority: BorshDeserialize::deserialize(buf)?,
+            resource: BorshDeserialize::deserialize(buf)?,
+            token_mint: BorshDeserialize::deserialize(buf)?,
+            last_bid: BorshDeserialize::deserialize(buf)?,
+            ended_at: BorshDeserialize::deserialize(buf)?,
+            end_auction_at: BorshDeserialize::deserialize(buf)?,
+            end_auction_gap: BorshDeserialize::deserialize(buf)?,
+            price_floor: BorshDeserialize::deserialize(buf)?,
+            state: BorshDeserialize::deserialize(buf)?,
+            bid_state: BorshDeserialize::deserialize(buf)?,
+            settlement: SettlementMode::FirstPrice,
+            highest_losing_bid: 0,
+            clearing_price: None,
+            max_gap_extension: None,
+            hard_end_at: None,
+            bid_increment: BidIncrement::Default,
+            has_bid_history: false,
+            start_at: None,
+            native_bids: false,
+            fee_config: None,
+            fee_schedule: None,
+            gate: None,
+            buy_now_price: None,
+            extends_end: false,
+        };
+
+        if data.len() >= AuctionData::size(&auction.bid_state) {
+            auction.settlement = BorshDeserialize::deserialize(buf)?;
+            auction.highest_losing_bid = BorshDeserialize::deserialize(buf)?;
+            auction.clearing_price = BorshDeserialize::deserialize(buf)?;
+            auction.max_gap_extension = BorshDeserialize::deserialize(buf)?;
+            auction.hard_end_at = BorshDeserialize::deserialize(buf)?;
+            auction.bid_increment = BorshDeserialize::deserialize(buf)?;
+            auction.has_bid_history = BorshDeserialize::deserialize(buf)?;
+            auction.start_at = BorshDeserialize::deserialize(buf)?;
+            auction.native_bids = BorshDeserialize::deserialize(buf)?;
+            auction.fee_config = BorshDeserialize::deserialize(buf)?;
+            auction.fee_schedule = BorshDeserialize::deserialize(buf)?;
+            auction.gate = BorshDeserialize::deserialize(buf)?;
+            auction.buy_now_price = BorshDeserialize::deserialize(buf)?;
+            auction.extends_end = BorshDeserialize::deserialize(buf)?;
+        }
+
+        Ok(auction)
+    }
+
+    /// Writes the auction back, zeroing whatever a longer bid state left behind. Accounts created
+    /// without room for the appended fields only accept them at their defaults.
+    pub fn save(&self, a: &AccountInfo) -> ProgramResult {
+        let bytes = self.try_to_vec()?;
+        let mut data = a.data.borrow_mut();
+        let written = bytes.len().min(data.len());
+        if bytes[written..].iter().any(|b| *b != 0) {
+            return Err(AuctionError::AuctionAccountTooSmall.into());
+        }
+        data[..written].copy_from_slice(&bytes[..written]);
+        for byte in data[written..].iter_mut() {
+            *byte = 0;
+        }
+        Ok(())
+    }
+
+    /// Account size of an auction with this bid state.
+    pub fn size(bid_state: &BidState) -> usize {
+        BASE_AUCTION_DATA_SIZE + bid_state.space() + AUCTION_DATA_EXTENSION_SIZE
+    }
+
     pub fn ended(&self, now: UnixTimestamp) -> Result<bool, ProgramError> {
-        // If there is an end time specified, handle conditions.
-        return match (self.ended_at, self.end_auction_gap) {
-            // Both end and gap present, means a bid can still be placed post-auction if it is
-            // within the gap time.
-            (Some(end), Some(gap)) => {
-                // Check if the bid is within the gap between the last bidder.
-                if let Some(last) = self.last_bid {
-                    let next_bid_time = match last.checked_add(HARDCODED_GAP_TIME) {
-                        Some(val) => val,
-                        None => return Err(AuctionError::NumericalOverflowError.into()),
-                    };
-                    Ok(now > end && now > next_bid_time)
-                } else {
-                    Ok(now > end)
-                }
+        // Bids are refundable as soon as the reserve is known to be missed, the auction is off,
+        // or it sold out at the buy now price.
+        if matches!(
+            self.state,
+            AuctionState::ReserveNotMet | AuctionState::Cancelled
+        ) || self.bought_out()
+        {
+            return Ok(true);
+        }
+
+        // A sealed bid auction only ends once its reveal phase is over.
+        if let BidState::SealedBid { config, .. } = &self.bid_state {
+            return match self.ended_at {
+                Some(end) => match end.checked_add(config.reveal_period) {
+                    Some(reveal_end) => Ok(now > reveal_end),
+                    None => Err(AuctionError::NumericalOverflowError.into()),
+                },
+                None => Ok(false),
+            };
+        }
+
+        match (self.ended_at, self.end_auction_gap, self.last_bid) {
+            // The end time was never moved, a bid within the gap keeps the auction open instead.
+            (Some(end), Some(_), Some(last)) if !self.extends_end => {
+                let next_bid_time = match last.checked_add(HARDCODED_GAP_TIME) {
+                    Some(val) => val,
+                    None => return Err(AuctionError::NumericalOverflowError.into()),
+                };
+                Ok(now > end && now > next_bid_time)
             }
 
-            // Simply whether now has passed the end.
-            (Some(end), None) => Ok(now > end),
+            // Bids within the gap push the end time forward, so it is the only end condition.
+            (Some(end), _, _) => Ok(now > end),
 
             // No other end conditions.
             _ => Ok(false),
+        }
+    }
+
+    /// Sets the end time, and where the Dutch price decays from, relative to `start`.
+    pub fn begin(&mut self, start: UnixTimestamp) -> ProgramResult {
+        self.ended_at = match self.end_auction_at {
+            Some(end_auction_at) => match start.checked_add(end_auction_at) {
+                Some(val) => Some(val),
+                None => return Err(AuctionError::NumericalOverflowError.into()),
+            },
+            None => None,
         };
+
+        // Bids within the gap can push the end no further than this.
+        self.hard_end_at = match (self.ended_at, self.max_gap_extension) {
+            (Some(end), Some(cap)) => match end.checked_add(cap) {
+                Some(val) => Some(val),
+                None => return Err(AuctionError::NumericalOverflowError.into()),
+            },
+            _ => None,
+        };
+
+        if let BidState::DutchAuction { started_at, .. } = &mut self.bid_state {
+            *started_at = Some(start);
+        }
+        Ok(())
+    }
+
+    /// Starts an auction whose scheduled start time has passed, timing it from that start time
+    /// rather than from whenever this runs. Returns whether the auction was started.
+    pub fn start_if_scheduled(&mut self, now: UnixTimestamp) -> Result<bool, ProgramError> {
+        match (&self.state, self.start_at) {
+            (AuctionState::Created, Some(start_at))
+            | (AuctionState::BuyNowCreated, Some(start_at))
+                if now >= start_at =>
+            {
+                self.begin(start_at)?;
+                self.state = self.state.clone().start()?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Moves the end time to `end_auction_gap` seconds after a bid placed in the final window,
+    /// without going past the hard end when the extension is capped. Auctions created before the
+    /// gap moved the end time are left as they are.
+    pub fn extend_for_bid(&mut self, now: UnixTimestamp) -> ProgramResult {
+        if !self.extends_end {
+            return Ok(());
+        }
+        if let (Some(end), Some(gap)) = (self.ended_at, self.end_auction_gap) {
+            let extended = match now.checked_add(gap) {
+                Some(val) => val,
+                None => return Err(AuctionError::NumericalOverflowError.into()),
+            };
+            let extended = match self.hard_end_at {
+                Some(hard_end) => extended.min(hard_end),
+                None => extended,
+            };
+            if extended > end {
+                self.ended_at = Some(extended);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether bids can no longer be placed. Sealed bid auctions close to bids at their end time,
+    /// bidders then reveal until the reveal period is over.
+    pub fn bidding_closed(&self, now: UnixTimestamp) -> Result<bool, ProgramError> {
+        match (&self.bid_state, self.ended_at) {
+            (BidState::SealedBid { .. }, Some(end)) => Ok(now > end),
+            (BidState::SealedBid { .. }, None) => Ok(false),
+            _ => self.ended(now),
+        }
     }
 
     pub fn is_winner(&self, key: &Pubkey) -> Option<usize> {
-        let minimum = match self.price_floor {
+        self.bid_state.is_winner(key, self.minimum_price())
+    }
+
+    /// Whether bids at the buy now price took everything on sale, every winner slot of a capped
+    /// auction or every unit of a multi-unit one. Open editions never sell out, they can't be given
+    /// a buy now price and only a legacy buy now account ends one early, on its first purchase.
+    pub fn bought_out(&self) -> bool {
+        let price = match self.buy_now_price {
+            Some(price) => price,
+            None => return false,
+        };
+        let bought = self
+            .bid_state
+            .bids()
+            .iter()
+            .filter(|bid| bid.1 >= price)
+            .count();
+        match self.bid_state {
+            BidState::EnglishAuction { max, .. } | BidState::MultiUnit { max, .. } => bought >= max,
+            BidState::OpenEdition { .. } => false,
+            _ => bought > 0,
+        }
+    }
+
+    /// Buy now needs a capped auction whose winners pay their own bids, an English or multi-unit
+    /// auction with first price settlement. Open editions, and so uncapped auctions, are rejected
+    /// as they have no winner slots to sell out and only end by time.
+    pub fn validate_buy_now(&self) -> ProgramResult {
+        match (self.buy_now_price, &self.bid_state, &self.settlement) {
+            (None, _, _) => Ok(()),
+            (
+                Some(price),
+                BidState::EnglishAuction { .. } | BidState::MultiUnit { .. },
+                SettlementMode::FirstPrice,
+            ) if price > 0 => Ok(()),
+            _ => Err(AuctionError::InvalidBuyNowConfig.into()),
+        }
+    }
+
+    /// Whether the auction holds a bid that could still win. Open edition bids and sealed bid
+    /// commitments aren't kept in the bid state, so for those any bid ever placed counts.
+    pub fn has_active_bids(&self) -> bool {
+        match self.bid_state {
+            BidState::OpenEdition { .. } | BidState::SealedBid { .. } => self.last_bid.is_some(),
+            _ => !self.bid_state.bids().is_empty(),
+        }
+    }
+
+    /// Whether a bid beats the price floor. Open editions have no winners, so they can't miss it.
+    pub fn reserve_met(&self) -> bool {
+        match (&self.price_floor, &self.bid_state) {
+            (_, BidState::OpenEdition { .. }) => true,
+            (PriceFloor::MinimumPrice(min), bid_state) if min[0] > 0 => {
+                bid_state.bids().iter().any(|bid| bid.1 > min[0])
+            }
+            _ => true,
+        }
+    }
+
+    fn minimum_price(&self) -> u64 {
+        match self.price_floor {
             PriceFloor::MinimumPrice(min) => min[0],
             _ => 0,
-        };
-        self.bid_state.is_winner(key, minimum)
+        }
+    }
+
+    /// Keeps track of the highest bid pushed out of the winners, which the lowest winner pays
+    /// under second price settlement and which can set a uniform clearing price.
+    pub fn record_losing_bid(&mut self, bid: Option<Bid>) {
+        if let Some(bid) = bid {
+            if self.settlement != SettlementMode::FirstPrice {
+                self.highest_losing_bid = self.highest_losing_bid.max(bid.1);
+            }
+        }
+    }
+
+    /// Price every unit sells at under uniform price settlement, never below the price floor or
+    /// above the lowest winning bid.
+    pub fn uniform_clearing_price(&self, rule: &ClearingRule) -> Option<u64> {
+        let min = self.minimum_price();
+        let lowest_winning = self
+            .bid_state
+            .bids()
+            .iter()
+            .map(|b| b.1)
+            .filter(|price| *price > min)
+            .min()?;
+        match rule {
+            ClearingRule::LowestWinningBid => Some(lowest_winning),
+            ClearingRule::HighestLosingBid => {
+                Some(self.highest_losing_bid.max(min).min(lowest_winning))
+            }
+        }
+    }
+
+    /// Clearing price recorded when the auction is settled, first price auctions have none.
+    pub fn final_clearing_price(&self) -> Option<u64> {
+        match &self.settlement {
+            SettlementMode::FirstPrice => None,
+            SettlementMode::SecondPrice => self.top_settlement_price(),
+            SettlementMode::UniformPrice(rule) => self.uniform_clearing_price(rule),
+        }
+    }
+
+    /// Whether claiming a winning bid can leave part of the pot to refund, because the winner
+    /// pays less than their bid or won fewer units than they bid for.
+    pub fn refunds_on_claim(&self) -> bool {
+        self.settlement != SettlementMode::FirstPrice
+            || matches!(self.bid_state, BidState::MultiUnit { .. })
+    }
+
+    /// Amount a winner pays out of their bid. Under second price settlement that is the highest
+    /// bid ranking below theirs, never less than the price floor. In a multi-unit auction it is
+    /// the price of a unit times the units won.
+    pub fn settlement_price(&self, key: &Pubkey) -> Option<u64> {
+        let slot = self.is_winner(key)?;
+        let bid = self.bid_state.bids()[slot].1;
+        let price = self.unit_settlement_price(slot, bid)?;
+        match self.bid_state {
+            BidState::MultiUnit { .. } => {
+                price.checked_mul(self.bid_state.units_won(key, self.minimum_price()))
+            }
+            _ => Some(price),
+        }
+    }
+
+    fn unit_settlement_price(&self, slot: usize, bid: u64) -> Option<u64> {
+        let bids = self.bid_state.bids();
+        match &self.settlement {
+            SettlementMode::FirstPrice => Some(bid),
+            SettlementMode::UniformPrice(rule) => Some(self.uniform_clearing_price(rule)?.min(bid)),
+            SettlementMode::SecondPrice => {
+                // Equal bids rank by their position in the winner stack, the top bid is last.
+                let next = bids
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, b)| b.1 < bid || (b.1 == bid && *i < slot))
+                    .map(|(_, b)| b.1)
+                    .chain(std::iter::once(self.highest_losing_bid))
+                    .max()
+                    .unwrap_or(0);
+                Some(next.max(self.minimum_price()).min(bid))
+            }
+        }
+    }
+
+    /// Price paid by the top winner, the single clearing price of a one winner auction.
+    pub fn top_settlement_price(&self) -> Option<u64> {
+        let bids = self.bid_state.bids();
+        let top = bids
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.1 > self.minimum_price())
+            .max_by_key(|(i, b)| (b.1, *i))?;
+        self.settlement_price(&(top.1).0)
     }
 }
 
@@ -147,19 +574,19 @@ pub enum AuctionState {
     Created,
     Started,
     Ended,
+    /// The buy now states are only left by auctions created with a separate buy now account, see
+    /// BuyNowData. New auctions keep their buy now price in AuctionData.
     BuyNowCreated,
     BuyNowStarted,
     BuyNowEnded,
+    /// Ended without any bid beating the revealed price floor. Nobody wins, every bid can be
+    /// refunded, and the items go back to the seller. Nothing moves an auction out of it.
+    ReserveNotMet,
+    /// Called off by its authority before any bid was taken. Nothing moves an auction out of it.
+    Cancelled,
 }
 
 impl AuctionState {
-    pub fn create(is_buy_now: bool) -> Self {
-        if is_buy_now {
-            return AuctionState::BuyNowCreated;
-        }
-        AuctionState::Created
-    }
-
     #[inline(always)]
     pub fn start(self) -> Result<Self, ProgramError> {
         match self {
@@ -177,6 +604,17 @@ impl AuctionState {
             _ => Err(AuctionError::AuctionTransitionInvalid.into()),
         }
     }
+
+    #[inline(always)]
+    pub fn cancel(self) -> Result<Self, ProgramError> {
+        match self {
+            AuctionState::Created
+            | AuctionState::Started
+            | AuctionState::BuyNowCreated
+            | AuctionState::BuyNowStarted => Ok(AuctionState::Cancelled),
+            _ => Err(AuctionError::AuctionTransitionInvalid.into()),
+        }
+    }
 }
 
 /// Bids associate a bidding key with an amount bid.
@@ -184,13 +622,186 @@ impl AuctionState {
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
 pub struct Bid(pub Pubkey, pub u64);
 
+/// How the asking price of a Dutch auction falls from its start price to its floor.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub enum DutchDecay {
+    /// The price falls in a straight line, reaching the floor `duration` seconds after the start.
+    Linear { duration: UnixTimestamp },
+    /// The price drops by `step` every `interval` seconds until it reaches the floor.
+    Stepped { interval: UnixTimestamp, step: u64 },
+}
+
+/// Pricing schedule of a Dutch auction.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct DutchAuctionConfig {
+    /// Asking price when the auction starts.
+    pub start_price: u64,
+    /// Lowest asking price, reached once the decay has run its course.
+    pub floor_price: u64,
+    pub decay: DutchDecay,
+}
+
+// Largest serialized size of the Dutch specific fields of a BidState, the config plus started_at.
+pub const DUTCH_AUCTION_STATE_SIZE: usize = 8 + 8 + 1 + 8 + 8 + 9;
+
+impl DutchAuctionConfig {
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        if self.start_price <= self.floor_price {
+            return Err(AuctionError::InvalidDutchAuctionConfig.into());
+        }
+        let valid = match self.decay {
+            DutchDecay::Linear { duration } => duration > 0,
+            DutchDecay::Stepped { interval, step } => interval > 0 && step > 0,
+        };
+        if !valid {
+            return Err(AuctionError::InvalidDutchAuctionConfig.into());
+        }
+        Ok(())
+    }
+
+    /// Asking price `elapsed` seconds after the auction started.
+    pub fn price_at(&self, elapsed: UnixTimestamp) -> u64 {
+        if elapsed <= 0 {
+            return self.start_price;
+        }
+        let range = self.start_price.saturating_sub(self.floor_price);
+        let drop = match self.decay {
+            DutchDecay::Linear { duration } => {
+                if elapsed >= duration {
+                    range
+                } else {
+                    (range as u128 * elapsed as u128 / duration as u128) as u64
+                }
+            }
+            DutchDecay::Stepped { interval, step } => {
+                ((elapsed / interval) as u64).saturating_mul(step)
+            }
+        };
+        self.start_price.saturating_sub(drop).max(self.floor_price)
+    }
+}
+
+pub const MAX_BID_INCREMENT_TIERS: usize = 4;
+
+/// Increment that applies from a price upwards.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct BidIncrementTier {
+    /// Top bid at which this tier starts.
+    pub from: u64,
+    /// Amount a new bid must beat the top bid by.
+    pub increment: u64,
+}
+
+/// Minimum amount by which a bid must beat the current top bid.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub enum BidIncrement {
+    /// More than 5% of the top bid, auctions created without a setting use this.
+    Default,
+    /// A fixed amount.
+    Absolute(u64),
+    /// A share of the top bid, in basis points.
+    Percentage(u16),
+    /// An amount per price band, tiers are sorted by `from` and the first starts at zero.
+    Tiered(Vec<BidIncrementTier>),
+}
+
+impl BidIncrement {
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        let valid = match self {
+            BidIncrement::Default => true,
+            BidIncrement::Absolute(amount) => *amount > 0,
+            BidIncrement::Percentage(basis_points) => *basis_points > 0,
+            BidIncrement::Tiered(tiers) => {
+                !tiers.is_empty()
+                    && tiers.len() <= MAX_BID_INCREMENT_TIERS
+                    && tiers[0].from == 0
+                    && tiers.iter().all(|tier| tier.increment > 0)
+                    && tiers.windows(2).all(|pair| pair[0].from < pair[1].from)
+            }
+        };
+        if !valid {
+            return Err(AuctionError::InvalidBidIncrement.into());
+        }
+        Ok(())
+    }
+
+    /// Smallest amount a bid must beat `top` by.
+    pub fn minimum_outbid(&self, top: u64) -> u64 {
+        match self {
+            BidIncrement::Default => top / 20 + 1,
+            BidIncrement::Absolute(amount) => *amount,
+            BidIncrement::Percentage(basis_points) => {
+                (top as u128 * *basis_points as u128 / 10000) as u64
+            }
+            BidIncrement::Tiered(tiers) => tiers
+                .iter()
+                .rev()
+                .find(|tier| tier.from <= top)
+                .map_or(0, |tier| tier.increment),
+        }
+    }
+}
+
+/// Settings of a sealed bid auction.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct SealedBidConfig {
+    /// Seconds after bidding closes during which bidders reveal their bids.
+    pub reveal_period: UnixTimestamp,
+    /// Share of the deposit, in basis points, that goes to the auction authority when a bid is
+    /// never revealed. The rest is returned to the bidder.
+    pub forfeit_basis_points: u16,
+}
+
+// Serialized size of the sealed bid specific fields of a BidState.
+pub const SEALED_BID_STATE_SIZE: usize = 8 + 2;
+
+impl SealedBidConfig {
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        if self.reveal_period <= 0 || self.forfeit_basis_points > 10000 {
+            return Err(AuctionError::InvalidSealedBidConfig.into());
+        }
+        Ok(())
+    }
+}
+
 /// BidState tracks the running state of an auction, each variant represents a different kind of
 /// auction being run.
 #[repr(C)]
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
 pub enum BidState {
-    EnglishAuction { bids: Vec<Bid>, max: usize },
-    OpenEdition { bids: Vec<Bid>, max: usize },
+    EnglishAuction {
+        bids: Vec<Bid>,
+        max: usize,
+    },
+    OpenEdition {
+        bids: Vec<Bid>,
+        max: usize,
+    },
+    DutchAuction {
+        bids: Vec<Bid>,
+        max: usize,
+        config: DutchAuctionConfig,
+        /// Set when the auction starts, the price decays from this point on.
+        started_at: Option<UnixTimestamp>,
+    },
+    SealedBid {
+        /// Winning revealed bids, bids are hidden until revealed.
+        bids: Vec<Bid>,
+        max: usize,
+        config: SealedBidConfig,
+    },
+    MultiUnit {
+        /// One entry per unit held, sorted with the top bid last. A bid for several units holds
+        /// as many entries.
+        bids: Vec<Bid>,
+        /// Number of units on sale.
+        max: usize,
+    },
 }
 
 /// Bidding Implementations.
@@ -200,6 +811,15 @@ pub enum BidState {
 ///
 /// Open Edition: All bids are accepted, cancellations return money to the bidder and always
 /// succeed.
+///
+/// Dutch Auction: the asking price falls over time, the first bid to meet it wins outright and
+/// ends the auction.
+///
+/// Sealed Bid: bidders commit to a hidden bid along with a deposit and reveal it once bidding
+/// closes, the highest revealed bids win.
+///
+/// Multi-Unit: `max` identical units are sold, a bid names a price per unit and how many units it
+/// is for. The highest priced units win, a bidder outbid on some of their units keeps the rest.
 impl BidState {
     pub fn new_english(n: usize) -> Self {
         BidState::EnglishAuction {
@@ -215,22 +835,91 @@ impl BidState {
         }
     }
 
-    /// Push a new bid into the state, this succeeds only if the bid is larger than the current top
-    /// winner stored. Crappy list information to start with.
-    pub fn place_bid(&mut self, bid: Bid) -> Result<(), ProgramError> {
+    pub fn new_dutch(config: DutchAuctionConfig) -> Self {
+        BidState::DutchAuction {
+            bids: vec![],
+            max: 1,
+            config,
+            started_at: None,
+        }
+    }
+
+    pub fn new_sealed(n: usize, config: SealedBidConfig) -> Self {
+        BidState::SealedBid {
+            bids: vec![],
+            max: n,
+            config,
+        }
+    }
+
+    pub fn new_multi_unit(n: usize) -> Self {
+        BidState::MultiUnit {
+            bids: vec![],
+            max: n,
+        }
+    }
+
+    /// Current asking price if this is a Dutch auction.
+    pub fn dutch_price(&self, now: UnixTimestamp) -> Option<u64> {
+        match self {
+            BidState::DutchAuction {
+                config, started_at, ..
+            } => Some(config.price_at(now.saturating_sub(started_at.unwrap_or(now)))),
+            _ => None,
+        }
+    }
+
+    /// Space the bid state needs in an auction account on top of BASE_AUCTION_DATA_SIZE.
+    pub fn space(&self) -> usize {
+        match self {
+            BidState::EnglishAuction { max, .. } => mem::size_of::<Bid>() * max,
+            BidState::OpenEdition { .. } => 0,
+            BidState::DutchAuction { .. } => mem::size_of::<Bid>() + DUTCH_AUCTION_STATE_SIZE,
+            BidState::SealedBid { max, .. } => mem::size_of::<Bid>() * max + SEALED_BID_STATE_SIZE,
+            BidState::MultiUnit { max, .. } => mem::size_of::<Bid>() * max,
+        }
+    }
+
+    pub fn bids(&self) -> &Vec<Bid> {
+        match self {
+            BidState::EnglishAuction { bids, .. }
+            | BidState::OpenEdition { bids, .. }
+            | BidState::DutchAuction { bids, .. }
+            | BidState::SealedBid { bids, .. }
+            | BidState::MultiUnit { bids, .. } => bids,
+        }
+    }
+
+    /// Units a bidder holds at a price above `min`, one for any winner outside a multi-unit
+    /// auction.
+    pub fn units_won(&self, key: &Pubkey, min: u64) -> u64 {
+        self.bids()
+            .iter()
+            .filter(|bid| &bid.0 == key && bid.1 > min)
+            .count() as u64
+    }
+
+    /// Push a new bid into the state, this succeeds only if the bid beats the current top winner
+    /// stored by the increment. Crappy list information to start with. Returns the bid pushed out
+    /// of the winners, if any.
+    pub fn place_bid(
+        &mut self,
+        bid: Bid,
+        increment: &BidIncrement,
+    ) -> Result<Option<Bid>, ProgramError> {
         match self {
             // In a capped auction, track the limited number of winners.
             BidState::EnglishAuction { ref mut bids, max } => match bids.last() {
                 Some(top) => {
                     let outbid = bid.1.saturating_sub(top.1);
-                    let minimum_bid_increment = top.1 / 20;
-                    if outbid > minimum_bid_increment || bids.len() < *max {
+                    let minimum_bid_increment = increment.minimum_outbid(top.1);
+                    if (outbid > 0 && outbid >= minimum_bid_increment) || bids.len() < *max {
                         bids.retain(|b| b.0 != bid.0);
                         bids.push(bid);
                         if bids.len() > *max {
-                            bids.remove(0);
+                            return Ok(Some(bids.remove(0)));
                         }
-                        Ok(())
+                        Ok(None)
                     } else {
                         msg!("This bid fails to make it onto the winner stack.");
                         Err(AuctionError::BidTooSmall.into())
@@ -239,12 +928,177 @@ impl BidState {
                 _ => {
                     msg!("Pushing bid onto stack");
                     bids.push(bid);
-                    Ok(())
+                    Ok(None)
                 }
             },
 
             // In an open auction, bidding simply succeeds.
-            BidState::OpenEdition { bids, max } => Ok(()),
+            BidState::OpenEdition { bids, max } => Ok(None),
+
+            // The asking price has already been checked, the first bid to get here wins.
+            BidState::DutchAuction { ref mut bids, .. } => {
+                if !bids.is_empty() {
+                    msg!("This Dutch auction has already been won.");
+                    return Err(AuctionError::InvalidState.into());
+                }
+                bids.push(bid);
+                Ok(None)
+            }
+
+            // Sealed bids only enter the state once revealed.
+            BidState::SealedBid { .. } => Err(AuctionError::SealedBidMismatch.into()),
+
+            BidState::MultiUnit { .. } => self.place_units_bid(bid, 1, increment),
+        }
+    }
+
+    /// Raises a bidder's active bid. While it is still winning the raise only has to beat the
+    /// bidder's own bid, once outbid it has to beat the lowest winner it takes the place of.
+    pub fn raise_bid(
+        &mut self,
+        bid: Bid,
+        increment: &BidIncrement,
+    ) -> Result<Option<Bid>, ProgramError> {
+        match self {
+            BidState::EnglishAuction { ref mut bids, max } => {
+                let current = bids.iter().position(|b| b.0 == bid.0);
+                let replaced = match current {
+                    Some(index) => Some(index),
+                    None if bids.len() >= *max => bids
+                        .iter()
+                        .enumerate()
+                        .min_by_key(|(_, b)| b.1)
+                        .map(|(index, _)| index),
+                    None => None,
+                };
+                let replaced = match replaced {
+                    Some(index) => {
+                        let beaten = bids.remove(index);
+                        let outbid = bid.1.saturating_sub(beaten.1);
+                        if outbid == 0 || outbid < increment.minimum_outbid(beaten.1) {
+                            msg!("This raise fails to beat the bid it replaces.");
+                            return Err(AuctionError::BidTooSmall.into());
+                        }
+                        Some(beaten)
+                    }
+                    None => None,
+                };
+
+                // Winners stay ordered lowest first, a raise goes below bids it only ties with.
+                let at = bids
+                    .iter()
+                    .position(|b| b.1 >= bid.1)
+                    .unwrap_or_else(|| bids.len());
+                bids.insert(at, bid);
+                Ok(replaced.filter(|_| current.is_none()))
+            }
+
+            // In an open auction, bidding simply succeeds.
+            BidState::OpenEdition { .. } => Ok(None),
+
+            _ => Err(AuctionError::CannotIncreaseBid.into()),
+        }
+    }
+
+    /// Enters a bid at the buy now price. No bid can be higher, so it goes on top, taking the place
+    /// of the lowest winner which is all it has to beat.
+    pub fn place_buy_now_bid(
+        &mut self,
+        bid: Bid,
+        quantity: usize,
+    ) -> Result<Option<Bid>, ProgramError> {
+        match self {
+            BidState::EnglishAuction { ref mut bids, max } => {
+                bids.retain(|b| b.0 != bid.0);
+                if bids.len() >= *max && bids.first().map_or(false, |lowest| lowest.1 >= bid.1) {
+                    msg!("Every winner already bid the buy now price.");
+                    return Err(AuctionError::BidTooSmall.into());
+                }
+                bids.push(bid);
+                if bids.len() > *max {
+                    return Ok(Some(bids.remove(0)));
+                }
+                Ok(None)
+            }
+            BidState::MultiUnit { .. } => {
+                self.place_units_bid(bid, quantity, &BidIncrement::Absolute(1))
+            }
+            _ => self.place_bid(bid, &BidIncrement::Absolute(1)),
+        }
+    }
+
+    /// Enters a bid for `quantity` units of a multi-unit auction, replacing the bidder's previous
+    /// bid. When the units run out the bid must beat each unit it takes by the increment. Returns
+    /// the highest priced unit pushed out of the winners, if any.
+    pub fn place_units_bid(
+        &mut self,
+        bid: Bid,
+        quantity: usize,
+        increment: &BidIncrement,
+    ) -> Result<Option<Bid>, ProgramError> {
+        match self {
+            BidState::MultiUnit { ref mut bids, max } => {
+                if quantity == 0 || quantity > *max {
+                    return Err(AuctionError::InvalidBidQuantity.into());
+                }
+                bids.retain(|b| b.0 != bid.0);
+
+                // Units are taken from the bottom of the stack, the last one taken is the
+                // highest the bid has to beat.
+                let taken = (bids.len() + quantity).saturating_sub(*max);
+                if taken > 0 {
+                    let beaten = bids[taken - 1].1;
+                    let outbid = bid.1.saturating_sub(beaten);
+                    if outbid == 0 || outbid < increment.minimum_outbid(beaten) {
+                        msg!("This bid fails to make it onto the winner stack.");
+                        return Err(AuctionError::BidTooSmall.into());
+                    }
+                }
+
+                // Equal bids rank below the ones already placed.
+                let position = bids
+                    .iter()
+                    .position(|b| b.1 >= bid.1)
+                    .unwrap_or_else(|| bids.len());
+                for _ in 0..quantity {
+                    bids.insert(position, bid.clone());
+                }
+                Ok(bids.drain(..taken).last())
+            }
+            _ => Err(AuctionError::InvalidBidQuantity.into()),
+        }
+    }
+
+    /// Enters a revealed sealed bid, keeping the `max` highest bids sorted with the top bid last.
+    /// Returns the bid that doesn't make the cut, if any.
+    pub fn reveal_bid(&mut self, bid: Bid) -> Result<Option<Bid>, ProgramError> {
+        match self {
+            BidState::SealedBid {
+                ref mut bids, max, ..
+            } => {
+                bids.retain(|b| b.0 != bid.0);
+                let position = bids
+                    .iter()
+                    .position(|b| b.1 >= bid.1)
+                    .unwrap_or_else(|| bids.len());
+                bids.insert(position, bid);
+                if bids.len() > *max {
+                    return Ok(Some(bids.remove(0)));
+                }
+                Ok(None)
+            }
+            _ => Err(AuctionError::SealedBidMismatch.into()),
+        }
+    }
+
+    /// Drops every bid below `price` from the winners, they are refunded like any losing bid.
+    pub fn drop_bids_below(&mut self, price: u64) {
+        match self {
+            BidState::EnglishAuction { ref mut bids, .. }
+            | BidState::DutchAuction { ref mut bids, .. }
+            | BidState::SealedBid { ref mut bids, .. }
+            | BidState::MultiUnit { ref mut bids, .. } => bids.retain(|b| b.1 >= price),
+            BidState::OpenEdition { .. } => (),
         }
     }
 
@@ -252,7 +1106,14 @@ impl BidState {
     /// function simple no-ops.
     pub fn cancel_bid(&mut self, key: Pubkey) -> Result<(), ProgramError> {
         match self {
-            BidState::EnglishAuction { ref mut bids, max } => {
+            BidState::EnglishAuction { ref mut bids, max }
+            | BidState::DutchAuction {
+                ref mut bids, max, ..
+            }
+            | BidState::SealedBid {
+                ref mut bids, max, ..
+            }
+            | BidState::MultiUnit { ref mut bids, max } => {
                 bids.retain(|b| b.0 != key);
                 Ok(())
             }
@@ -267,7 +1128,10 @@ impl BidState {
     pub fn is_winner(&self, key: &Pubkey, min: u64) -> Option<usize> {
         match self {
             // Presense in the winner list is enough to check win state.
-            BidState::EnglishAuction { bids, max } => {
+            BidState::EnglishAuction { bids, max }
+            | BidState::DutchAuction { bids, max, .. }
+            | BidState::SealedBid { bids, max, .. }
+            | BidState::MultiUnit { bids, max } => {
                 bids.iter().position(|bid| &bid.0 == key && bid.1 > min)
             }
             // There are no winners in an open edition, it is up to the auction manager to decide
@@ -303,10 +1167,144 @@ pub struct BidderMetadata {
     pub cancelled: bool,
 }
 
+pub const BID_HISTORY_HEADER_LEN: usize = 32 + 4 + 8;
+pub const BID_EVENT_LEN: usize = 32 + 8 + 8 + 1;
+// Keeps the history account within what a program can allocate in one go.
+pub const MAX_BID_HISTORY_CAPACITY: u32 = 200;
+
+/// Header of an auction's bid history, a PDA holding a ring buffer of the last `capacity` events
+/// right after this header.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct BidHistory {
+    /// Auction this history belongs to.
+    pub auction: Pubkey,
+    /// Number of events the buffer holds before the oldest are overwritten.
+    pub capacity: u32,
+    /// Number of events ever recorded, the next one goes in slot `count % capacity`.
+    pub count: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub enum BidEventKind {
+    Placed,
+    Cancelled,
+    /// An active bid was raised, the amount is the new bid.
+    Raised,
+    /// A losing bid was refunded by RefundLosers rather than cancelled by the bidder.
+    Refunded,
+}
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct BidEvent {
+    /// Wallet of the bidder.
+    pub bidder: Pubkey,
+    /// Amount bid, or the amount of the bid being cancelled.
+    pub amount: u64,
+    pub timestamp: UnixTimestamp,
+    pub kind: BidEventKind,
+}
+
+impl BidHistory {
+    pub fn size(capacity: u32) -> usize {
+        BID_HISTORY_HEADER_LEN + BID_EVENT_LEN * capacity as usize
+    }
+
+    /// Writes an event over the oldest slot once the buffer is full, only touching that slot.
+    pub fn append(account: &AccountInfo, event: &BidEvent) -> ProgramResult {
+        let mut data = account.data.borrow_mut();
+        let mut history: BidHistory = try_from_slice_unchecked(&data[..BID_HISTORY_HEADER_LEN])?;
+        let slot = (history.count % history.capacity as u64) as usize;
+        let start = BID_HISTORY_HEADER_LEN + slot * BID_EVENT_LEN;
+        event.serialize(&mut &mut data[start..start + BID_EVENT_LEN])?;
+        history.count = match history.count.checked_add(1) {
+            Some(val) => val,
+            None => return Err(AuctionError::NumericalOverflowError.into()),
+        };
+        history.serialize(&mut &mut data[..BID_HISTORY_HEADER_LEN])?;
+        Ok(())
+    }
+
+    /// Splits an auction's bid history off the end of its trailing accounts, when it keeps one.
+    pub fn split_account<'a, 'b>(
+        program_id: &Pubkey,
+        auction: &AuctionData,
+        auction_key: &Pubkey,
+        remaining: &'a [AccountInfo<'b>],
+    ) -> Result<(Option<&'a AccountInfo<'b>>, &'a [AccountInfo<'b>]), ProgramError> {
+        if !auction.has_bid_history {
+            return Ok((None, remaining));
+        }
+        let (bid_history, rest) = match remaining.split_last() {
+            Some(split) => split,
+            None => return Err(AuctionError::BidHistoryMissing.into()),
+        };
+        assert_owned_by(bid_history, program_id)?;
+        assert_derivation(
+            program_id,
+            bid_history,
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                auction_key.as_ref(),
+                "history".as_bytes(),
+            ],
+        )
+        .map_err(|_| AuctionError::BidHistoryMissing)?;
+        Ok((Some(bid_history), rest))
+    }
+
+    /// Events still held in the buffer, oldest first.
+    pub fn events(data: &[u8]) -> Result<Vec<BidEvent>, ProgramError> {
+        let history: BidHistory = try_from_slice_unchecked(&data[..BID_HISTORY_HEADER_LEN])?;
+        let capacity = history.capacity as u64;
+        let first = history.count.saturating_sub(capacity);
+        (first..history.count)
+            .map(|i| {
+                let start = BID_HISTORY_HEADER_LEN + (i % capacity) as usize * BID_EVENT_LEN;
+                Ok(try_from_slice_unchecked(
+                    &data[start..start + BID_EVENT_LEN],
+                )?)
+            })
+            .collect()
+    }
+}
+
+pub const SEALED_BID_LEN: usize = 32 + 8 + 9;
+/// A bidder's commitment in a sealed bid auction, stored in a PDA next to their BidderMetadata.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct SealedBid {
+    /// Commitment to the bid, see SealedBid::commit.
+    pub commitment: Hash,
+    /// Amount put in the bidder pot with the commitment, the revealed bid can't exceed it.
+    pub deposit: u64,
+    /// The bid amount, once revealed.
+    pub revealed: Option<u64>,
+}
+
+impl SealedBid {
+    /// Hash of the auction key, the bidder's wallet key, then the bid amount and a salt as big
+    /// endian u64s. Binding the keys in stops a commitment from being copied onto another bidder
+    /// or auction and revealed once its owner reveals it.
+    pub fn commit(auction: &Pubkey, bidder: &Pubkey, amount: u64, salt: u64) -> Hash {
+        hash::hashv(&[
+            auction.as_ref(),
+            bidder.as_ref(),
+            &amount.to_be_bytes(),
+            &salt.to_be_bytes(),
+        ])
+    }
+}
+
+pub const BIDDER_POT_LEN: usize = 32 + 32 + 32 + 1 + 33 + 33;
 #[repr(C)]
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
 pub struct BidderPot {
-    /// Points at actual pot that is a token account
+    /// Points at actual pot that is a token account, or at this account itself when it holds a
+    /// native bid as lamports.
     pub bidder_pot: Pubkey,
     /// Originating bidder account
     pub bidder_act: Pubkey,
@@ -314,7 +1312,280 @@ pub struct BidderPot {
     pub auction_act: Pubkey,
     /// emptied or not
     pub emptied: bool,
+    /// Referrer registered with the bid, paid their share of the fees when it is claimed.
+    pub referrer: Option<Pubkey>,
+    /// Token account the bid was paid from, refunds made on the bidder's behalf go back to it.
+    pub refund_account: Option<Pubkey>,
+}
+
+impl BidderPot {
+    pub fn from_account_info(a: &AccountInfo) -> Result<BidderPot, ProgramError> {
+        BidderPot::from_slice(&a.data.borrow())
+    }
+
+    /// Decodes a pot, fields appended after `emptied` take their defaults on pots created before
+    /// they existed.
+    pub fn from_slice(data: &[u8]) -> Result<BidderPot, ProgramError> {
+        let buf = &mut &data[..];
+        let mut pot = BidderPot {
+            bidder_pot: BorshDeserialize::deserialize(buf)?,
+            bidder_act: BorshDeserialize::deserialize(buf)?,
+            auction_act: BorshDeserialize::deserialize(buf)?,
+            emptied: BorshDeserialize::deserialize(buf)?,
+            referrer: None,
+            refund_account: None,
+        };
+        if !buf.is_empty() {
+            pot.referrer = BorshDeserialize::deserialize(buf)?;
+        }
+        if !buf.is_empty() {
+            pot.refund_account = BorshDeserialize::deserialize(buf)?;
+        }
+        Ok(pot)
+    }
+
+    /// Writes the pot back, pots created before a field existed only accept it at its default.
+    pub fn save(&self, a: &AccountInfo) -> ProgramResult {
+        let bytes = self.try_to_vec()?;
+        let mut data = a.data.borrow_mut();
+        let written = bytes.len().min(data.len());
+        if bytes[written..].iter().any(|b| *b != 0) {
+            return Err(AuctionError::BidderPotTooSmall.into());
+        }
+        data[..written].copy_from_slice(&bytes[..written]);
+        Ok(())
+    }
+
+    /// Whether the pot can record fields up to its refund account.
+    pub fn records_refund_account(a: &AccountInfo) -> bool {
+        a.data_len() >= BIDDER_POT_LEN
+    }
+
+    /// Whether the pot at `key` holds its bid as lamports rather than in a token account.
+    pub fn is_native(&self, key: &Pubkey) -> bool {
+        self.bidder_pot == *key
+    }
+}
+
+/// Leading byte of every referral registry, no other account of the program starts with a key.
+pub const REFERRAL_REGISTRY_KEY: u8 = 1;
+pub const REFERRAL_REGISTRY_LEN: usize = 1 + 32 + 8;
+/// Registers a referrer with the program, bids placed with it pay the referrer a share of the fees
+/// when they are claimed.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct ReferralRegistry {
+    /// Always REFERRAL_REGISTRY_KEY.
+    pub key: u8,
+    /// Wallet of the referrer, token fees go to its token accounts.
+    pub referrer: Pubkey,
+    /// Number of bids placed with this referrer.
+    pub bids_referred: u64,
+}
+
+impl ReferralRegistry {
+    /// Splits a referral registry off the end of a bid's trailing accounts, when the bidder passed
+    /// one. A registry is recognised by its key and its address, other trailing accounts share
+    /// neither.
+    pub fn split_account<'a, 'b>(
+        program_id: &Pubkey,
+        remaining: &'a [AccountInfo<'b>],
+    ) -> Result<(Option<&'a AccountInfo<'b>>, &'a [AccountInfo<'b>]), ProgramError> {
+        let (registry, rest) = match remaining.split_last() {
+            Some(split) => split,
+            None => return Ok((None, remaining)),
+        };
+        let referral = match ReferralRegistry::from_account_info(program_id, registry) {
+            Some(referral) => referral,
+            None => return Ok((None, remaining)),
+        };
+        let derivation = assert_derivation(
+            program_id,
+            registry,
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                referral.referrer.as_ref(),
+                "referral".as_bytes(),
+            ],
+        );
+        match derivation {
+            Ok(_) => Ok((Some(registry), rest)),
+            Err(_) => Ok((None, remaining)),
+        }
+    }
+
+    /// Decodes the account as a registry if it is one of this program's and carries the key.
+    pub fn from_account_info(program_id: &Pubkey, a: &AccountInfo) -> Option<ReferralRegistry> {
+        if a.owner != program_id || a.data_len() < REFERRAL_REGISTRY_LEN {
+            return None;
+        }
+        let data = a.data.borrow();
+        if data[0] != REFERRAL_REGISTRY_KEY {
+            return None;
+        }
+        try_from_slice_unchecked(&data[..REFERRAL_REGISTRY_LEN]).ok()
+    }
+}
+
+/// Restricts who may bid on an auction.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub enum BidderGate {
+    /// Wallets in the merkle tree with this root, each proves it once with JoinAllowlist.
+    Allowlist(Hash),
+    /// Wallets holding a token of this mint, shown by passing one of their token accounts.
+    TokenHolder(Pubkey),
+}
+
+impl BidderGate {
+    /// Whether `proof` shows `bidder` is a leaf of the tree with this `root`. Leaves are the sha256
+    /// of the wallet, and each pair of nodes is hashed in sorted order so proofs need no left or
+    /// right flags.
+    pub fn verify_proof(root: &Hash, bidder: &Pubkey, proof: &[Hash]) -> bool {
+        let leaf = hash::hashv(&[bidder.as_ref()]);
+        let computed = proof.iter().fold(leaf, |node, sibling| {
+            if node.as_ref() <= sibling.as_ref() {
+                hash::hashv(&[node.as_ref(), sibling.as_ref()])
+            } else {
+                hash::hashv(&[sibling.as_ref(), node.as_ref()])
+            }
+        });
+        computed == *root
+    }
+
+    /// Splits the account showing a bidder passes the auction's gate off the end of their bid's
+    /// trailing accounts and checks it, when the auction is gated. That is their allowlist pass,
+    /// or a token account of theirs holding a token of the gate mint.
+    pub fn split_pass<'a, 'b>(
+        program_id: &Pubkey,
+        auction: &AuctionData,
+        auction_key: &Pubkey,
+        bidder_key: &Pubkey,
+        remaining: &'a [AccountInfo<'b>],
+    ) -> Result<&'a [AccountInfo<'b>], ProgramError> {
+        let gate = match &auction.gate {
+            Some(gate) => gate,
+            None => return Ok(remaining),
+        };
+        let (pass, rest) = match remaining.split_last() {
+            Some(split) => split,
+            None => return Err(AuctionError::BidderNotAllowed.into()),
+        };
+        match gate {
+            BidderGate::Allowlist(_) => {
+                // Passes are only created by JoinAllowlist, once the proof checked out.
+                assert_owned_by(pass, program_id).map_err(|_| AuctionError::BidderNotAllowed)?;
+                assert_derivation(
+                    program_id,
+                    pass,
+                    &[
+                        PREFIX.as_bytes(),
+                        program_id.as_ref(),
+                        auction_key.as_ref(),
+                        bidder_key.as_ref(),
+                        "allowlist".as_bytes(),
+                    ],
+                )
+                .map_err(|_| AuctionError::BidderNotAllowed)?;
+            }
+            BidderGate::TokenHolder(mint) => {
+                assert_owned_by(pass, &spl_token::id())?;
+                let account: Account = assert_initialized(pass)?;
+                if account.owner != *bidder_key || account.mint != *mint || account.amount == 0 {
+                    msg!("The bidder holds no token of the gate mint in this account");
+                    return Err(AuctionError::BidderNotAllowed.into());
+                }
+            }
+        }
+        Ok(rest)
+    }
+}
+
+pub const ALLOWLIST_PASS_LEN: usize = 32 + 32;
+/// Shows a wallet proved it is on an auction's allowlist, stored in a PDA next to their
+/// BidderMetadata.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct AllowlistPass {
+    /// Auction the wallet may bid on.
+    pub auction: Pubkey,
+    /// Wallet of the bidder.
+    pub bidder: Pubkey,
+}
+
+/// Fees taken out of a winning bid when it is claimed.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct FeeSchedule {
+    /// Share of the price taken as fees, in basis points.
+    pub fee_basis_points: u16,
+    /// Share of the fees paid to the referrer when there is one, in basis points.
+    pub referral_share_basis_points: u16,
+    /// Most that can be taken as fees from a single bid.
+    pub max_fee: Option<u64>,
+}
+
+impl FeeSchedule {
+    pub fn validate(&self) -> ProgramResult {
+        if self.fee_basis_points > 10000 || self.referral_share_basis_points > 10000 {
+            return Err(AuctionError::InvalidFeeSchedule.into());
+        }
+        Ok(())
+    }
+
+    /// Splits the fees on `price` into what goes to the fee recipient and what goes to the
+    /// referrer, if any.
+    pub fn fees(&self, price: u64, has_referrer: bool) -> Result<(u64, u64), ProgramError> {
+        let fees = (price as u128)
+            .checked_mul(self.fee_basis_points as u128)
+            .ok_or(AuctionError::NumericalOverflowError)?
+            / 10000;
+        let fees = match self.max_fee {
+            Some(max_fee) => (fees as u64).min(max_fee),
+            None => fees as u64,
+        };
+        let referral_fees = if has_referrer {
+            (fees as u128 * self.referral_share_basis_points as u128 / 10000) as u64
+        } else {
+            0
+        };
+        Ok((fees - referral_fees, referral_fees))
+    }
+}
+
+pub const FEE_CONFIG_LEN: usize = 32 + 32 + 2 + 2 + 9;
+/// Fee settings of an operator, a PDA derived from the key that created it so that the same
+/// program can serve several operators.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct FeeConfig {
+    /// Key allowed to change this config and to close bidder pots of auctions using it.
+    pub admin: Pubkey,
+    /// Wallet receiving the fees, token fees go to its token accounts.
+    pub recipient: Pubkey,
+    pub schedule: FeeSchedule,
+}
+
+impl FeeConfig {
+    /// Loads the FeeConfig an auction was created with.
+    pub fn from_auction(
+        program_id: &Pubkey,
+        auction: &AuctionData,
+        account: &AccountInfo,
+    ) -> Result<FeeConfig, ProgramError> {
+        if auction.fee_config != Some(*account.key) {
+            return Err(AuctionError::FeeConfigMismatch.into());
+        }
+        assert_owned_by(account, program_id)?;
+        Ok(try_from_slice_unchecked(&account.data.borrow())?)
+    }
 }
+
+pub const BUY_NOW_DATA_LEN: usize = 8;
+/// Buy now price of an auction created before it moved into AuctionData, those auctions have no
+/// room to keep it. Bids on them pass this account to read the price, UpdateAuction closes it and
+/// claiming a bid sweeps its lamports to the fee recipient.
 #[repr(C)]
 #[derive(Clone, BorshDeserialize, BorshSerialize, PartialEq, Debug)]
 pub struct BuyNowData {
@@ -322,4 +1593,37 @@ pub struct BuyNowData {
     pub max_price: u64,
 }
 
-pub const BUY_NOW_DATA_LEN: usize = 8;
+impl BuyNowData {
+    /// Whether `account` is the buy now account of the auction of `resource`.
+    pub fn is_buy_now_account(
+        program_id: &Pubkey,
+        resource: &Pubkey,
+        account: &AccountInfo,
+    ) -> bool {
+        let (key, _) = Pubkey::find_program_address(
+            &[BUY_NOW.as_bytes(), program_id.as_ref(), resource.as_ref()],
+            program_id,
+        );
+        *account.key == key
+    }
+
+    /// Loads the buy now account of the auction of `resource`.
+    pub fn from_account_info(
+        program_id: &Pubkey,
+        resource: &Pubkey,
+        account: &AccountInfo,
+    ) -> Result<BuyNowData, ProgramError> {
+        if !BuyNowData::is_buy_now_account(program_id, resource, account) {
+            msg!("Invalid buy now account provided");
+            return Err(ProgramError::InvalidArgument);
+        }
+        assert_owned_by(account, program_id)?;
+        Ok(try_from_slice_unchecked(&account.data.borrow())?)
+    }
+
+    /// Moves every lamport of the buy now account to `destination`, which closes it.
+    pub fn close(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+        let lamports = account.lamports();
+        transfer_lamports(account, destination, lamports)
+    }
+}
@@ -0,0 +1,92 @@
+//! Calls off an auction that hasn't taken a bid that could still win, whether it started or not.
+//! Bids that lost or were cancelled can still be pulled out, or refunded with RefundLosers.
+
+use crate::{
+    errors::AuctionError,
+    processor::AuctionData,
+    utils::{assert_derivation, assert_owned_by, assert_signer},
+    PREFIX,
+};
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        clock::Clock,
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::Sysvar,
+    },
+};
+
+/// Arguments for the CancelAuction instruction discriminant.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct CancelAuctionArgs {
+    /// The resource being auctioned. See AuctionData.
+    pub resource: Pubkey,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    authority: &'a AccountInfo<'b>,
+    auction: &'a AccountInfo<'b>,
+    clock_sysvar: &'a AccountInfo<'b>,
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        authority: next_account_info(account_iter)?,
+        auction: next_account_info(account_iter)?,
+        clock_sysvar: next_account_info(account_iter)?,
+    };
+    assert_owned_by(accounts.auction, program_id)?;
+    assert_signer(accounts.authority)?;
+    Ok(accounts)
+}
+
+pub fn cancel_auction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: CancelAuctionArgs,
+) -> ProgramResult {
+    msg!("+ Processing CancelAuction");
+    let accounts = parse_accounts(program_id, accounts)?;
+    let clock = Clock::from_account_info(accounts.clock_sysvar)?;
+
+    assert_derivation(
+        program_id,
+        accounts.auction,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+        ],
+    )?;
+
+    let mut auction = AuctionData::from_account_info(accounts.auction)?;
+
+    if auction.authority != *accounts.authority.key {
+        return Err(AuctionError::InvalidAuthority.into());
+    }
+
+    // Once its time is up the auction is decided, whether or not anyone settled it.
+    if auction.ended(clock.unix_timestamp)? {
+        return Err(AuctionError::AuctionTransitionInvalid.into());
+    }
+
+    if auction.has_active_bids() {
+        return Err(AuctionError::AuctionHasBids.into());
+    }
+
+    auction.state = auction.state.cancel()?;
+    auction.ended_at = Some(clock.unix_timestamp);
+    auction.save(accounts.auction)?;
+
+    Ok(())
+}
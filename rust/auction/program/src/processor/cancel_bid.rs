@@ -3,16 +3,23 @@
 //! 1) The auction is still going on, in which case it is possible to cancel a bid at any time.
 //! 2) The auction has finished, but the bid did not win. This allows users to claim back their
 //!    funds from bid accounts.
+//!
+//! Sealed bids can be withdrawn while bidding is open, after that only revealed losing bids can be
+//! cancelled, unrevealed ones are settled with ClaimForfeitedBid.
 
 use crate::{
     errors::AuctionError,
-    processor::{AuctionData, BidderMetadata, BidderPot},
+    processor::{
+        AuctionData, BidEvent, BidEventKind, BidHistory, BidState, BidderMetadata, BidderPot,
+        SealedBid,
+    },
     utils::{
         assert_derivation, assert_initialized, assert_owned_by, assert_signer,
-        create_or_allocate_account_raw, spl_token_transfer, spl_token_transfer_checked,
-        TokenTransferCheckedParams, TokenTransferParams,
+        create_or_allocate_account_raw, lamports_above_rent, spl_token_transfer,
+        spl_token_transfer_checked, transfer_lamports, TokenTransferCheckedParams,
+        TokenTransferParams,
     },
-    PREFIX,
+    MINT_DECIMALS, PREFIX,
 };
 
 use {
@@ -50,6 +57,9 @@ struct Accounts<'a, 'b: 'a> {
     rent: &'a AccountInfo<'b>,
     system: &'a AccountInfo<'b>,
     token_program: &'a AccountInfo<'b>,
+    /// The sealed bid account on sealed bid auctions, then the bid history if the auction keeps
+    /// one.
+    remaining: &'a [AccountInfo<'b>],
 }
 
 fn parse_accounts<'a, 'b: 'a>(
@@ -69,12 +79,12 @@ fn parse_accounts<'a, 'b: 'a>(
         rent: next_account_info(account_iter)?,
         system: next_account_info(account_iter)?,
         token_program: next_account_info(account_iter)?,
+        remaining: account_iter.as_slice(),
     };
 
     assert_owned_by(accounts.auction, program_id)?;
     assert_owned_by(accounts.bidder_meta, program_id)?;
     assert_owned_by(accounts.mint, &spl_token::id())?;
-    assert_owned_by(accounts.bidder_pot_token, &spl_token::id())?;
     assert_signer(accounts.bidder)?;
 
     Ok(accounts)
@@ -88,12 +98,6 @@ pub fn cancel_bid(
     msg!("+ Processing Cancelbid");
     let accounts = parse_accounts(program_id, accounts)?;
 
-    // The account within the pot must be owned by us.
-    let actual_account: Account = assert_initialized(accounts.bidder_pot_token)?;
-    if actual_account.owner != *accounts.auction.key {
-        return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
-    }
-
     // Derive and load Auction.
     let auction_bump = assert_derivation(
         program_id,
@@ -113,7 +117,16 @@ pub fn cancel_bid(
     ];
 
     // Load the auction and verify this bid is valid.
-    let mut auction: AuctionData = try_from_slice_unchecked(&accounts.auction.data.borrow())?;
+    let mut auction = AuctionData::from_account_info(accounts.auction)?;
+
+    // The account within the pot must be owned by us, native pots hold the bid themselves.
+    if !auction.native_bids {
+        assert_owned_by(accounts.bidder_pot_token, &spl_token::id())?;
+        let actual_account: Account = assert_initialized(accounts.bidder_pot_token)?;
+        if actual_account.owner != *accounts.auction.key {
+            return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
+        }
+    }
 
     // Load the clock, used for various auction timing.
     let clock = Clock::from_account_info(accounts.clock_sysvar)?;
@@ -160,6 +173,37 @@ pub fn cancel_bid(
         return Err(AuctionError::BidderPotDoesNotExist.into());
     }
 
+    let (bid_history, remaining) = BidHistory::split_account(
+        program_id,
+        &auction,
+        accounts.auction.key,
+        accounts.remaining,
+    )?;
+
+    // Hidden bids can't be pulled once bidders are revealing.
+    if let BidState::SealedBid { .. } = auction.bid_state {
+        let sealed_bid_account = match remaining.first() {
+            Some(account) => account,
+            None => return Err(AuctionError::SealedBidMismatch.into()),
+        };
+        assert_derivation(
+            program_id,
+            sealed_bid_account,
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                accounts.auction.key.as_ref(),
+                accounts.bidder.key.as_ref(),
+                "sealed".as_bytes(),
+            ],
+        )?;
+        assert_owned_by(sealed_bid_account, program_id)?;
+        let sealed_bid: SealedBid = try_from_slice_unchecked(&sealed_bid_account.data.borrow())?;
+        if sealed_bid.revealed.is_none() && auction.bidding_closed(clock.unix_timestamp)? {
+            return Err(AuctionError::SealedBidNotRevealed.into());
+        }
+    }
+
     // Refuse to cancel if the auction ended and this person is a winning account.
     if auction.is_winner(accounts.bidder_pot.key).is_some() {
         msg!("The winner cannot cancel their bid.");
@@ -167,24 +211,34 @@ pub fn cancel_bid(
     }
 
     // Confirm we're looking at the real SPL account for this bidder.
-    let bidder_pot: BidderPot = try_from_slice_unchecked(&accounts.bidder_pot.data.borrow_mut())?;
+    let bidder_pot = BidderPot::from_account_info(accounts.bidder_pot)?;
     if bidder_pot.bidder_pot != *accounts.bidder_pot_token.key {
         return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
     }
 
-    // Transfer SPL bid balance back to the user.
-    let account: Account = Account::unpack_from_slice(&accounts.bidder_pot_token.data.borrow())?;
+    if bidder_pot.is_native(accounts.bidder_pot.key) {
+        // Return the lamports held above the pot's rent to the bidder's wallet.
+        if accounts.bidder_token.key != accounts.bidder.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let amount = lamports_above_rent(accounts.bidder_pot)?;
+        transfer_lamports(accounts.bidder_pot, accounts.bidder, amount)?;
+    } else {
+        // Transfer SPL bid balance back to the user.
+        let account: Account =
+            Account::unpack_from_slice(&accounts.bidder_pot_token.data.borrow())?;
 
-    spl_token_transfer_checked(TokenTransferCheckedParams {
-        source: accounts.bidder_pot_token.clone(),
-        destination: accounts.bidder_token.clone(),
-        authority: accounts.auction.clone(),
-        authority_signer_seeds: auction_seeds,
-        token_program: accounts.token_program.clone(),
-        amount: account.amount,
-        mint: accounts.mint.clone(),
-        decimals: 6, // USDC and FIDA decimals are 6
-    })?;
+        spl_token_transfer_checked(TokenTransferCheckedParams {
+            source: accounts.bidder_pot_token.clone(),
+            destination: accounts.bidder_token.clone(),
+            authority: accounts.auction.clone(),
+            authority_signer_seeds: auction_seeds,
+            token_program: accounts.token_program.clone(),
+            amount: account.amount,
+            mint: accounts.mint.clone(),
+            decimals: MINT_DECIMALS,
+        })?;
+    }
 
     // Update Metadata
     let metadata: BidderMetadata =
@@ -193,11 +247,23 @@ pub fn cancel_bid(
         cancelled: true,
         ..metadata
     }
-    .serialize(&mut *accounts.bidder_meta.data.borrow_mut())?;
+    .serialize(&mut &mut accounts.bidder_meta.data.borrow_mut()[..])?;
+
+    if let Some(bid_history) = bid_history {
+        BidHistory::append(
+            bid_history,
+            &BidEvent {
+                bidder: *accounts.bidder.key,
+                amount: metadata.last_bid,
+                timestamp: clock.unix_timestamp,
+                kind: BidEventKind::Cancelled,
+            },
+        )?;
+    }
 
     // Update Auction
     auction.bid_state.cancel_bid(*accounts.bidder_pot.key);
-    auction.serialize(&mut *accounts.auction.data.borrow_mut())?;
+    auction.save(accounts.auction)?;
 
     Ok(())
 }
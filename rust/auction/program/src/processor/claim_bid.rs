@@ -1,20 +1,21 @@
 //! Claim bid winnings into a target SPL account, only the authorised key can do this, though the
-//! target can be any SPL account.
-
-use std::str::FromStr;
+//! target can be any SPL account. Under second price settlement the winner is refunded whatever
+//! they bid above their price. Native bids are paid out in lamports to wallets instead. Fees go
+//! to the recipient of the FeeConfig the auction was created with, if any.
 
 use crate::{
     errors::AuctionError,
-    processor::{AuctionData, BidderMetadata, BidderPot},
+    processor::{
+        AuctionData, AuctionState, BidderMetadata, BidderPot, BuyNowData, FeeConfig, SettlementMode,
+    },
     utils::{
-        assert_account_key, assert_derivation, assert_initialized, assert_owned_by, assert_signer,
-        create_or_allocate_account_raw, spl_token_transfer, TokenTransferParams,
+        assert_derivation, assert_initialized, assert_owned_by, assert_signer,
+        create_or_allocate_account_raw, lamports_above_rent, spl_token_transfer, transfer_lamports,
+        TokenTransferParams,
     },
-    BONFIDA_SOL_VAULT, BUY_NOW, PREFIX, REF_SHARE,
+    PREFIX,
 };
 
-use super::BuyNowData;
-
 use {
     borsh::{try_from_slice_with_schema, BorshDeserialize, BorshSerialize},
     solana_program::{
@@ -36,7 +37,6 @@ use {
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
 pub struct ClaimBidArgs {
     pub resource: Pubkey,
-    pub fee_percentage: u64, // * 10_000
 }
 
 struct Accounts<'a, 'b: 'a> {
@@ -49,10 +49,12 @@ struct Accounts<'a, 'b: 'a> {
     mint: &'a AccountInfo<'b>,
     clock_sysvar: &'a AccountInfo<'b>,
     token_program: &'a AccountInfo<'b>,
-    bonfida_vault: &'a AccountInfo<'b>,
-    buy_now: &'a AccountInfo<'b>,
-    bonfida_sol_vault: &'a AccountInfo<'b>,
-    referrer: Option<&'a AccountInfo<'b>>,
+    fee_recipient_token: &'a AccountInfo<'b>,
+    fee_recipient: &'a AccountInfo<'b>,
+    fee_config: &'a AccountInfo<'b>,
+    /// The bidder token account under second price settlement, then the referrer's account when
+    /// the bid was referred, then the buy now account of an auction still in a buy now state.
+    remaining: &'a [AccountInfo<'b>],
 }
 
 fn parse_accounts<'a, 'b: 'a>(
@@ -70,42 +72,28 @@ fn parse_accounts<'a, 'b: 'a>(
         mint: next_account_info(account_iter)?,
         clock_sysvar: next_account_info(account_iter)?,
         token_program: next_account_info(account_iter)?,
-        bonfida_vault: next_account_info(account_iter)?,
-        buy_now: next_account_info(account_iter)?,
-        bonfida_sol_vault: next_account_info(account_iter)?,
-        referrer: next_account_info(account_iter).ok(),
+        fee_recipient_token: next_account_info(account_iter)?,
+        fee_recipient: next_account_info(account_iter)?,
+        fee_config: next_account_info(account_iter)?,
+        remaining: account_iter.as_slice(),
     };
 
     assert_owned_by(accounts.auction, program_id)?;
     assert_owned_by(accounts.mint, &spl_token::id())?;
-    assert_owned_by(accounts.destination, &spl_token::id())?;
-    assert_owned_by(accounts.bidder_pot_token, &spl_token::id())?;
     assert_signer(accounts.authority)?;
 
-    assert_account_key(
-        accounts.bonfida_sol_vault,
-        &Pubkey::from_str(BONFIDA_SOL_VAULT).unwrap(),
-    )
-    .unwrap();
-
     Ok(accounts)
 }
 
-pub fn claim_bid(
+pub fn claim_bid<'a, 'b: 'a>(
     program_id: &Pubkey,
-    accounts: &[AccountInfo],
+    accounts: &'a [AccountInfo<'b>],
     args: ClaimBidArgs,
 ) -> ProgramResult {
     msg!("+ Processing ClaimBid");
     let accounts = parse_accounts(program_id, accounts)?;
     let clock = Clock::from_account_info(accounts.clock_sysvar)?;
 
-    // The account within the pot must be owned by us.
-    let actual_account: Account = assert_initialized(accounts.bidder_pot_token)?;
-    if actual_account.owner != *accounts.auction.key && &actual_account.owner != program_id {
-        return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
-    }
-
     // Derive and load Auction.
     let auction_bump = assert_derivation(
         program_id,
@@ -125,7 +113,7 @@ pub fn claim_bid(
     ];
 
     // Load the auction and verify this bid is valid.
-    let auction: AuctionData = try_from_slice_unchecked(&accounts.auction.data.borrow())?;
+    let mut auction = AuctionData::from_account_info(accounts.auction)?;
 
     // User must have won the auction in order to claim their funds. Check early as the rest of the
     // checks will be for nothing otherwise.
@@ -167,97 +155,182 @@ pub fn claim_bid(
     }
 
     // Confirm we're looking at the real SPL account for this bidder.
-    let mut bidder_pot: BidderPot =
-        try_from_slice_unchecked(&accounts.bidder_pot.data.borrow_mut())?;
+    let mut bidder_pot = BidderPot::from_account_info(accounts.bidder_pot)?;
     if bidder_pot.bidder_pot != *accounts.bidder_pot_token.key {
         return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
     }
 
-    // Calculate fees
-    let fees = args
-        .fee_percentage
-        .checked_mul(actual_account.amount)
-        .ok_or(AuctionError::NumericalOverflowError)?
-        .checked_div(10000)
-        .ok_or(AuctionError::NumericalOverflowError)?;
-    let ref_fees = if accounts.referrer.is_some() {
-        fees.checked_mul(REF_SHARE)
-            .ok_or(AuctionError::NumericalOverflowError)?
-            .checked_div(100)
-            .ok_or(AuctionError::NumericalOverflowError)?
+    // A native pot holds the bid as lamports and pays out in lamports, the destination, fee
+    // and refund accounts are then wallets. The fee recipient takes the place of its token
+    // account.
+    let native = bidder_pot.is_native(accounts.bidder_pot.key);
+    let (pot_amount, pays_out) = if native {
+        (lamports_above_rent(accounts.bidder_pot)?, true)
     } else {
-        0
+        // The account within the pot must be owned by us.
+        assert_owned_by(accounts.destination, &spl_token::id())?;
+        assert_owned_by(accounts.bidder_pot_token, &spl_token::id())?;
+        let actual_account: Account = assert_initialized(accounts.bidder_pot_token)?;
+        if actual_account.owner != *accounts.auction.key && &actual_account.owner != program_id {
+            return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
+        }
+        (actual_account.amount, &actual_account.owner != program_id)
     };
 
-    let rest_amount = actual_account
-        .amount
-        .checked_sub(fees)
+    // A winner who may pay less than their pot gets a refund account ahead of the referrer.
+    let (bidder_token, referrer) = if auction.refunds_on_claim() {
+        match accounts.remaining.get(0) {
+            Some(bidder_token) => (Some(bidder_token), accounts.remaining.get(1)),
+            None => return Err(ProgramError::NotEnoughAccountKeys),
+        }
+    } else {
+        (None, accounts.remaining.get(0))
+    };
+
+    // The refund can only go back to the bidder.
+    match bidder_token {
+        Some(bidder_token) if native => {
+            if bidder_token.key != accounts.bidder.key {
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+        Some(bidder_token) => {
+            assert_owned_by(bidder_token, &spl_token::id())?;
+            let bidder_account: Account = assert_initialized(bidder_token)?;
+            if bidder_account.owner != *accounts.bidder.key {
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+        None => (),
+    }
+
+    // The referrer recorded with the bid is paid, their account can't be left out or swapped.
+    let referrer = match bidder_pot.referrer {
+        Some(referrer_key) => {
+            let referrer = referrer.ok_or(AuctionError::ReferrerMismatch)?;
+            if native {
+                if *referrer.key != referrer_key {
+                    return Err(AuctionError::ReferrerMismatch.into());
+                }
+            } else {
+                assert_owned_by(referrer, &spl_token::id())?;
+                let referrer_account: Account = assert_initialized(referrer)?;
+                if referrer_account.owner != referrer_key {
+                    return Err(AuctionError::ReferrerMismatch.into());
+                }
+            }
+            Some(referrer)
+        }
+        None => None,
+    };
+
+    // What the winner pays, anything else in the pot is refunded.
+    let price = if auction.refunds_on_claim() {
+        auction
+            .settlement_price(accounts.bidder_pot.key)
+            .ok_or(AuctionError::InvalidState)?
+            .min(pot_amount)
+    } else {
+        pot_amount
+    };
+    let refund = pot_amount
+        .checked_sub(price)
         .ok_or(AuctionError::NumericalOverflowError)?;
-    let fees = fees
+
+    // Fees follow the auction's fee config and can only go to its recipient.
+    let fee_config = match auction.fee_config {
+        Some(_) => {
+            let fee_config = FeeConfig::from_auction(program_id, &auction, accounts.fee_config)?;
+            if *accounts.fee_recipient.key != fee_config.recipient {
+                return Err(AuctionError::FeeConfigMismatch.into());
+            }
+            if !native {
+                assert_owned_by(accounts.fee_recipient_token, &spl_token::id())?;
+                let fee_account: Account = assert_initialized(accounts.fee_recipient_token)?;
+                if fee_account.owner != fee_config.recipient {
+                    return Err(AuctionError::FeeConfigMismatch.into());
+                }
+            }
+            Some(fee_config)
+        }
+        None => None,
+    };
+
+    // Calculate fees from the schedule recorded at creation, the config may have changed since.
+    let (fees, ref_fees) = match (&fee_config, &auction.fee_schedule) {
+        (Some(_), Some(schedule)) => schedule.fees(price, referrer.is_some())?,
+        _ => (0, 0),
+    };
+
+    let rest_amount = price
+        .checked_sub(fees)
+        .ok_or(AuctionError::NumericalOverflowError)?
         .checked_sub(ref_fees)
         .ok_or(AuctionError::NumericalOverflowError)?;
 
-    // Stopgap measure
-    if &actual_account.owner != program_id {
-        // Transfer SPL bid balance back to the user and the bonfida vault
-        spl_token_transfer(TokenTransferParams {
-            source: accounts.bidder_pot_token.clone(),
-            destination: accounts.destination.clone(),
-            authority: accounts.auction.clone(),
-            authority_signer_seeds: auction_seeds,
-            token_program: accounts.token_program.clone(),
-            amount: rest_amount,
-        })?;
-        spl_token_transfer(TokenTransferParams {
-            source: accounts.bidder_pot_token.clone(),
-            destination: accounts.bonfida_vault.clone(),
-            authority: accounts.auction.clone(),
-            authority_signer_seeds: auction_seeds,
-            token_program: accounts.token_program.clone(),
-            amount: fees,
-        })?;
-
-        if ref_fees != 0 {
+    // Moves part of the pot out, in lamports for a native pot and in tokens otherwise.
+    let pay = |destination: &AccountInfo<'b>, amount: u64| -> ProgramResult {
+        if native {
+            transfer_lamports(accounts.bidder_pot, destination, amount)
+        } else {
             spl_token_transfer(TokenTransferParams {
                 source: accounts.bidder_pot_token.clone(),
-                destination: accounts.referrer.unwrap().clone(),
+                destination: destination.clone(),
                 authority: accounts.auction.clone(),
                 authority_signer_seeds: auction_seeds,
                 token_program: accounts.token_program.clone(),
-                amount: ref_fees,
-            })?;
+                amount,
+            })
         }
-    }
+    };
 
-    bidder_pot.emptied = true;
-    bidder_pot.serialize(&mut *accounts.bidder_pot.data.borrow_mut())?;
+    // Stopgap measure
+    if pays_out {
+        // Transfer the bid balance to the destination and the fees to the fee recipient, whose
+        // wallet takes native bids.
+        pay(accounts.destination, rest_amount)?;
+        if fees > 0 {
+            if native {
+                pay(accounts.fee_recipient, fees)?;
+            } else {
+                pay(accounts.fee_recipient_token, fees)?;
+            }
+        }
 
-    // Collect lamports from the buy_now account as an additional fee for this type of sales
-    if buy_now_account_exists(program_id, &args.resource, &accounts.buy_now) {
-        let mut target_lamports = accounts.bonfida_sol_vault.lamports.borrow_mut();
-        let mut buy_now_lamports = accounts.buy_now.lamports.borrow_mut();
+        if ref_fees != 0 {
+            pay(referrer.ok_or(AuctionError::ReferrerMismatch)?, ref_fees)?;
+        }
 
-        **target_lamports += **buy_now_lamports;
-        **buy_now_lamports = 0;
+        if let (Some(bidder_token), true) = (bidder_token, refund > 0) {
+            pay(bidder_token, refund)?;
+        }
     }
 
-    Ok(())
-}
+    if auction.settlement != SettlementMode::FirstPrice && auction.clearing_price.is_none() {
+        auction.clearing_price = auction.final_clearing_price();
+        auction.save(accounts.auction)?;
+    }
 
-fn buy_now_account_exists(program_id: &Pubkey, resource: &Pubkey, buy_now: &AccountInfo) -> bool {
-    let buy_now_path = [BUY_NOW.as_bytes(), program_id.as_ref(), resource.as_ref()];
-    let (buy_now_key, buy_now_bump) = Pubkey::find_program_address(&buy_now_path, program_id);
-    assert_account_key(buy_now, &buy_now_key).unwrap();
-    if buy_now.data_len() != 0 {
-        // If the account exists it must be owned by the program
-        assert_owned_by(buy_now, program_id).unwrap();
-        // To prevent issues when the domains are directly resold before being gc
-        BuyNowData {
-            max_price: u64::MAX,
+    bidder_pot.emptied = true;
+    bidder_pot.save(accounts.bidder_pot)?;
+
+    // Collect lamports from the buy_now account as an additional fee for this type of sales, the
+    // first claim closes it. Without a fee config to check the recipient against, they can only go
+    // to the auction's authority.
+    let buy_now = accounts.remaining.last().filter(|buy_now| {
+        BuyNowData::is_buy_now_account(program_id, &args.resource, buy_now)
+            && buy_now.owner == program_id
+    });
+    let is_buy_now = matches!(
+        auction.state,
+        AuctionState::BuyNowCreated | AuctionState::BuyNowStarted | AuctionState::BuyNowEnded
+    );
+    if let (true, Some(buy_now)) = (is_buy_now, buy_now) {
+        if fee_config.is_none() && *accounts.fee_recipient.key != auction.authority {
+            return Err(AuctionError::InvalidAuthority.into());
         }
-        .serialize(&mut *buy_now.data.borrow_mut())
-        .unwrap();
-        return true;
+        BuyNowData::close(buy_now, accounts.fee_recipient)?;
     }
-    false
+
+    Ok(())
 }
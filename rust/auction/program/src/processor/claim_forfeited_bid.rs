@@ -0,0 +1,232 @@
+//! Settles a sealed bid that was never revealed. Once the reveal phase is over the auction
+//! authority collects the forfeited share of the deposit, and the rest goes back to the bidder.
+
+use crate::{
+    errors::AuctionError,
+    processor::{AuctionData, BidState, BidderMetadata, BidderPot, SealedBid},
+    utils::{
+        assert_derivation, assert_initialized, assert_owned_by, assert_signer, spl_token_transfer,
+        TokenTransferParams,
+    },
+    PREFIX,
+};
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        borsh::try_from_slice_unchecked,
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::{clock::Clock, Sysvar},
+    },
+    spl_token::state::Account,
+};
+
+/// Arguments for the ClaimForfeitedBid instruction discriminant.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct ClaimForfeitedBidArgs {
+    pub resource: Pubkey,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    destination: &'a AccountInfo<'b>,
+    bidder_token: &'a AccountInfo<'b>,
+    bidder_pot_token: &'a AccountInfo<'b>,
+    bidder_pot: &'a AccountInfo<'b>,
+    sealed_bid: &'a AccountInfo<'b>,
+    bidder_meta: &'a AccountInfo<'b>,
+    authority: &'a AccountInfo<'b>,
+    auction: &'a AccountInfo<'b>,
+    bidder: &'a AccountInfo<'b>,
+    mint: &'a AccountInfo<'b>,
+    clock_sysvar: &'a AccountInfo<'b>,
+    token_program: &'a AccountInfo<'b>,
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        destination: next_account_info(account_iter)?,
+        bidder_token: next_account_info(account_iter)?,
+        bidder_pot_token: next_account_info(account_iter)?,
+        bidder_pot: next_account_info(account_iter)?,
+        sealed_bid: next_account_info(account_iter)?,
+        bidder_meta: next_account_info(account_iter)?,
+        authority: next_account_info(account_iter)?,
+        auction: next_account_info(account_iter)?,
+        bidder: next_account_info(account_iter)?,
+        mint: next_account_info(account_iter)?,
+        clock_sysvar: next_account_info(account_iter)?,
+        token_program: next_account_info(account_iter)?,
+    };
+
+    assert_owned_by(accounts.auction, program_id)?;
+    assert_owned_by(accounts.bidder_pot, program_id)?;
+    assert_owned_by(accounts.sealed_bid, program_id)?;
+    assert_owned_by(accounts.bidder_meta, program_id)?;
+    assert_owned_by(accounts.mint, &spl_token::id())?;
+    assert_owned_by(accounts.destination, &spl_token::id())?;
+    assert_owned_by(accounts.bidder_token, &spl_token::id())?;
+    assert_owned_by(accounts.bidder_pot_token, &spl_token::id())?;
+    assert_signer(accounts.authority)?;
+
+    Ok(accounts)
+}
+
+pub fn claim_forfeited_bid(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ClaimForfeitedBidArgs,
+) -> ProgramResult {
+    msg!("+ Processing ClaimForfeitedBid");
+    let accounts = parse_accounts(program_id, accounts)?;
+    let clock = Clock::from_account_info(accounts.clock_sysvar)?;
+
+    // Derive and load Auction.
+    let auction_bump = assert_derivation(
+        program_id,
+        accounts.auction,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+        ],
+    )?;
+
+    let auction_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+        &[auction_bump],
+    ];
+
+    let auction = AuctionData::from_account_info(accounts.auction)?;
+    let forfeit_basis_points = match &auction.bid_state {
+        BidState::SealedBid { config, .. } => config.forfeit_basis_points,
+        _ => return Err(AuctionError::SealedBidMismatch.into()),
+    };
+
+    if auction.authority != *accounts.authority.key {
+        return Err(AuctionError::InvalidAuthority.into());
+    }
+
+    if auction.token_mint != *accounts.mint.key {
+        return Err(AuctionError::IncorrectMint.into());
+    }
+
+    // Bidders keep the chance to reveal until the auction is over.
+    if !auction.ended(clock.unix_timestamp)? {
+        return Err(AuctionError::InvalidState.into());
+    }
+
+    assert_derivation(
+        program_id,
+        accounts.bidder_pot,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            accounts.bidder.key.as_ref(),
+        ],
+    )?;
+
+    assert_derivation(
+        program_id,
+        accounts.sealed_bid,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            accounts.bidder.key.as_ref(),
+            "sealed".as_bytes(),
+        ],
+    )?;
+
+    assert_derivation(
+        program_id,
+        accounts.bidder_meta,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            accounts.bidder.key.as_ref(),
+            "metadata".as_bytes(),
+        ],
+    )?;
+
+    let sealed_bid: SealedBid = try_from_slice_unchecked(&accounts.sealed_bid.data.borrow())?;
+    if sealed_bid.revealed.is_some() {
+        msg!("Revealed bids are claimed or cancelled as usual");
+        return Err(AuctionError::InvalidState.into());
+    }
+
+    let metadata: BidderMetadata = try_from_slice_unchecked(&accounts.bidder_meta.data.borrow())?;
+    if metadata.cancelled {
+        return Err(AuctionError::BidderPotEmpty.into());
+    }
+
+    // Confirm we're looking at the real SPL account for this bidder.
+    let mut bidder_pot = BidderPot::from_account_info(accounts.bidder_pot)?;
+    if bidder_pot.bidder_pot != *accounts.bidder_pot_token.key {
+        return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
+    }
+    if bidder_pot.emptied {
+        return Err(AuctionError::BidderPotEmpty.into());
+    }
+
+    // The remainder can only go back to the bidder.
+    let bidder_token: Account = assert_initialized(accounts.bidder_token)?;
+    if bidder_token.owner != *accounts.bidder.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let pot_account: Account = assert_initialized(accounts.bidder_pot_token)?;
+    let forfeit = (pot_account.amount as u128)
+        .checked_mul(forfeit_basis_points as u128)
+        .ok_or(AuctionError::NumericalOverflowError)?
+        .checked_div(10000)
+        .ok_or(AuctionError::NumericalOverflowError)? as u64;
+    let refund = pot_account
+        .amount
+        .checked_sub(forfeit)
+        .ok_or(AuctionError::NumericalOverflowError)?;
+
+    if forfeit > 0 {
+        spl_token_transfer(TokenTransferParams {
+            source: accounts.bidder_pot_token.clone(),
+            destination: accounts.destination.clone(),
+            authority: accounts.auction.clone(),
+            authority_signer_seeds: auction_seeds,
+            token_program: accounts.token_program.clone(),
+            amount: forfeit,
+        })?;
+    }
+    if refund > 0 {
+        spl_token_transfer(TokenTransferParams {
+            source: accounts.bidder_pot_token.clone(),
+            destination: accounts.bidder_token.clone(),
+            authority: accounts.auction.clone(),
+            authority_signer_seeds: auction_seeds,
+            token_program: accounts.token_program.clone(),
+            amount: refund,
+        })?;
+    }
+
+    bidder_pot.emptied = true;
+    bidder_pot.save(accounts.bidder_pot)?;
+
+    BidderMetadata {
+        cancelled: true,
+        ..metadata
+    }
+    .serialize(&mut &mut accounts.bidder_meta.data.borrow_mut()[..])?;
+
+    Ok(())
+}
@@ -1,11 +1,10 @@
-use std::str::FromStr;
-
 use crate::{
     errors::AuctionError,
-    processor::BidderPot,
+    processor::{AuctionData, BidderPot, FeeConfig},
     utils::{
         assert_account_key, assert_derivation, assert_initialized, assert_owned_by, assert_signer,
-        create_or_allocate_account_raw, spl_token_transfer, TokenTransferParams,
+        create_or_allocate_account_raw, lamports_above_rent, spl_token_transfer, transfer_lamports,
+        TokenTransferParams,
     },
     PREFIX,
 };
@@ -16,7 +15,6 @@ use solana_program::{
     program_pack::Pack,
 };
 
-use super::EXCLUSIVE_AUCTION_AUTHORITY;
 use {
     solana_program::{
         account_info::{next_account_info, AccountInfo},
@@ -42,7 +40,7 @@ struct Accounts<'a, 'b: 'a> {
     authority: &'a AccountInfo<'b>,
     spl_token_program: &'a AccountInfo<'b>,
     bidder_pot_token: &'a AccountInfo<'b>,
-    bonfida_vault: &'a AccountInfo<'b>,
+    fee_config: &'a AccountInfo<'b>,
 }
 
 fn parse_account<'a, 'b: 'a>(
@@ -60,15 +58,11 @@ fn parse_account<'a, 'b: 'a>(
         authority: next_account_info(account_iter)?,
         spl_token_program: next_account_info(account_iter)?,
         bidder_pot_token: next_account_info(account_iter)?,
-        bonfida_vault: next_account_info(account_iter)?,
+        fee_config: next_account_info(account_iter)?,
     };
 
     assert_owned_by(accounts.auction, program_id)?;
     assert_signer(accounts.authority)?;
-    assert_owned_by(
-        accounts.authority,
-        &Pubkey::from_str(EXCLUSIVE_AUCTION_AUTHORITY).unwrap(),
-    )?;
     assert_account_key(accounts.system, &solana_program::system_program::ID)?;
     return Ok(accounts);
 }
@@ -113,6 +107,24 @@ pub fn close_auction_pot<'a, 'b: 'a>(
     ];
 
     if !accounts.bidder_pot.data_is_empty() && !accounts.auction.data_is_empty() {
+        // Pots are closed by the operator running the auction, or its authority without one.
+        let auction = AuctionData::from_account_info(accounts.auction)?;
+        let closer = match auction.fee_config {
+            Some(_) => FeeConfig::from_auction(program_id, &auction, accounts.fee_config)?.admin,
+            None => auction.authority,
+        };
+        if closer != *accounts.authority.key {
+            return Err(AuctionError::InvalidAuthority.into());
+        }
+
+        // A native pot may still hold a bid that was never claimed or cancelled, it goes back to
+        // the bidder rather than to the destination.
+        let bidder_pot = BidderPot::from_account_info(accounts.bidder_pot)?;
+        if bidder_pot.is_native(accounts.bidder_pot.key) {
+            let amount = lamports_above_rent(accounts.bidder_pot)?;
+            transfer_lamports(accounts.bidder_pot, accounts.bidder, amount)?;
+        }
+
         let mut bidder_pot_lamports = accounts.bidder_pot.lamports.borrow_mut();
         let mut destination_lamports = accounts.destination.lamports.borrow_mut();
         **destination_lamports += **bidder_pot_lamports;
@@ -127,7 +139,7 @@ pub fn close_auction_pot<'a, 'b: 'a>(
         //     .amount;
         // spl_token_transfer(TokenTransferParams {
         //     source: accounts.bidder_pot_token.clone(),
-        //     destination: accounts.bonfida_vault.clone(),
+        //     destination: accounts.fee_recipient_token.clone(),
         //     authority: accounts.auction.clone(),
         //     authority_signer_seeds: &auction_seeds,
         //     token_program: accounts.spl_token_program.clone(),
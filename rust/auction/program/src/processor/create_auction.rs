@@ -1,19 +1,15 @@
-use std::str::FromStr;
-
 use solana_program::clock::UnixTimestamp;
 
 use crate::{
     errors::AuctionError,
     processor::{
-        AuctionData, AuctionState, Bid, BidState, BuyNowData, PriceFloor, WinnerLimit,
-        BASE_AUCTION_DATA_SIZE, BUY_NOW_DATA_LEN,
+        AuctionData, AuctionState, BidIncrement, BidState, BidderGate, DutchAuctionConfig,
+        FeeConfig, PriceFloor, SealedBidConfig, SettlementMode, WinnerLimit,
     },
     utils::{assert_owned_by, create_or_allocate_account_raw},
-    BUY_NOW, PREFIX,
+    PREFIX,
 };
 
-use super::EXCLUSIVE_AUCTION_AUTHORITY;
-
 use {
     borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{
@@ -37,14 +33,40 @@ pub struct CreateAuctionArgs {
     pub end_auction_at: Option<UnixTimestamp>,
     /// Gap time is how much time after the previous bid where the auction ends. See AuctionData.
     pub end_auction_gap: Option<UnixTimestamp>,
+    /// Cap on how far bids within the gap can push the end time. See AuctionData.
+    pub max_gap_extension: Option<UnixTimestamp>,
     /// Token mint for the SPL token used for bidding.
     pub token_mint: Pubkey,
     /// The resource being auctioned. See AuctionData.
     pub resource: Pubkey,
     /// Set a price floor.
     pub price_floor: PriceFloor,
-    /// Max price of the auction i.e buy now price
+    /// Max price of the auction i.e buy now price. See AuctionData.
     pub max_price: Option<u64>,
+    /// Run a Dutch auction with this pricing schedule instead, there is a single winner so
+    /// `winners` is ignored.
+    pub dutch_auction: Option<DutchAuctionConfig>,
+    /// Run a sealed bid auction, bids are committed as hashes and revealed after
+    /// `end_auction_at`. Requires capped winners.
+    pub sealed_bid: Option<SealedBidConfig>,
+    /// How winners are charged, first or second price.
+    pub settlement: SettlementMode,
+    /// How much a new bid must beat the top bid by.
+    pub bid_increment: BidIncrement,
+    /// Sell `winners` identical units, each bid names a price per unit and a quantity. Requires
+    /// capped winners.
+    pub multi_unit: bool,
+    /// Take bids in lamports held by the bidder pots instead of SPL tokens. Requires the native
+    /// mint as `token_mint`.
+    pub native_bids: bool,
+    /// Time at which the auction starts by itself, the end time then counts from it rather than
+    /// from a StartAuction call.
+    pub start_at: Option<UnixTimestamp>,
+    /// FeeConfig whose recipient takes the fees when bids are claimed, no fees are taken without
+    /// one. Its current schedule is recorded in the auction and cannot change afterwards.
+    pub fee_config: Option<Pubkey>,
+    /// Restrict bidding to an allowlist or to holders of a mint. See AuctionData.
+    pub gate: Option<BidderGate>,
 }
 
 struct Accounts<'a, 'b: 'a> {
@@ -53,12 +75,13 @@ struct Accounts<'a, 'b: 'a> {
     rent: &'a AccountInfo<'b>,
     system: &'a AccountInfo<'b>,
     authority: &'a AccountInfo<'b>,
-    buy_now: Option<&'a AccountInfo<'b>>,
+    fee_config: Option<&'a AccountInfo<'b>>,
 }
 
 fn parse_accounts<'a, 'b: 'a>(
     program_id: &Pubkey,
     accounts: &'a [AccountInfo<'b>],
+    args: &CreateAuctionArgs,
 ) -> Result<Accounts<'a, 'b>, ProgramError> {
     let account_iter = &mut accounts.iter();
     let accounts = Accounts {
@@ -67,16 +90,12 @@ fn parse_accounts<'a, 'b: 'a>(
         rent: next_account_info(account_iter)?,
         system: next_account_info(account_iter)?,
         authority: next_account_info(account_iter)?,
-        buy_now: next_account_info(account_iter).ok(),
+        fee_config: next_account_info(account_iter).ok(),
     };
     if !accounts.authority.is_signer {
         msg!("The authority account should be a signer");
         return Err(ProgramError::MissingRequiredSignature);
     }
-    let exclusive_auth = Pubkey::from_str(EXCLUSIVE_AUCTION_AUTHORITY).unwrap();
-    if &exclusive_auth != accounts.authority.owner {
-        msg!("This command can only be called by the Bonfida name auctioning smart contract");
-    }
     Ok(accounts)
 }
 
@@ -86,7 +105,7 @@ pub fn create_auction(
     args: CreateAuctionArgs,
 ) -> ProgramResult {
     msg!("+ Processing CreateAuction");
-    let accounts = parse_accounts(program_id, accounts)?;
+    let accounts = parse_accounts(program_id, accounts, &args)?;
 
     let auction_path = [
         PREFIX.as_bytes(),
@@ -100,17 +119,93 @@ pub fn create_auction(
     if auction_key != *accounts.auction.key {
         return Err(AuctionError::InvalidAuctionAccount.into());
     }
-    // The data must be large enough to hold at least the number of winners.
-    let auction_size = match args.winners {
-        WinnerLimit::Capped(n) => mem::size_of::<Bid>() * n + BASE_AUCTION_DATA_SIZE,
-        WinnerLimit::Unlimited(_) => BASE_AUCTION_DATA_SIZE,
+    // A Dutch auction already ends on the first bid that meets its price.
+    if args.dutch_auction.is_some() && args.max_price.is_some() {
+        msg!("A Dutch auction cannot also have a buy now price");
+        return Err(AuctionError::InvalidDutchAuctionConfig.into());
+    }
+
+    match (args.end_auction_gap, args.max_gap_extension) {
+        (Some(gap), _) if gap <= 0 => return Err(AuctionError::InvalidEndAuctionGap.into()),
+        (_, Some(cap)) if cap < 0 => return Err(AuctionError::InvalidEndAuctionGap.into()),
+        (None, Some(_)) => return Err(AuctionError::InvalidEndAuctionGap.into()),
+        _ => (),
+    }
+
+    args.bid_increment.validate()?;
+
+    // Sealed bids are revealed against a fixed end time, so they can't end early on a buy now
+    // price or a gap, and need a known number of winners.
+    if let Some(config) = &args.sealed_bid {
+        config.validate()?;
+        if args.dutch_auction.is_some()
+            || args.max_price.is_some()
+            || args.end_auction_at.is_none()
+            || args.end_auction_gap.is_some()
+        {
+            msg!("A sealed bid auction needs an end time, and no buy now price, gap or Dutch pricing");
+            return Err(AuctionError::InvalidSealedBidConfig.into());
+        }
+        if let WinnerLimit::Unlimited(_) = args.winners {
+            msg!("A sealed bid auction needs a capped number of winners");
+            return Err(AuctionError::InvalidSealedBidConfig.into());
+        }
+    }
+
+    if args.multi_unit
+        && (args.dutch_auction.is_some()
+            || args.sealed_bid.is_some()
+            || !matches!(args.winners, WinnerLimit::Capped(_)))
+    {
+        return Err(AuctionError::InvalidMultiUnitConfig.into());
+    }
+
+    // Sealed bid deposits are refunded in tokens as bids are revealed.
+    if args.native_bids
+        && (args.token_mint != spl_token::native_mint::id() || args.sealed_bid.is_some())
+    {
+        return Err(AuctionError::InvalidNativeBidConfig.into());
+    }
+
+    let bid_state = match (args.dutch_auction, args.sealed_bid, args.winners) {
+        (None, None, WinnerLimit::Capped(n)) if args.multi_unit => BidState::new_multi_unit(n),
+        (Some(config), _, _) => {
+            config.validate()?;
+            BidState::new_dutch(config)
+        }
+        (None, Some(config), WinnerLimit::Capped(n)) => BidState::new_sealed(n, config),
+        (None, _, WinnerLimit::Capped(n)) => BidState::new_english(n),
+        (None, _, WinnerLimit::Unlimited(_)) => BidState::new_open_edition(),
     };
 
-    let bid_state = match args.winners {
-        WinnerLimit::Capped(n) => BidState::new_english(n),
-        WinnerLimit::Unlimited(_) => BidState::new_open_edition(),
+    // Second price settlement needs losing bids to price winners against, and a uniform price
+    // needs units to price.
+    match (&args.settlement, &bid_state) {
+        (SettlementMode::FirstPrice, _)
+        | (SettlementMode::SecondPrice, BidState::EnglishAuction { .. })
+        | (SettlementMode::SecondPrice, BidState::SealedBid { .. })
+        | (SettlementMode::UniformPrice(_), BidState::MultiUnit { .. }) => (),
+        _ => return Err(AuctionError::InvalidSettlementMode.into()),
+    }
+
+    // Bidders are charged the fee schedule in force now, later changes to the config only apply
+    // to new auctions.
+    let fee_schedule = match (args.fee_config, accounts.fee_config) {
+        (Some(key), Some(fee_config)) => {
+            if *fee_config.key != key {
+                return Err(AuctionError::FeeConfigMismatch.into());
+            }
+            assert_owned_by(fee_config, program_id)?;
+            let fee_config: FeeConfig = try_from_slice_unchecked(&fee_config.data.borrow())?;
+            Some(fee_config.schedule)
+        }
+        (Some(_), None) => return Err(ProgramError::NotEnoughAccountKeys),
+        (None, _) => None,
     };
 
+    // The data must be large enough to hold at least the number of winners.
+    let auction_size = AuctionData::size(&bid_state);
+
     if accounts.auction.data_is_empty() {
         // Create auction account with enough space for a winner tracking.
         create_or_allocate_account_raw(
@@ -128,53 +223,15 @@ pub fn create_auction(
             ],
         )?;
     } else {
-        let parsed: AuctionData = try_from_slice_unchecked(&accounts.auction.data.borrow())?;
+        let parsed = AuctionData::from_account_info(accounts.auction)?;
         if &parsed.authority != accounts.authority.key {
             msg!("Invalid authority account for already existing auction");
             return Err(ProgramError::InvalidArgument);
         }
     }
 
-    // Allow buy now
-    let mut is_buy_now = args.max_price.is_some() && accounts.buy_now.is_some();
-    if is_buy_now {
-        let max_price = args.max_price.unwrap();
-        let buy_now_account = accounts.buy_now.unwrap();
-
-        let buy_now_path = [
-            BUY_NOW.as_bytes(),
-            program_id.as_ref(),
-            &args.resource.to_bytes(),
-        ];
-
-        let (buy_now_key, buy_now_bump) = Pubkey::find_program_address(&buy_now_path, program_id);
-
-        if buy_now_account.key != &buy_now_key {
-            msg!("Invalid buy now account provided");
-            return Err(ProgramError::InvalidArgument);
-        }
-
-        if buy_now_account.data_is_empty() {
-            create_or_allocate_account_raw(
-                *program_id,
-                buy_now_account,
-                accounts.rent,
-                accounts.system,
-                accounts.payer,
-                BUY_NOW_DATA_LEN,
-                &[
-                    BUY_NOW.as_bytes(),
-                    program_id.as_ref(),
-                    &args.resource.to_bytes(),
-                    &[buy_now_bump],
-                ],
-            )?;
-        }
-        BuyNowData { max_price }.serialize(&mut *buy_now_account.data.borrow_mut())?;
-    }
-
     // Configure Auction.
-    AuctionData {
+    let auction = AuctionData {
         authority: *accounts.authority.key,
         bid_state: bid_state,
         resource: args.resource,
@@ -183,10 +240,25 @@ pub fn create_auction(
         ended_at: None,
         last_bid: None,
         price_floor: args.price_floor,
-        state: AuctionState::create(is_buy_now),
+        state: AuctionState::Created,
         token_mint: args.token_mint,
-    }
-    .serialize(&mut *accounts.auction.data.borrow_mut())?;
+        settlement: args.settlement,
+        highest_losing_bid: 0,
+        clearing_price: None,
+        max_gap_extension: args.max_gap_extension,
+        hard_end_at: None,
+        bid_increment: args.bid_increment,
+        has_bid_history: false,
+        start_at: args.start_at,
+        native_bids: args.native_bids,
+        fee_config: args.fee_config,
+        fee_schedule,
+        gate: args.gate,
+        buy_now_price: args.max_price,
+        extends_end: true,
+    };
+    auction.validate_buy_now()?;
+    auction.save(accounts.auction)?;
 
     Ok(())
 }
@@ -0,0 +1,135 @@
+//! Creates the bid history of an auction, a ring buffer that place_bid and cancel_bid append to
+//! once it exists. Only the authority can add one, and only before the auction starts so that
+//! the history covers every bid.
+
+use crate::{
+    errors::AuctionError,
+    processor::{AuctionData, AuctionState, BidHistory, MAX_BID_HISTORY_CAPACITY},
+    utils::{assert_derivation, assert_owned_by, assert_signer, create_or_allocate_account_raw},
+    PREFIX,
+};
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct CreateBidHistoryArgs {
+    /// The resource being auctioned.
+    pub resource: Pubkey,
+    /// Number of events kept before the oldest are overwritten.
+    pub capacity: u32,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    authority: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+    auction: &'a AccountInfo<'b>,
+    bid_history: &'a AccountInfo<'b>,
+    rent: &'a AccountInfo<'b>,
+    system: &'a AccountInfo<'b>,
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        authority: next_account_info(account_iter)?,
+        payer: next_account_info(account_iter)?,
+        auction: next_account_info(account_iter)?,
+        bid_history: next_account_info(account_iter)?,
+        rent: next_account_info(account_iter)?,
+        system: next_account_info(account_iter)?,
+    };
+
+    assert_owned_by(accounts.auction, program_id)?;
+    assert_signer(accounts.authority)?;
+    assert_signer(accounts.payer)?;
+
+    Ok(accounts)
+}
+
+pub fn create_bid_history(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: CreateBidHistoryArgs,
+) -> ProgramResult {
+    msg!("+ Processing CreateBidHistory");
+    let accounts = parse_accounts(program_id, accounts)?;
+
+    assert_derivation(
+        program_id,
+        accounts.auction,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+        ],
+    )?;
+
+    let mut auction = AuctionData::from_account_info(accounts.auction)?;
+    if auction.authority != *accounts.authority.key {
+        return Err(AuctionError::InvalidAuthority.into());
+    }
+
+    if auction.state != AuctionState::Created && auction.state != AuctionState::BuyNowCreated {
+        return Err(AuctionError::InvalidState.into());
+    }
+
+    if auction.has_bid_history || !accounts.bid_history.data_is_empty() {
+        return Err(AuctionError::BidHistoryAlreadyExists.into());
+    }
+
+    if args.capacity == 0 || args.capacity > MAX_BID_HISTORY_CAPACITY {
+        return Err(AuctionError::InvalidBidHistoryCapacity.into());
+    }
+
+    let bump = assert_derivation(
+        program_id,
+        accounts.bid_history,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            "history".as_bytes(),
+        ],
+    )?;
+
+    create_or_allocate_account_raw(
+        *program_id,
+        accounts.bid_history,
+        accounts.rent,
+        accounts.system,
+        accounts.payer,
+        BidHistory::size(args.capacity),
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            "history".as_bytes(),
+            &[bump],
+        ],
+    )?;
+
+    BidHistory {
+        auction: *accounts.auction.key,
+        capacity: args.capacity,
+        count: 0,
+    }
+    .serialize(&mut &mut accounts.bid_history.data.borrow_mut()[..])?;
+
+    auction.has_bid_history = true;
+    auction.save(accounts.auction)?;
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use crate::{
     errors::AuctionError,
-    processor::BidderPot,
+    processor::{BidderPot, BIDDER_POT_LEN},
     utils::{
         assert_account_key, assert_derivation, assert_initialized, assert_owned_by, assert_signer,
         create_or_allocate_account_raw,
@@ -101,14 +101,14 @@ pub fn create_bidder_pot<'a, 'b: 'a>(
             accounts.rent,
             accounts.system,
             accounts.payer,
-            mem::size_of::<BidderPot>(),
+            BIDDER_POT_LEN,
             bump_authority_seeds,
         )?;
-        let mut pot: BidderPot = try_from_slice_unchecked(&accounts.bidder_pot.data.borrow_mut())?;
+        let mut pot = BidderPot::from_account_info(accounts.bidder_pot)?;
         pot.bidder_pot = *accounts.bidder_pot_token.key;
         pot.bidder_act = *accounts.bidder.key;
         pot.auction_act = *accounts.auction.key;
-        pot.serialize(&mut *accounts.bidder_pot.data.borrow_mut())?;
+        pot.save(accounts.bidder_pot)?;
     } else {
         msg!("Bidder pot already exists");
         return Err(ProgramError::AccountAlreadyInitialized);
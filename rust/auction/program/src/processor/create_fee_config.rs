@@ -0,0 +1,101 @@
+//! Creates the FeeConfig of an operator. The signing admin's key seeds the config, so each
+//! operator running auctions on this program gets their own recipient and fee schedule.
+
+use crate::{
+    processor::{FeeConfig, FeeSchedule, FEE_CONFIG_LEN},
+    utils::{assert_derivation, assert_signer, create_or_allocate_account_raw},
+    PREFIX,
+};
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct CreateFeeConfigArgs {
+    /// Wallet receiving the fees.
+    pub recipient: Pubkey,
+    pub schedule: FeeSchedule,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    admin: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+    fee_config: &'a AccountInfo<'b>,
+    rent: &'a AccountInfo<'b>,
+    system: &'a AccountInfo<'b>,
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        admin: next_account_info(account_iter)?,
+        payer: next_account_info(account_iter)?,
+        fee_config: next_account_info(account_iter)?,
+        rent: next_account_info(account_iter)?,
+        system: next_account_info(account_iter)?,
+    };
+
+    assert_signer(accounts.admin)?;
+    assert_signer(accounts.payer)?;
+
+    Ok(accounts)
+}
+
+pub fn create_fee_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: CreateFeeConfigArgs,
+) -> ProgramResult {
+    msg!("+ Processing CreateFeeConfig");
+    let accounts = parse_accounts(program_id, accounts)?;
+
+    args.schedule.validate()?;
+
+    let bump = assert_derivation(
+        program_id,
+        accounts.fee_config,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.admin.key.as_ref(),
+            "fee_config".as_bytes(),
+        ],
+    )?;
+
+    create_or_allocate_account_raw(
+        *program_id,
+        accounts.fee_config,
+        accounts.rent,
+        accounts.system,
+        accounts.payer,
+        FEE_CONFIG_LEN,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.admin.key.as_ref(),
+            "fee_config".as_bytes(),
+            &[bump],
+        ],
+    )?;
+
+    FeeConfig {
+        admin: *accounts.admin.key,
+        recipient: args.recipient,
+        schedule: args.schedule,
+    }
+    .serialize(&mut &mut accounts.fee_config.data.borrow_mut()[..])?;
+
+    Ok(())
+}
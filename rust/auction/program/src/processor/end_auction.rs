@@ -94,25 +94,38 @@ pub fn end_auction<'a, 'b: 'a>(
     )?;
 
     // End auction.
-    let mut auction: AuctionData = try_from_slice_unchecked(&accounts.auction.data.borrow())?;
+    let mut auction = AuctionData::from_account_info(accounts.auction)?;
 
     // Check authority is correct.
     if auction.authority != *accounts.authority.key {
         return Err(AuctionError::InvalidAuthority.into());
     }
 
-    // As long as it hasn't already ended.
-    if auction.ended(clock.unix_timestamp)? {
-        return Err(AuctionError::AuctionTransitionInvalid.into());
-    }
+    // An auction past its end time that nobody settled keeps that end time, this is how its
+    // blinded price floor gets revealed. Ended auctions are refused by the state transition.
+    let ended = auction.ended(clock.unix_timestamp)?;
+    let ended_at = if ended {
+        auction.ended_at
+    } else {
+        Some(clock.unix_timestamp)
+    };
 
-    AuctionData {
-        ended_at: Some(clock.unix_timestamp),
+    let mut auction = AuctionData {
+        ended_at,
         state: auction.state.end()?,
         price_floor: reveal(auction.price_floor, args.reveal)?,
         ..auction
+    };
+
+    // With the floor revealed, an auction no bid beat ends with nobody winning. Sealed bids are
+    // only all in once their reveal period is over.
+    let bids_final = ended || !matches!(auction.bid_state, BidState::SealedBid { .. });
+    if bids_final && !auction.reserve_met() {
+        msg!("No bid met the reserve");
+        auction.state = AuctionState::ReserveNotMet;
     }
-    .serialize(&mut *accounts.auction.data.borrow_mut())?;
+
+    auction.save(accounts.auction)?;
 
     Ok(())
 }
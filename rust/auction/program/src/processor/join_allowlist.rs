@@ -0,0 +1,134 @@
+//! Admits a wallet to an allowlisted auction. The wallet proves once that it is a leaf of the
+//! auction's merkle tree, its bids then only need to pass the allowlist pass this creates.
+
+use crate::{
+    errors::AuctionError,
+    processor::{AllowlistPass, AuctionData, BidderGate, ALLOWLIST_PASS_LEN},
+    utils::{assert_derivation, assert_owned_by, assert_signer, create_or_allocate_account_raw},
+    PREFIX,
+};
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        hash::Hash,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+/// Arguments for the JoinAllowlist instruction discriminant.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct JoinAllowlistArgs {
+    /// The resource being auctioned. See AuctionData.
+    pub resource: Pubkey,
+    /// Sibling hashes from the bidder's leaf up to the root, see BidderGate.
+    pub proof: Vec<Hash>,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    bidder: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+    auction: &'a AccountInfo<'b>,
+    pass: &'a AccountInfo<'b>,
+    rent: &'a AccountInfo<'b>,
+    system: &'a AccountInfo<'b>,
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        bidder: next_account_info(account_iter)?,
+        payer: next_account_info(account_iter)?,
+        auction: next_account_info(account_iter)?,
+        pass: next_account_info(account_iter)?,
+        rent: next_account_info(account_iter)?,
+        system: next_account_info(account_iter)?,
+    };
+
+    assert_owned_by(accounts.auction, program_id)?;
+    assert_signer(accounts.bidder)?;
+    assert_signer(accounts.payer)?;
+
+    Ok(accounts)
+}
+
+pub fn join_allowlist(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: JoinAllowlistArgs,
+) -> ProgramResult {
+    msg!("+ Processing JoinAllowlist");
+    let accounts = parse_accounts(program_id, accounts)?;
+
+    assert_derivation(
+        program_id,
+        accounts.auction,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+        ],
+    )?;
+
+    let auction = AuctionData::from_account_info(accounts.auction)?;
+    let root = match auction.gate {
+        Some(BidderGate::Allowlist(root)) => root,
+        _ => {
+            msg!("The auction has no allowlist");
+            return Err(AuctionError::BidderNotAllowed.into());
+        }
+    };
+
+    if !BidderGate::verify_proof(&root, accounts.bidder.key, &args.proof) {
+        return Err(AuctionError::InvalidAllowlistProof.into());
+    }
+
+    let bump = assert_derivation(
+        program_id,
+        accounts.pass,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            accounts.bidder.key.as_ref(),
+            "allowlist".as_bytes(),
+        ],
+    )?;
+
+    if !accounts.pass.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    create_or_allocate_account_raw(
+        *program_id,
+        accounts.pass,
+        accounts.rent,
+        accounts.system,
+        accounts.payer,
+        ALLOWLIST_PASS_LEN,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            accounts.bidder.key.as_ref(),
+            "allowlist".as_bytes(),
+            &[bump],
+        ],
+    )?;
+
+    AllowlistPass {
+        auction: *accounts.auction.key,
+        bidder: *accounts.bidder.key,
+    }
+    .serialize(&mut &mut accounts.pass.data.borrow_mut()[..])?;
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 //! Places a bid on a running auction, the logic here implements a standard English auction
-//! mechanism, once the auction starts, new bids can be made until 10 minutes has passed with no
-//! new bid. At this point the auction ends.
+//! mechanism, once the auction starts, new bids can be made until its end time. A bid placed within
+//! the auction's gap of the end pushes the end out by that gap, up to an optional cap.
 //!
 //! Possible Attacks to Consider:
 //!
@@ -16,13 +16,15 @@ use solana_program::system_program;
 use crate::{
     errors::AuctionError,
     processor::{
-        AuctionData, AuctionState, Bid, BidderMetadata, BidderPot, BuyNowData, PriceFloor,
+        AuctionData, AuctionState, Bid, BidEvent, BidEventKind, BidHistory, BidState, BidderGate,
+        BidderMetadata, BidderPot, BuyNowData, PriceFloor, ReferralRegistry, SealedBid,
+        BIDDER_POT_LEN, SEALED_BID_LEN,
     },
     utils::{
         allocate_and_create_token_account, assert_derivation, assert_initialized, assert_owned_by,
         assert_signer, create_or_allocate_account_raw, spl_token_transfer, TokenTransferParams,
     },
-    BUY_NOW, PREFIX,
+    MINT_DECIMALS, PREFIX,
 };
 
 use super::BIDDER_METADATA_LEN;
@@ -33,9 +35,11 @@ use {
         account_info::{next_account_info, AccountInfo},
         borsh::try_from_slice_unchecked,
         entrypoint::ProgramResult,
+        hash::Hash,
         msg,
         program::{invoke, invoke_signed},
         program_error::ProgramError,
+        program_option::COption,
         program_pack::Pack,
         pubkey::Pubkey,
         rent::Rent,
@@ -43,7 +47,7 @@ use {
         system_instruction::create_account,
         sysvar::{clock::Clock, Sysvar},
     },
-    spl_token::state::Account,
+    spl_token::state::{Account, Mint},
     std::mem,
 };
 
@@ -57,6 +61,30 @@ pub struct PlaceBidArgs {
     pub resource: Pubkey,
 }
 
+/// Arguments for the PlaceMultiUnitBid instruction discriminant.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct PlaceMultiUnitBidArgs {
+    /// Price bid for each unit.
+    pub amount: u64,
+    /// Number of units bid for, the pot is charged the price of all of them.
+    pub quantity: u64,
+    /// Resource being bid on.
+    pub resource: Pubkey,
+}
+
+/// Arguments for the PlaceSealedBid instruction discriminant.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct PlaceSealedBidArgs {
+    /// Tokens put in the bidder pot, the revealed bid can be anything up to this amount.
+    pub deposit: u64,
+    /// Hash of the auction, the bidder, the bid amount and a salt, see SealedBid::commit.
+    pub commitment: Hash,
+    /// Resource being bid on.
+    pub resource: Pubkey,
+}
+
 struct Accounts<'a, 'b: 'a> {
     auction: &'a AccountInfo<'b>,
     bidder_meta: &'a AccountInfo<'b>,
@@ -71,7 +99,10 @@ struct Accounts<'a, 'b: 'a> {
     system: &'a AccountInfo<'b>,
     token_program: &'a AccountInfo<'b>,
     transfer_authority: &'a AccountInfo<'b>,
-    buy_now: Option<&'a AccountInfo<'b>>,
+    /// The sealed bid account on sealed bid auctions or the buy now account of auctions still in
+    /// a buy now state, then the bidder's gate pass on gated auctions, then the referrer's registry
+    /// if the bid was referred, then the bid history if the auction keeps one.
+    remaining: &'a [AccountInfo<'b>],
 }
 
 fn parse_accounts<'a, 'b: 'a>(
@@ -93,26 +124,25 @@ fn parse_accounts<'a, 'b: 'a>(
         rent: next_account_info(account_iter)?,
         system: next_account_info(account_iter)?,
         token_program: next_account_info(account_iter)?,
-        buy_now: next_account_info(account_iter).ok(),
+        remaining: account_iter.as_slice(),
     };
 
     assert_owned_by(accounts.auction, program_id)?;
     assert_owned_by(accounts.mint, &spl_token::id())?;
-    assert_owned_by(accounts.bidder_pot_token, &spl_token::id()).or(assert_owned_by(
-        accounts.bidder_pot_token,
-        &system_program::id(),
-    ))?;
+    // A native bid pot stands in for its own token account, so it may be owned by us.
+    assert_owned_by(accounts.bidder_pot_token, &spl_token::id())
+        .or(assert_owned_by(
+            accounts.bidder_pot_token,
+            &system_program::id(),
+        ))
+        .or(assert_owned_by(accounts.bidder_pot_token, program_id))?;
     assert_signer(accounts.bidder)?;
     assert_signer(accounts.payer)?;
     assert_signer(accounts.transfer_authority)?;
-    if accounts.buy_now.is_some() {
-        assert_owned_by(accounts.buy_now.unwrap(), program_id)?;
-    }
 
     Ok(accounts)
 }
 
-#[allow(clippy::absurd_extreme_comparisons)]
 pub fn place_bid<'r, 'b: 'r>(
     program_id: &Pubkey,
     accounts: &'r [AccountInfo<'b>],
@@ -120,31 +150,224 @@ pub fn place_bid<'r, 'b: 'r>(
 ) -> ProgramResult {
     msg!("+ Processing PlaceBid");
     let accounts = parse_accounts(program_id, accounts)?;
+    place_bid_internal(
+        program_id,
+        accounts,
+        args.amount,
+        1,
+        args.resource,
+        None,
+        false,
+    )
+}
 
+/// Raises an active bid to `amount` without cancelling it first, the pot is topped up by the
+/// difference and the bidder never leaves the winners in between.
+pub fn increase_bid<'r, 'b: 'r>(
+    program_id: &Pubkey,
+    accounts: &'r [AccountInfo<'b>],
+    args: PlaceBidArgs,
+) -> ProgramResult {
+    msg!("+ Processing IncreaseBid");
+    let accounts = parse_accounts(program_id, accounts)?;
+    place_bid_internal(
+        program_id,
+        accounts,
+        args.amount,
+        1,
+        args.resource,
+        None,
+        true,
+    )
+}
+
+/// Bids a price per unit for several units of a multi-unit auction. Units the bid loses to higher
+/// bids are refunded when the bid is claimed.
+pub fn place_multi_unit_bid<'r, 'b: 'r>(
+    program_id: &Pubkey,
+    accounts: &'r [AccountInfo<'b>],
+    args: PlaceMultiUnitBidArgs,
+) -> ProgramResult {
+    msg!("+ Processing PlaceMultiUnitBid");
+    let accounts = parse_accounts(program_id, accounts)?;
+    place_bid_internal(
+        program_id,
+        accounts,
+        args.amount,
+        args.quantity,
+        args.resource,
+        None,
+        false,
+    )
+}
+
+/// Commits to a hidden bid on a sealed bid auction. The deposit is moved into the bidder pot just
+/// like a regular bid, the bid itself only counts once revealed with RevealBid.
+pub fn place_sealed_bid<'r, 'b: 'r>(
+    program_id: &Pubkey,
+    accounts: &'r [AccountInfo<'b>],
+    args: PlaceSealedBidArgs,
+) -> ProgramResult {
+    msg!("+ Processing PlaceSealedBid");
+    let accounts = parse_accounts(program_id, accounts)?;
+    place_bid_internal(
+        program_id,
+        accounts,
+        args.deposit,
+        1,
+        args.resource,
+        Some(args.commitment),
+        false,
+    )
+}
+
+/// Checks the bidder can pay `charge`: their balance covers it and, for token bids, the transfer
+/// authority may move it out of an account of the auction mint, whose decimals refunds rely on.
+fn assert_can_pay(auction: &AuctionData, accounts: &Accounts, charge: u64) -> ProgramResult {
+    if auction.native_bids {
+        if accounts.bidder_token.key != accounts.bidder.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if accounts.bidder.lamports() < charge {
+            return Err(AuctionError::BalanceTooLow.into());
+        }
+        return Ok(());
+    }
+
+    let mint: Mint = assert_initialized(accounts.mint)?;
+    if mint.decimals != MINT_DECIMALS {
+        msg!(
+            "Mint has {:?} decimals, bids are refunded with {:?}",
+            mint.decimals,
+            MINT_DECIMALS
+        );
+        return Err(AuctionError::InvalidMintDecimals.into());
+    }
+
+    assert_owned_by(accounts.bidder_token, &spl_token::id())?;
+    let account: Account = assert_initialized(accounts.bidder_token)?;
+    if account.mint != auction.token_mint {
+        return Err(AuctionError::IncorrectMint.into());
+    }
+    if account.amount < charge {
+        msg!(
+            "Amount is too large: {:?}, compared to account amount of {:?}",
+            charge,
+            account.amount
+        );
+        return Err(AuctionError::BalanceTooLow.into());
+    }
+
+    // The owner can move its own tokens, anyone else needs an allowance covering the bid.
+    if account.owner != *accounts.transfer_authority.key {
+        let allowance = match account.delegate {
+            COption::Some(delegate) if delegate == *accounts.transfer_authority.key => {
+                account.delegated_amount
+            }
+            _ => 0,
+        };
+        if allowance < charge {
+            msg!(
+                "Amount is too large: {:?}, compared to the allowance of {:?}",
+                charge,
+                allowance
+            );
+            return Err(AuctionError::AllowanceTooLow.into());
+        }
+    }
+
+    Ok(())
+}
+
+fn place_bid_internal<'r, 'b: 'r>(
+    program_id: &Pubkey,
+    accounts: Accounts<'r, 'b>,
+    amount: u64,
+    quantity: u64,
+    resource: Pubkey,
+    commitment: Option<Hash>,
+    raise: bool,
+) -> ProgramResult {
     // Load the auction and verify this bid is valid.
-    let mut auction: AuctionData = try_from_slice_unchecked(&accounts.auction.data.borrow())?;
+    let mut auction = AuctionData::from_account_info(accounts.auction)?;
 
     if &auction.token_mint != accounts.mint.key {
         msg!("Invalid token mint!");
         return Err(ProgramError::InvalidArgument);
     }
 
-    if auction.state == AuctionState::BuyNowStarted && accounts.buy_now.is_none() {
-        msg!("The buy_now account is missing");
-        return Err(ProgramError::InvalidArgument);
+    let is_sealed = matches!(auction.bid_state, BidState::SealedBid { .. });
+    if is_sealed != commitment.is_some() {
+        return Err(AuctionError::SealedBidMismatch.into());
+    }
+
+    let is_multi_unit = matches!(auction.bid_state, BidState::MultiUnit { .. });
+    if quantity != 1 && !is_multi_unit {
+        return Err(AuctionError::InvalidBidQuantity.into());
+    }
+
+    // A raised multi-unit bid would lose its quantity, and Dutch auctions end on their first bid.
+    if raise
+        && !matches!(
+            auction.bid_state,
+            BidState::EnglishAuction { .. } | BidState::OpenEdition { .. }
+        )
+    {
+        return Err(AuctionError::CannotIncreaseBid.into());
     }
 
+    let (bid_history, remaining) = BidHistory::split_account(
+        program_id,
+        &auction,
+        accounts.auction.key,
+        accounts.remaining,
+    )?;
+    let (referral, remaining) = ReferralRegistry::split_account(program_id, remaining)?;
+    let remaining = BidderGate::split_pass(
+        program_id,
+        &auction,
+        accounts.auction.key,
+        accounts.bidder.key,
+        remaining,
+    )?;
+    let sealed_bid = match (is_sealed, remaining.first()) {
+        (true, Some(sealed_bid)) => Some(sealed_bid),
+        (true, None) => return Err(ProgramError::NotEnoughAccountKeys),
+        (false, _) => None,
+    };
+
     // Load the clock, used for various auction timing.
     let clock = Clock::from_account_info(accounts.clock_sysvar)?;
 
+    // A scheduled auction needs no StartAuction call, the first bid past its start time starts it.
+    auction.start_if_scheduled(clock.unix_timestamp)?;
+
+    // Sealed bids are only taken until the end time, the auction itself runs on until every bid
+    // had the chance to be revealed.
+    if is_sealed && auction.bidding_closed(clock.unix_timestamp)? {
+        return Err(AuctionError::BiddingClosed.into());
+    }
+
     // Verify auction has not ended.
     if auction.ended(clock.unix_timestamp)? {
         auction.state = auction.state.end()?;
-        auction.serialize(&mut *accounts.auction.data.borrow_mut())?;
+        auction.save(accounts.auction)?;
         msg!("Auction ended!");
         return Ok(());
     }
 
+    // Auctions still in a buy now state keep their price in their own account, it is only taken
+    // for this bid as they have no room to keep it.
+    let legacy_buy_now = auction.state == AuctionState::BuyNowStarted;
+    if legacy_buy_now {
+        let buy_now = remaining.first().ok_or_else(|| {
+            msg!("The buy_now account is missing");
+            ProgramError::InvalidArgument
+        })?;
+        let buy_now = BuyNowData::from_account_info(program_id, &resource, buy_now)?;
+        auction.buy_now_price = Some(buy_now.max_price);
+    }
+
     // Derive Metadata key and load it.
     let metadata_bump = assert_derivation(
         program_id,
@@ -158,8 +381,20 @@ pub fn place_bid<'r, 'b: 'r>(
         ],
     )?;
 
-    // If metadata doesn't exist, create it.
-    if accounts.bidder_meta.owner != program_id {
+    // If metadata doesn't exist, create it. A raise needs the active bid it raises instead, and
+    // is only charged what it adds.
+    let mut previous_bid = 0;
+    if raise {
+        if accounts.bidder_meta.owner != program_id || accounts.bidder_pot.data_is_empty() {
+            return Err(AuctionError::NoActiveBid.into());
+        }
+        let bidder_metadata: BidderMetadata =
+            try_from_slice_unchecked(&accounts.bidder_meta.data.borrow())?;
+        if bidder_metadata.cancelled {
+            return Err(AuctionError::NoActiveBid.into());
+        }
+        previous_bid = bidder_metadata.last_bid;
+    } else if accounts.bidder_meta.owner != program_id {
         create_or_allocate_account_raw(
             *program_id,
             accounts.bidder_meta,
@@ -204,11 +439,7 @@ pub fn place_bid<'r, 'b: 'r>(
     let auction_bump = assert_derivation(
         program_id,
         accounts.auction,
-        &[
-            PREFIX.as_bytes(),
-            program_id.as_ref(),
-            args.resource.as_ref(),
-        ],
+        &[PREFIX.as_bytes(), program_id.as_ref(), resource.as_ref()],
     )?;
 
     // Can't bid on an auction that isn't running.
@@ -216,18 +447,50 @@ pub fn place_bid<'r, 'b: 'r>(
         return Err(AuctionError::InvalidState.into());
     }
 
-    // Can't bid smaller than the minimum price.
+    // Can't bid smaller than the minimum price. A sealed bid deposit doesn't have to meet it, as
+    // revealed bids below the floor simply never win.
     if let PriceFloor::MinimumPrice(min) = auction.price_floor {
         msg!(
             "Amount is too small: {:?}, compared to price floor of {:?}",
-            args.amount,
+            amount,
             min[0]
         );
-        if args.amount <= min[0] {
+        if amount <= min[0] && !is_sealed {
             return Err(AuctionError::BidTooSmall.into());
         }
     }
 
+    // A Dutch auction bid only has to meet the current asking price, and is charged exactly that.
+    let amount = match auction.bid_state.dutch_price(clock.unix_timestamp) {
+        Some(price) if amount < price => {
+            msg!(
+                "Amount is too small: {:?}, compared to asking price of {:?}",
+                amount,
+                price
+            );
+            return Err(AuctionError::BidTooSmall.into());
+        }
+        Some(price) => price,
+        None => amount,
+    };
+
+    // A bid at or above the buy now price buys at it, and only has to beat what it takes.
+    let buy_now_price = auction.buy_now_price.filter(|price| amount >= *price);
+    let amount = buy_now_price.unwrap_or(amount);
+
+    // A multi-unit bid pays for every unit up front.
+    let total = match amount.checked_mul(quantity) {
+        Some(val) => val,
+        None => return Err(AuctionError::NumericalOverflowError.into()),
+    };
+    let charge = match total.checked_sub(previous_bid) {
+        Some(charge) if charge > 0 || !raise => charge,
+        _ => return Err(AuctionError::BidTooSmall.into()),
+    };
+
+    // Check the bid can be paid before creating anything, rather than failing in the transfer.
+    assert_can_pay(&auction, &accounts, charge)?;
+
     let bump_authority_seeds = &[
         PREFIX.as_bytes(),
         program_id.as_ref(),
@@ -244,39 +507,53 @@ pub fn place_bid<'r, 'b: 'r>(
             accounts.rent,
             accounts.system,
             accounts.payer,
-            mem::size_of::<BidderPot>(),
+            BIDDER_POT_LEN,
             bump_authority_seeds,
         )?;
 
         // Attach SPL token address to pot account.
-        let mut pot: BidderPot = try_from_slice_unchecked(&accounts.bidder_pot.data.borrow_mut())?;
+        let mut pot = BidderPot::from_account_info(accounts.bidder_pot)?;
         pot.bidder_pot = *accounts.bidder_pot_token.key;
         pot.bidder_act = *accounts.bidder.key;
         pot.auction_act = *accounts.auction.key;
-        pot.serialize(&mut *accounts.bidder_pot.data.borrow_mut())?;
-
-        let (bidder_pot_key, bidder_pot_nonce) =
-            Pubkey::find_program_address(&[&accounts.bidder_pot.key.to_bytes()], program_id);
-        if &bidder_pot_key != accounts.bidder_pot_token.key {
+        pot.save(accounts.bidder_pot)?;
+
+        // A native pot holds the bid itself, there is no token account to create.
+        if auction.native_bids {
+            if !pot.is_native(accounts.bidder_pot.key) {
+                return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
+            }
+        } else {
+            let (bidder_pot_key, bidder_pot_nonce) =
+                Pubkey::find_program_address(&[&accounts.bidder_pot.key.to_bytes()], program_id);
+            if &bidder_pot_key != accounts.bidder_pot_token.key {
+                return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
+            }
+
+            let bidder_pot_seeds = &[
+                &accounts.bidder_pot.key.to_bytes() as &[u8],
+                &[bidder_pot_nonce],
+            ];
+
+            assert_owned_by(accounts.bidder_pot_token, &system_program::ID)?;
+            allocate_and_create_token_account(
+                accounts.auction.key,
+                accounts.token_program,
+                accounts.payer,
+                bidder_pot_seeds,
+                accounts.bidder_pot_token,
+                accounts.mint,
+                accounts.rent,
+                accounts.system,
+            )?;
+        }
+    } else if auction.native_bids {
+        let bidder_pot = BidderPot::from_account_info(accounts.bidder_pot)?;
+        if !bidder_pot.is_native(accounts.bidder_pot.key)
+            || accounts.bidder_pot_token.key != accounts.bidder_pot.key
+        {
             return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
         }
-
-        let bidder_pot_seeds = &[
-            &accounts.bidder_pot.key.to_bytes() as &[u8],
-            &[bidder_pot_nonce],
-        ];
-
-        assert_owned_by(accounts.bidder_pot_token, &system_program::ID)?;
-        allocate_and_create_token_account(
-            accounts.auction.key,
-            accounts.token_program,
-            accounts.payer,
-            bidder_pot_seeds,
-            accounts.bidder_pot_token,
-            accounts.mint,
-            accounts.rent,
-            accounts.system,
-        )?;
     } else {
         // The account within the pot must be owned by us.
         let actual_account: Account = assert_initialized(accounts.bidder_pot_token)?;
@@ -289,76 +566,184 @@ pub fn place_bid<'r, 'b: 'r>(
         // Already exists, verify that the pot contains the specified SPL address.
 
         assert_owned_by(accounts.bidder_pot_token, &spl_token::ID)?;
-        let bidder_pot: BidderPot =
-            try_from_slice_unchecked(&accounts.bidder_pot.data.borrow_mut())?;
+        let bidder_pot = BidderPot::from_account_info(accounts.bidder_pot)?;
         if bidder_pot.bidder_pot != *accounts.bidder_pot_token.key {
             return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
         }
     }
 
-    // Confirm payers SPL token balance is enough to pay the bid.
-    let account: Account = Account::unpack_from_slice(&accounts.bidder_token.data.borrow())?;
-    if account.amount.saturating_sub(args.amount) < 0 {
-        msg!(
-            "Amount is too small: {:?}, compared to account amount of {:?}",
-            args.amount,
-            account.amount
-        );
-        return Err(AuctionError::BalanceTooLow.into());
+    if auction.native_bids {
+        // Native bids are paid straight from the bidder's wallet.
+        invoke(
+            &system_instruction::transfer(accounts.bidder.key, accounts.bidder_pot.key, charge),
+            &[
+                accounts.bidder.clone(),
+                accounts.bidder_pot.clone(),
+                accounts.system.clone(),
+            ],
+        )?;
+    } else {
+        // Transfer amount of SPL token to bid account.
+        spl_token_transfer(TokenTransferParams {
+            source: accounts.bidder_token.clone(),
+            destination: accounts.bidder_pot_token.clone(),
+            authority: accounts.transfer_authority.clone(),
+            authority_signer_seeds: bump_authority_seeds,
+            token_program: accounts.token_program.clone(),
+            amount: charge,
+        })?;
     }
 
-    // Transfer amount of SPL token to bid account.
-    spl_token_transfer(TokenTransferParams {
-        source: accounts.bidder_token.clone(),
-        destination: accounts.bidder_pot_token.clone(),
-        authority: accounts.transfer_authority.clone(),
-        authority_signer_seeds: bump_authority_seeds,
-        token_program: accounts.token_program.clone(),
-        amount: args.amount,
-    })?;
-
     // Serialize new Auction State
     auction.last_bid = Some(clock.unix_timestamp);
-    auction
-        .bid_state
-        .place_bid(Bid(*accounts.bidder_pot.key, args.amount))?;
-    auction.serialize(&mut (&mut accounts.auction.data.borrow_mut() as &mut [u8]))?;
+    match (commitment, sealed_bid) {
+        (Some(commitment), Some(sealed_bid)) => {
+            record_sealed_bid(program_id, &accounts, sealed_bid, commitment, amount)?
+        }
+        _ => {
+            let bid = Bid(*accounts.bidder_pot.key, amount);
+            let outbid = if buy_now_price.is_some() {
+                auction
+                    .bid_state
+                    .place_buy_now_bid(bid, quantity as usize)?
+            } else if raise {
+                auction.bid_state.raise_bid(bid, &auction.bid_increment)?
+            } else if is_multi_unit {
+                auction
+                    .bid_state
+                    .place_units_bid(bid, quantity as usize, &auction.bid_increment)?
+            } else {
+                auction.bid_state.place_bid(bid, &auction.bid_increment)?
+            };
+            auction.record_losing_bid(outbid);
+        }
+    }
+
+    // Bids late in the auction keep it open for another gap.
+    auction.extend_for_bid(clock.unix_timestamp)?;
+
+    // The first bid to meet a Dutch auction's asking price wins it outright.
+    if let BidState::DutchAuction { .. } = auction.bid_state {
+        auction.ended_at = Some(clock.unix_timestamp);
+        auction.end_auction_gap = None;
+        auction.state = auction.state.end()?;
+    }
+
+    // Once buy now bids take everything on sale the auction ends, and the bids below them lose. A
+    // legacy buy now account sells a single purchase, whatever the auction's winners or editions.
+    let bought_out = if legacy_buy_now {
+        buy_now_price.is_some()
+    } else {
+        auction.bought_out()
+    };
+    if let (true, Some(price)) = (bought_out, auction.buy_now_price) {
+        auction.ended_at = Some(clock.unix_timestamp);
+        auction.end_auction_gap = None;
+        auction.state = auction.state.end()?;
+        auction.bid_state.drop_bids_below(price);
+    }
+    if legacy_buy_now {
+        auction.buy_now_price = None;
+    }
+    auction.save(accounts.auction)?;
 
     // Update latest metadata with results from the bid.
     BidderMetadata {
         bidder_pubkey: *accounts.bidder.key,
         auction_pubkey: *accounts.auction.key,
-        last_bid: args.amount,
+        last_bid: total,
         last_bid_timestamp: clock.unix_timestamp,
         cancelled: false,
     }
-    .serialize(&mut *accounts.bidder_meta.data.borrow_mut())?;
+    .serialize(&mut &mut accounts.bidder_meta.data.borrow_mut()[..])?;
 
-    if accounts.buy_now.is_some() {
-        let buy_now_account = accounts.buy_now.unwrap();
+    // Refunds made on the bidder's behalf go back to the account this bid was paid from.
+    let mut bidder_pot = BidderPot::from_account_info(accounts.bidder_pot)?;
+    if !auction.native_bids && BidderPot::records_refund_account(accounts.bidder_pot) {
+        bidder_pot.refund_account = Some(*accounts.bidder_token.key);
+    }
 
-        let buy_now_path = [
-            BUY_NOW.as_bytes(),
-            program_id.as_ref(),
-            &args.resource.to_bytes(),
-        ];
-        let (buy_now_key, buy_now_bump) = Pubkey::find_program_address(&buy_now_path, program_id);
-        if buy_now_account.key != &buy_now_key {
-            msg!("Invalid buy now account provided");
-            return Err(ProgramError::InvalidArgument);
+    // The referrer is kept with the pot so that claiming the bid pays them whoever claims it. A
+    // new bid placed without a registry drops the referrer of the bid it replaces, a raise keeps it.
+    if let Some(registry) = referral {
+        let mut referral = ReferralRegistry::from_account_info(program_id, registry)
+            .ok_or(AuctionError::InvalidReferralRegistry)?;
+        if referral.referrer == *accounts.bidder.key {
+            msg!("Bidders cannot refer themselves");
+            return Err(AuctionError::InvalidReferralRegistry.into());
         }
+        bidder_pot.referrer = Some(referral.referrer);
 
-        // Deserialize data
-        let buy_now_state: BuyNowData = try_from_slice_unchecked(&buy_now_account.data.borrow())?;
-        // End the auction
-        if args.amount >= buy_now_state.max_price {
-            auction.ended_at = Some(clock.unix_timestamp);
-            // Disable the end auction gap in the buy now case.
-            auction.end_auction_gap = None;
-            auction.state = auction.state.end()?;
-            auction.serialize(&mut (&mut accounts.auction.data.borrow_mut() as &mut [u8]))?;
-        }
+        referral.bids_referred = referral.bids_referred.saturating_add(1);
+        referral.serialize(&mut &mut registry.data.borrow_mut()[..])?;
+    } else if !raise {
+        bidder_pot.referrer = None;
+    }
+    bidder_pot.save(accounts.bidder_pot)?;
+
+    if let Some(bid_history) = bid_history {
+        BidHistory::append(
+            bid_history,
+            &BidEvent {
+                bidder: *accounts.bidder.key,
+                amount: total,
+                timestamp: clock.unix_timestamp,
+                kind: if raise {
+                    BidEventKind::Raised
+                } else {
+                    BidEventKind::Placed
+                },
+            },
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Creates the sealed bid PDA of a bidder if needed, and stores their new commitment in it.
+fn record_sealed_bid<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &Accounts<'a, 'b>,
+    sealed_bid: &'a AccountInfo<'b>,
+    commitment: Hash,
+    deposit: u64,
+) -> ProgramResult {
+    let sealed_bid_bump = assert_derivation(
+        program_id,
+        sealed_bid,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            accounts.bidder.key.as_ref(),
+            "sealed".as_bytes(),
+        ],
+    )?;
+
+    if sealed_bid.owner != program_id {
+        create_or_allocate_account_raw(
+            *program_id,
+            sealed_bid,
+            accounts.rent,
+            accounts.system,
+            accounts.payer,
+            SEALED_BID_LEN,
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                accounts.auction.key.as_ref(),
+                accounts.bidder.key.as_ref(),
+                "sealed".as_bytes(),
+                &[sealed_bid_bump],
+            ],
+        )?;
     }
 
+    SealedBid {
+        commitment,
+        deposit,
+        revealed: None,
+    }
+    .serialize(&mut &mut sealed_bid.data.borrow_mut()[..])?;
     Ok(())
 }
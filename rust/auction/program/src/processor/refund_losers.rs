@@ -0,0 +1,227 @@
+//! Refunds losing bids once an auction has ended, anyone can call this. Each bid goes back to the
+//! token account it was paid from, or to the bidder's wallet for native bids, so bidders who never
+//! come back to cancel don't leave their funds sitting in the pots.
+//!
+//! Winners and bids that were already cancelled or refunded are skipped, so a crank can pass every
+//! bidder of the auction. Sealed bids are left to CancelSealedBid and ClaimForfeitedBid, as only
+//! revealed ones may be refunded.
+
+use crate::{
+    errors::AuctionError,
+    processor::{
+        AuctionData, BidEvent, BidEventKind, BidHistory, BidState, BidderMetadata, BidderPot,
+    },
+    utils::{
+        assert_derivation, assert_initialized, assert_owned_by, lamports_above_rent,
+        spl_token_transfer_checked, transfer_lamports, TokenTransferCheckedParams,
+    },
+    MINT_DECIMALS, PREFIX,
+};
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        borsh::try_from_slice_unchecked,
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::{clock::Clock, Sysvar},
+    },
+    spl_token::state::Account,
+};
+
+/// Arguments for the RefundLosers instruction discriminant.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct RefundLosersArgs {
+    pub resource: Pubkey,
+}
+
+/// Accounts of each bid being refunded, in order.
+const REFUND_ACCOUNTS: usize = 4;
+
+struct Accounts<'a, 'b: 'a> {
+    auction: &'a AccountInfo<'b>,
+    mint: &'a AccountInfo<'b>,
+    clock_sysvar: &'a AccountInfo<'b>,
+    token_program: &'a AccountInfo<'b>,
+    /// The bidder pot, pot token account, metadata and refund account of each bid, then the bid
+    /// history if the auction keeps one.
+    remaining: &'a [AccountInfo<'b>],
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        auction: next_account_info(account_iter)?,
+        mint: next_account_info(account_iter)?,
+        clock_sysvar: next_account_info(account_iter)?,
+        token_program: next_account_info(account_iter)?,
+        remaining: account_iter.as_slice(),
+    };
+
+    assert_owned_by(accounts.auction, program_id)?;
+
+    Ok(accounts)
+}
+
+pub fn refund_losers(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: RefundLosersArgs,
+) -> ProgramResult {
+    msg!("+ Processing RefundLosers");
+    let accounts = parse_accounts(program_id, accounts)?;
+    let clock = Clock::from_account_info(accounts.clock_sysvar)?;
+
+    // Derive and load Auction.
+    let auction_bump = assert_derivation(
+        program_id,
+        accounts.auction,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+        ],
+    )?;
+
+    let auction_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+        &[auction_bump],
+    ];
+
+    let auction = AuctionData::from_account_info(accounts.auction)?;
+
+    // Until the end bids can still win, and a losing bid can still be raised.
+    if !auction.ended(clock.unix_timestamp)? {
+        return Err(AuctionError::AuctionNotEnded.into());
+    }
+
+    if let BidState::SealedBid { .. } = auction.bid_state {
+        msg!("Sealed bids are refunded with CancelSealedBid");
+        return Err(AuctionError::SealedBidMismatch.into());
+    }
+
+    if auction.token_mint != *accounts.mint.key {
+        return Err(AuctionError::IncorrectMint.into());
+    }
+
+    let (bid_history, remaining) = BidHistory::split_account(
+        program_id,
+        &auction,
+        accounts.auction.key,
+        accounts.remaining,
+    )?;
+    if remaining.len() % REFUND_ACCOUNTS != 0 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    for refund in remaining.chunks(REFUND_ACCOUNTS) {
+        let (bidder_pot, bidder_pot_token, bidder_meta, destination) =
+            (&refund[0], &refund[1], &refund[2], &refund[3]);
+
+        assert_owned_by(bidder_pot, program_id)?;
+        let pot = BidderPot::from_account_info(bidder_pot)?;
+        assert_derivation(
+            program_id,
+            bidder_pot,
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                accounts.auction.key.as_ref(),
+                pot.bidder_act.as_ref(),
+            ],
+        )?;
+        assert_derivation(
+            program_id,
+            bidder_meta,
+            &[
+                PREFIX.as_bytes(),
+                program_id.as_ref(),
+                accounts.auction.key.as_ref(),
+                pot.bidder_act.as_ref(),
+                "metadata".as_bytes(),
+            ],
+        )?;
+        assert_owned_by(bidder_meta, program_id)?;
+        if pot.bidder_pot != *bidder_pot_token.key {
+            return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
+        }
+
+        let metadata: BidderMetadata = try_from_slice_unchecked(&bidder_meta.data.borrow())?;
+        if metadata.cancelled || pot.emptied || auction.is_winner(bidder_pot.key).is_some() {
+            msg!("Skipping the bid of {}", pot.bidder_act);
+            continue;
+        }
+
+        if pot.is_native(bidder_pot.key) {
+            // Native bids go back to the bidder's wallet.
+            if *destination.key != pot.bidder_act {
+                return Err(ProgramError::InvalidArgument);
+            }
+            let amount = lamports_above_rent(bidder_pot)?;
+            transfer_lamports(bidder_pot, destination, amount)?;
+        } else {
+            // Bids go back to the account they were paid from, pots that predate recording it
+            // take any account of the bidder.
+            match pot.refund_account {
+                Some(refund_account) if refund_account != *destination.key => {
+                    return Err(ProgramError::InvalidArgument);
+                }
+                Some(_) => (),
+                None => {
+                    assert_owned_by(destination, &spl_token::id())?;
+                    let destination_account: Account = assert_initialized(destination)?;
+                    if destination_account.owner != pot.bidder_act {
+                        return Err(ProgramError::InvalidArgument);
+                    }
+                }
+            }
+
+            assert_owned_by(bidder_pot_token, &spl_token::id())?;
+            let pot_account: Account = assert_initialized(bidder_pot_token)?;
+            if pot_account.owner != *accounts.auction.key {
+                return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
+            }
+            if pot_account.amount > 0 {
+                spl_token_transfer_checked(TokenTransferCheckedParams {
+                    source: bidder_pot_token.clone(),
+                    destination: destination.clone(),
+                    authority: accounts.auction.clone(),
+                    authority_signer_seeds: auction_seeds,
+                    token_program: accounts.token_program.clone(),
+                    amount: pot_account.amount,
+                    mint: accounts.mint.clone(),
+                    decimals: MINT_DECIMALS,
+                })?;
+            }
+        }
+
+        BidderMetadata {
+            cancelled: true,
+            ..metadata
+        }
+        .serialize(&mut &mut bidder_meta.data.borrow_mut()[..])?;
+
+        if let Some(bid_history) = bid_history {
+            BidHistory::append(
+                bid_history,
+                &BidEvent {
+                    bidder: pot.bidder_act,
+                    amount: metadata.last_bid,
+                    timestamp: clock.unix_timestamp,
+                    kind: BidEventKind::Refunded,
+                },
+            )?;
+        }
+    }
+
+    Ok(())
+}
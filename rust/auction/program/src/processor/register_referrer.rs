@@ -0,0 +1,91 @@
+//! Registers a referrer with the program. Bidders name the registry when they bid, and the
+//! referrer is paid their share of the fees when the bid is claimed.
+
+use crate::{
+    processor::{ReferralRegistry, REFERRAL_REGISTRY_KEY, REFERRAL_REGISTRY_LEN},
+    utils::{assert_derivation, assert_signer, create_or_allocate_account_raw},
+    PREFIX,
+};
+
+use {
+    borsh::BorshSerialize,
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+struct Accounts<'a, 'b: 'a> {
+    referrer: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+    registry: &'a AccountInfo<'b>,
+    rent: &'a AccountInfo<'b>,
+    system: &'a AccountInfo<'b>,
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        referrer: next_account_info(account_iter)?,
+        payer: next_account_info(account_iter)?,
+        registry: next_account_info(account_iter)?,
+        rent: next_account_info(account_iter)?,
+        system: next_account_info(account_iter)?,
+    };
+
+    assert_signer(accounts.referrer)?;
+    assert_signer(accounts.payer)?;
+
+    Ok(accounts)
+}
+
+pub fn register_referrer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("+ Processing RegisterReferrer");
+    let accounts = parse_accounts(program_id, accounts)?;
+
+    let bump = assert_derivation(
+        program_id,
+        accounts.registry,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.referrer.key.as_ref(),
+            "referral".as_bytes(),
+        ],
+    )?;
+
+    if !accounts.registry.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    create_or_allocate_account_raw(
+        *program_id,
+        accounts.registry,
+        accounts.rent,
+        accounts.system,
+        accounts.payer,
+        REFERRAL_REGISTRY_LEN,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.referrer.key.as_ref(),
+            "referral".as_bytes(),
+            &[bump],
+        ],
+    )?;
+
+    ReferralRegistry {
+        key: REFERRAL_REGISTRY_KEY,
+        referrer: *accounts.referrer.key,
+        bids_referred: 0,
+    }
+    .serialize(&mut &mut accounts.registry.data.borrow_mut()[..])?;
+
+    Ok(())
+}
@@ -0,0 +1,199 @@
+//! Reveals a sealed bid once bidding has closed. The revealed amount must hash to the commitment
+//! made with PlaceSealedBid, anything deposited above it is returned to the bidder right away and
+//! the bid enters the winner stack as if it was placed in an English auction.
+
+use crate::{
+    errors::AuctionError,
+    processor::{AuctionData, Bid, BidState, BidderPot, SealedBid},
+    utils::{
+        assert_derivation, assert_initialized, assert_owned_by, assert_signer,
+        spl_token_transfer_checked, TokenTransferCheckedParams,
+    },
+    MINT_DECIMALS, PREFIX,
+};
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        borsh::try_from_slice_unchecked,
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::{clock::Clock, Sysvar},
+    },
+    spl_token::state::Account,
+};
+
+/// Arguments for the RevealBid instruction discriminant.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct RevealBidArgs {
+    /// Resource being bid on.
+    pub resource: Pubkey,
+    /// The amount that was committed to.
+    pub amount: u64,
+    /// Salt used in the commitment.
+    pub salt: u64,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    auction: &'a AccountInfo<'b>,
+    bidder: &'a AccountInfo<'b>,
+    bidder_token: &'a AccountInfo<'b>,
+    bidder_pot: &'a AccountInfo<'b>,
+    bidder_pot_token: &'a AccountInfo<'b>,
+    sealed_bid: &'a AccountInfo<'b>,
+    mint: &'a AccountInfo<'b>,
+    clock_sysvar: &'a AccountInfo<'b>,
+    token_program: &'a AccountInfo<'b>,
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        bidder: next_account_info(account_iter)?,
+        bidder_token: next_account_info(account_iter)?,
+        bidder_pot: next_account_info(account_iter)?,
+        bidder_pot_token: next_account_info(account_iter)?,
+        sealed_bid: next_account_info(account_iter)?,
+        auction: next_account_info(account_iter)?,
+        mint: next_account_info(account_iter)?,
+        clock_sysvar: next_account_info(account_iter)?,
+        token_program: next_account_info(account_iter)?,
+    };
+
+    assert_owned_by(accounts.auction, program_id)?;
+    assert_owned_by(accounts.bidder_pot, program_id)?;
+    assert_owned_by(accounts.sealed_bid, program_id)?;
+    assert_owned_by(accounts.mint, &spl_token::id())?;
+    assert_owned_by(accounts.bidder_pot_token, &spl_token::id())?;
+    assert_signer(accounts.bidder)?;
+
+    Ok(accounts)
+}
+
+pub fn reveal_bid(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: RevealBidArgs,
+) -> ProgramResult {
+    msg!("+ Processing RevealBid");
+    let accounts = parse_accounts(program_id, accounts)?;
+
+    // Derive and load Auction.
+    let auction_bump = assert_derivation(
+        program_id,
+        accounts.auction,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+        ],
+    )?;
+
+    let auction_seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        args.resource.as_ref(),
+        &[auction_bump],
+    ];
+
+    let mut auction = AuctionData::from_account_info(accounts.auction)?;
+    if !matches!(auction.bid_state, BidState::SealedBid { .. }) {
+        return Err(AuctionError::SealedBidMismatch.into());
+    }
+
+    if &auction.token_mint != accounts.mint.key {
+        msg!("Invalid token mint!");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Bids are revealed between the close of bidding and the end of the auction.
+    let clock = Clock::from_account_info(accounts.clock_sysvar)?;
+    if !auction.bidding_closed(clock.unix_timestamp)? || auction.ended(clock.unix_timestamp)? {
+        return Err(AuctionError::NotInRevealPhase.into());
+    }
+
+    assert_derivation(
+        program_id,
+        accounts.bidder_pot,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            accounts.bidder.key.as_ref(),
+        ],
+    )?;
+
+    assert_derivation(
+        program_id,
+        accounts.sealed_bid,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            accounts.auction.key.as_ref(),
+            accounts.bidder.key.as_ref(),
+            "sealed".as_bytes(),
+        ],
+    )?;
+
+    // Confirm we're looking at the real SPL account for this bidder.
+    let bidder_pot = BidderPot::from_account_info(accounts.bidder_pot)?;
+    if bidder_pot.bidder_pot != *accounts.bidder_pot_token.key {
+        return Err(AuctionError::BidderPotTokenAccountOwnerMismatch.into());
+    }
+    if bidder_pot.emptied {
+        return Err(AuctionError::BidderPotEmpty.into());
+    }
+
+    let mut sealed_bid: SealedBid = try_from_slice_unchecked(&accounts.sealed_bid.data.borrow())?;
+    if sealed_bid.revealed.is_some() {
+        return Err(AuctionError::BidAlreadyRevealed.into());
+    }
+
+    let hashed = SealedBid::commit(
+        accounts.auction.key,
+        accounts.bidder.key,
+        args.amount,
+        args.salt,
+    );
+    if hashed != sealed_bid.commitment {
+        return Err(AuctionError::InvalidReveal.into());
+    }
+
+    let pot_account: Account = assert_initialized(accounts.bidder_pot_token)?;
+    if args.amount > sealed_bid.deposit || args.amount > pot_account.amount {
+        return Err(AuctionError::RevealExceedsDeposit.into());
+    }
+
+    // Return whatever was deposited above the bid.
+    let excess = pot_account.amount - args.amount;
+    if excess > 0 {
+        spl_token_transfer_checked(TokenTransferCheckedParams {
+            source: accounts.bidder_pot_token.clone(),
+            destination: accounts.bidder_token.clone(),
+            authority: accounts.auction.clone(),
+            authority_signer_seeds: auction_seeds,
+            token_program: accounts.token_program.clone(),
+            amount: excess,
+            mint: accounts.mint.clone(),
+            decimals: MINT_DECIMALS,
+        })?;
+    }
+
+    sealed_bid.revealed = Some(args.amount);
+    sealed_bid.serialize(&mut &mut accounts.sealed_bid.data.borrow_mut()[..])?;
+
+    let losing_bid = auction
+        .bid_state
+        .reveal_bid(Bid(*accounts.bidder_pot.key, args.amount))?;
+    auction.record_losing_bid(losing_bid);
+    auction.save(accounts.auction)?;
+
+    Ok(())
+}
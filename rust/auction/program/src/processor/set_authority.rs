@@ -25,7 +25,7 @@ pub fn set_authority(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramRe
     let current_authority = next_account_info(account_iter)?;
     let new_authority = next_account_info(account_iter)?;
 
-    let mut auction: AuctionData = try_from_slice_unchecked(&auction_act.data.borrow_mut())?;
+    let mut auction = AuctionData::from_account_info(auction_act)?;
     assert_owned_by(auction_act, program_id)?;
 
     if auction.authority != *current_authority.key {
@@ -37,6 +37,6 @@ pub fn set_authority(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramRe
     }
 
     auction.authority = *new_authority.key;
-    auction.serialize(&mut *auction_act.data.borrow_mut())?;
+    auction.save(auction_act)?;
     Ok(())
 }
@@ -0,0 +1,83 @@
+//! Moves an auction whose end time has passed into its ended state. Anyone can send this, so
+//! winners can redeem without waiting on another bid or the authority. Auctions with a blinded
+//! price floor are left to the authority, who reveals the floor through EndAuction.
+
+use crate::{
+    errors::AuctionError,
+    processor::{AuctionData, PriceFloor},
+    utils::{assert_derivation, assert_owned_by},
+    PREFIX,
+};
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        clock::Clock,
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::Sysvar,
+    },
+};
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct SettleAuctionArgs {
+    /// The resource being auctioned. See AuctionData.
+    pub resource: Pubkey,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    auction: &'a AccountInfo<'b>,
+    clock_sysvar: &'a AccountInfo<'b>,
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        auction: next_account_info(account_iter)?,
+        clock_sysvar: next_account_info(account_iter)?,
+    };
+    assert_owned_by(accounts.auction, program_id)?;
+    Ok(accounts)
+}
+
+pub fn settle_auction<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+    args: SettleAuctionArgs,
+) -> ProgramResult {
+    msg!("+ Processing SettleAuction");
+    let accounts = parse_accounts(program_id, accounts)?;
+    let clock = Clock::from_account_info(accounts.clock_sysvar)?;
+
+    assert_derivation(
+        program_id,
+        accounts.auction,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+        ],
+    )?;
+
+    let mut auction = AuctionData::from_account_info(accounts.auction)?;
+
+    if let PriceFloor::BlindedPrice(_) = auction.price_floor {
+        return Err(AuctionError::MustReveal.into());
+    }
+
+    if !auction.ended(clock.unix_timestamp)? {
+        return Err(AuctionError::AuctionNotEnded.into());
+    }
+
+    auction.state = auction.state.end()?;
+    auction.save(accounts.auction)?;
+
+    Ok(())
+}
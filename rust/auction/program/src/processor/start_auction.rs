@@ -69,38 +69,36 @@ pub fn start_auction<'a, 'b: 'a>(
     )?;
 
     // Initialise a new auction. The end time is calculated relative to now.
-    let mut auction: AuctionData = try_from_slice_unchecked(&accounts.auction.data.borrow())?;
+    let mut auction = AuctionData::from_account_info(accounts.auction)?;
 
     // Check authority is correct.
     if auction.authority != *accounts.authority.key {
         return Err(AuctionError::InvalidAuthority.into());
     }
 
-    // Calculate the relative end time.
-    let ended_at = if let Some(end_auction_at) = auction.end_auction_at {
-        match clock.unix_timestamp.checked_add(end_auction_at) {
-            Some(val) => Some(val),
-            None => return Err(AuctionError::NumericalOverflowError.into()),
-        }
-    } else {
-        None
-    };
+    // Calculate the relative end time, a Dutch asking price decays from now.
+    auction.begin(clock.unix_timestamp)?;
 
     // Reset current winner
 
     match &mut auction.bid_state {
         BidState::EnglishAuction { bids, max } => bids.pop(),
         BidState::OpenEdition { bids, max } => bids.pop(),
+        BidState::DutchAuction { bids, .. } => bids.pop(),
+        BidState::SealedBid { bids, .. } => bids.pop(),
+        BidState::MultiUnit { bids, .. } => bids.pop(),
     };
 
     let ended = auction.ended(clock.unix_timestamp)?;
     let has_no_bid = match &auction.bid_state {
         BidState::EnglishAuction { bids, max: _ } => bids.is_empty(),
         BidState::OpenEdition { bids, max: _ } => bids.is_empty(),
+        BidState::DutchAuction { bids, .. } => bids.is_empty(),
+        BidState::SealedBid { bids, .. } => bids.is_empty(),
+        BidState::MultiUnit { bids, .. } => bids.is_empty(),
     };
 
     AuctionData {
-        ended_at,
         state: match (auction.state, ended && has_no_bid) {
             (AuctionState::BuyNowEnded, _) | (AuctionState::BuyNowCreated, _) => {
                 AuctionState::BuyNowStarted
@@ -112,7 +110,7 @@ pub fn start_auction<'a, 'b: 'a>(
         },
         ..auction
     }
-    .serialize(&mut *accounts.auction.data.borrow_mut())?;
+    .save(accounts.auction)?;
 
     Ok(())
 }
@@ -0,0 +1,66 @@
+//! Starts an auction created with a `start_at` time once that time has passed. Anyone can send
+//! this, bids already start such an auction on their own, this only moves its state along for
+//! clients watching it.
+
+use crate::{
+    errors::AuctionError,
+    processor::{AuctionData, StartAuctionArgs},
+    utils::{assert_derivation, assert_owned_by},
+    PREFIX,
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+struct Accounts<'a, 'b: 'a> {
+    auction: &'a AccountInfo<'b>,
+    clock_sysvar: &'a AccountInfo<'b>,
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        auction: next_account_info(account_iter)?,
+        clock_sysvar: next_account_info(account_iter)?,
+    };
+    assert_owned_by(accounts.auction, program_id)?;
+    Ok(accounts)
+}
+
+pub fn start_scheduled_auction<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+    args: StartAuctionArgs,
+) -> ProgramResult {
+    msg!("+ Processing StartScheduledAuction");
+    let accounts = parse_accounts(program_id, accounts)?;
+    let clock = Clock::from_account_info(accounts.clock_sysvar)?;
+
+    assert_derivation(
+        program_id,
+        accounts.auction,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+        ],
+    )?;
+
+    let mut auction = AuctionData::from_account_info(accounts.auction)?;
+    if !auction.start_if_scheduled(clock.unix_timestamp)? {
+        return Err(AuctionError::ScheduledStartNotReached.into());
+    }
+    auction.save(accounts.auction)?;
+
+    Ok(())
+}
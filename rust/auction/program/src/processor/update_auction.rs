@@ -0,0 +1,134 @@
+//! Changes the timing and pricing of an auction that hasn't started yet, so a misconfigured
+//! auction can be fixed without creating a new resource. Every setting is replaced, clients pass
+//! the current value of the ones they keep.
+
+use crate::{
+    errors::AuctionError,
+    processor::{AuctionData, AuctionState, BidState, BuyNowData, PriceFloor},
+    utils::{assert_derivation, assert_owned_by, assert_signer},
+    PREFIX,
+};
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        clock::{Clock, UnixTimestamp},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::Sysvar,
+    },
+};
+
+/// Arguments for the UpdateAuction instruction discriminant.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct UpdateAuctionArgs {
+    /// The resource being auctioned. See AuctionData.
+    pub resource: Pubkey,
+    /// End time is the cut-off point that the auction is forced to end by. See AuctionData.
+    pub end_auction_at: Option<UnixTimestamp>,
+    /// Gap time is how much time after the previous bid where the auction ends. See AuctionData.
+    pub end_auction_gap: Option<UnixTimestamp>,
+    /// Minimum price for any bid to meet. See AuctionData.
+    pub price_floor: PriceFloor,
+    /// Max price of the auction i.e buy now price. See AuctionData.
+    pub max_price: Option<u64>,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    authority: &'a AccountInfo<'b>,
+    auction: &'a AccountInfo<'b>,
+    clock_sysvar: &'a AccountInfo<'b>,
+    buy_now: Option<&'a AccountInfo<'b>>,
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        authority: next_account_info(account_iter)?,
+        auction: next_account_info(account_iter)?,
+        clock_sysvar: next_account_info(account_iter)?,
+        buy_now: next_account_info(account_iter).ok(),
+    };
+    assert_owned_by(accounts.auction, program_id)?;
+    assert_signer(accounts.authority)?;
+    Ok(accounts)
+}
+
+pub fn update_auction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: UpdateAuctionArgs,
+) -> ProgramResult {
+    msg!("+ Processing UpdateAuction");
+    let accounts = parse_accounts(program_id, accounts)?;
+    let clock = Clock::from_account_info(accounts.clock_sysvar)?;
+
+    assert_derivation(
+        program_id,
+        accounts.auction,
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            args.resource.as_ref(),
+        ],
+    )?;
+
+    let mut auction = AuctionData::from_account_info(accounts.auction)?;
+
+    if auction.authority != *accounts.authority.key {
+        return Err(AuctionError::InvalidAuthority.into());
+    }
+
+    // A scheduled auction counts as started once its start time passes, bid or no bid.
+    let started = match auction.start_at {
+        Some(start_at) => clock.unix_timestamp >= start_at,
+        None => false,
+    };
+    if started
+        || !matches!(
+            auction.state,
+            AuctionState::Created | AuctionState::BuyNowCreated
+        )
+    {
+        return Err(AuctionError::InvalidState.into());
+    }
+
+    // The same rules as on creation, against the settings that aren't changing.
+    match (args.end_auction_gap, auction.max_gap_extension) {
+        (Some(gap), _) if gap <= 0 => return Err(AuctionError::InvalidEndAuctionGap.into()),
+        (None, Some(_)) => return Err(AuctionError::InvalidEndAuctionGap.into()),
+        _ => (),
+    }
+
+    if let BidState::SealedBid { .. } = auction.bid_state {
+        if args.end_auction_at.is_none() || args.end_auction_gap.is_some() {
+            msg!("A sealed bid auction needs an end time, and no gap");
+            return Err(AuctionError::InvalidSealedBidConfig.into());
+        }
+    }
+
+    auction.end_auction_at = args.end_auction_at;
+    auction.end_auction_gap = args.end_auction_gap;
+    auction.price_floor = args.price_floor;
+    auction.buy_now_price = args.max_price;
+    auction.validate_buy_now()?;
+
+    // Auctions left in the old buy now state keep their price in AuctionData from now on, and
+    // their buy now account goes back to the authority.
+    if auction.state == AuctionState::BuyNowCreated {
+        let buy_now = accounts.buy_now.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        BuyNowData::from_account_info(program_id, &args.resource, buy_now)?;
+        BuyNowData::close(buy_now, accounts.authority)?;
+    }
+    auction.state = AuctionState::Created;
+    auction.save(accounts.auction)?;
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+//! Changes the recipient, fee schedule or admin of a FeeConfig. Only its current admin can do
+//! this, and the config keeps the address derived from the admin that created it.
+
+use crate::{
+    errors::AuctionError,
+    processor::{FeeConfig, FeeSchedule},
+    utils::{assert_owned_by, assert_signer},
+};
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        borsh::try_from_slice_unchecked,
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct UpdateFeeConfigArgs {
+    /// Key that manages the config from now on.
+    pub admin: Pubkey,
+    /// Wallet receiving the fees.
+    pub recipient: Pubkey,
+    pub schedule: FeeSchedule,
+}
+
+struct Accounts<'a, 'b: 'a> {
+    admin: &'a AccountInfo<'b>,
+    fee_config: &'a AccountInfo<'b>,
+}
+
+fn parse_accounts<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Accounts<'a, 'b>, ProgramError> {
+    let account_iter = &mut accounts.iter();
+    let accounts = Accounts {
+        admin: next_account_info(account_iter)?,
+        fee_config: next_account_info(account_iter)?,
+    };
+
+    assert_owned_by(accounts.fee_config, program_id)?;
+    assert_signer(accounts.admin)?;
+
+    Ok(accounts)
+}
+
+pub fn update_fee_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: UpdateFeeConfigArgs,
+) -> ProgramResult {
+    msg!("+ Processing UpdateFeeConfig");
+    let accounts = parse_accounts(program_id, accounts)?;
+
+    let fee_config: FeeConfig = try_from_slice_unchecked(&accounts.fee_config.data.borrow())?;
+    if fee_config.admin != *accounts.admin.key {
+        return Err(AuctionError::InvalidAuthority.into());
+    }
+
+    args.schedule.validate()?;
+
+    FeeConfig {
+        admin: args.admin,
+        recipient: args.recipient,
+        schedule: args.schedule,
+    }
+    .serialize(&mut &mut accounts.fee_config.data.borrow_mut()[..])?;
+
+    Ok(())
+}
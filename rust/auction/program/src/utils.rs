@@ -172,6 +172,31 @@ pub fn allocate_and_create_token_account<'a>(
     Ok(())
 }
 
+/// Lamports an account holds above its rent exempt minimum.
+pub fn lamports_above_rent(account_info: &AccountInfo) -> Result<u64, ProgramError> {
+    let rent = Rent::get()?;
+    Ok(account_info
+        .lamports()
+        .saturating_sub(rent.minimum_balance(account_info.data_len())))
+}
+
+/// Moves lamports out of an account owned by this program, which needs no system transfer.
+pub fn transfer_lamports(
+    source: &AccountInfo,
+    destination: &AccountInfo,
+    amount: u64,
+) -> ProgramResult {
+    let mut source_lamports = source.lamports.borrow_mut();
+    let mut destination_lamports = destination.lamports.borrow_mut();
+    **source_lamports = source_lamports
+        .checked_sub(amount)
+        .ok_or(AuctionError::NumericalOverflowError)?;
+    **destination_lamports = destination_lamports
+        .checked_add(amount)
+        .ok_or(AuctionError::NumericalOverflowError)?;
+    Ok(())
+}
+
 ///TokenTransferParams
 pub struct TokenTransferParams<'a: 'b, 'b> {
     /// source
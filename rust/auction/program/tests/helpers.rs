@@ -1,18 +1,30 @@
-use solana_program::{hash::Hash, program_pack::Pack, pubkey::Pubkey, system_instruction};
+use num_traits::FromPrimitive;
+use solana_program::{
+    clock::{Clock, UnixTimestamp},
+    hash::Hash,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_instruction,
+};
 use solana_program_test::*;
 use solana_sdk::{
     account::Account,
-    signature::{Keypair, Signer},
-    transaction::Transaction,
+    instruction::{Instruction, InstructionError},
+    signature::{Keypair, Signature, Signer},
+    transaction::{Transaction, TransactionError},
     transport::TransportError,
 };
 use spl_auction::{
+    errors::AuctionError,
     instruction,
     processor::{
-        CancelBidArgs, ClaimBidArgs, CreateAuctionArgs, EndAuctionArgs, PlaceBidArgs, PriceFloor,
-        StartAuctionArgs, WinnerLimit,
+        process_instruction, AuctionData, BidIncrement, CancelAuctionArgs, CancelBidArgs,
+        ClaimBidArgs, CreateAuctionArgs, EndAuctionArgs, PlaceBidArgs, PriceFloor, SettlementMode,
+        StartAuctionArgs, WinnerLimit, BIDDER_POT_LEN,
     },
+    MINT_DECIMALS, PREFIX,
 };
+use std::collections::HashSet;
 
 pub async fn get_account(banks_client: &mut BanksClient, pubkey: &Pubkey) -> Account {
     banks_client
@@ -26,6 +38,7 @@ pub async fn create_mint(
     banks_client: &mut BanksClient,
     payer: &Keypair,
     recent_blockhash: &Hash,
+    decimals: u8,
 ) -> Result<(Keypair, Keypair), TransportError> {
     let rent = banks_client.get_rent().await.unwrap();
     let mint_rent = rent.minimum_balance(spl_token::state::Mint::LEN);
@@ -45,7 +58,7 @@ pub async fn create_mint(
                 &pool_mint.pubkey(),
                 &manager.pubkey(),
                 None,
-                0,
+                decimals,
             )
             .unwrap(),
         ],
@@ -132,6 +145,7 @@ pub async fn get_token_supply(banks_client: &mut BanksClient, mint: &Pubkey) ->
     account_info.supply
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn create_auction(
     banks_client: &mut BanksClient,
     program_id: &Pubkey,
@@ -140,21 +154,31 @@ pub async fn create_auction(
     resource: &Pubkey,
     mint_keypair: &Pubkey,
     max_winners: usize,
+    max_price: Option<u64>,
 ) -> Result<(), TransportError> {
     let transaction = Transaction::new_signed_with_payer(
         &[instruction::create_auction_instruction(
             *program_id,
             payer.pubkey(),
             payer.pubkey(),
-            None,
             CreateAuctionArgs {
                 end_auction_at: None,
                 end_auction_gap: None,
+                max_gap_extension: None,
                 resource: *resource,
                 token_mint: *mint_keypair,
                 winners: WinnerLimit::Capped(max_winners),
                 price_floor: PriceFloor::None([0u8; 32]),
-                max_price: None,
+                max_price,
+                dutch_auction: None,
+                sealed_bid: None,
+                settlement: SettlementMode::FirstPrice,
+                bid_increment: BidIncrement::Default,
+                multi_unit: false,
+                native_bids: false,
+                start_at: None,
+                fee_config: None,
+                gate: None,
             },
         )],
         Some(&payer.pubkey()),
@@ -189,6 +213,29 @@ pub async fn end_auction(
     Ok(())
 }
 
+pub async fn cancel_auction(
+    banks_client: &mut BanksClient,
+    program_id: &Pubkey,
+    recent_blockhash: &Hash,
+    payer: &Keypair,
+    resource: &Pubkey,
+) -> Result<(), TransportError> {
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::cancel_auction_instruction(
+            *program_id,
+            payer.pubkey(),
+            CancelAuctionArgs {
+                resource: *resource,
+            },
+        )],
+        Some(&payer.pubkey()),
+        &[payer],
+        *recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await?;
+    Ok(())
+}
+
 pub async fn start_auction(
     banks_client: &mut BanksClient,
     program_id: &Pubkey,
@@ -219,7 +266,7 @@ pub async fn place_bid(
     program_id: &Pubkey,
     payer: &Keypair,
     bidder: &Keypair,
-    bidder_spl_account: &Keypair,
+    bidder_pot_token: &Pubkey,
     transfer_authority: &Keypair,
     resource: &Pubkey,
     mint: &Pubkey,
@@ -230,11 +277,13 @@ pub async fn place_bid(
             *program_id,
             bidder.pubkey(),             // Wallet used to identify bidder
             bidder.pubkey(), // SPL token account (source) using same account here for ease of testing
-            bidder_spl_account.pubkey(), // SPL Token Account (Destination)
+            *bidder_pot_token, // SPL Token Account (Destination)
             *mint,           // Token Mint
             transfer_authority.pubkey(), // Approved to Move Tokens
             payer.pubkey(),  // Pays for Transactions
             None,
+            None,
+            None,
             PlaceBidArgs {
                 amount,
                 resource: *resource,
@@ -266,6 +315,7 @@ pub async fn cancel_bid(
             bidder.pubkey(),
             bidder_spl_account.pubkey(),
             *mint,
+            None,
             CancelBidArgs {
                 resource: *resource,
             },
@@ -320,17 +370,18 @@ pub async fn claim_bid(
     let transaction = Transaction::new_signed_with_payer(
         &[instruction::claim_bid_instruction(
             *program_id,
-            authority.pubkey(),
             *seller,
+            authority.pubkey(),
             bidder.pubkey(),
             bidder_spl_account.pubkey(),
             *mint,
             Pubkey::default(),
             Pubkey::default(),
             Pubkey::default(),
+            None,
+            None,
             ClaimBidArgs {
                 resource: *resource,
-                fee_percentage: 0,
             },
         )],
         Some(&payer.pubkey()),
@@ -340,3 +391,419 @@ pub async fn claim_bid(
     banks_client.process_transaction(transaction).await?;
     Ok(())
 }
+
+/// A bidder with their own funded token account, which their wallet owns and can move tokens
+/// out of, and the pot accounts their bids go to.
+pub struct Bidder {
+    pub wallet: Keypair,
+    pub token: Pubkey,
+    pub pot: Pubkey,
+    pub pot_token: Pubkey,
+}
+
+/// An auction test that can move the clock, with a mint and five funded bidders. The auction
+/// itself is only created by `create`.
+pub struct AuctionTest {
+    pub context: ProgramTestContext,
+    pub program_id: Pubkey,
+    pub resource: Pubkey,
+    pub auction: Pubkey,
+    pub mint: Pubkey,
+    pub mint_authority: Keypair,
+    pub bidders: Vec<Bidder>,
+    /// Fee recipient token account, fee recipient and fee config passed when claiming bids, only
+    /// checked on auctions with a fee config.
+    pub fee_accounts: (Pubkey, Pubkey, Pubkey),
+    sent: HashSet<Signature>,
+}
+
+impl AuctionTest {
+    /// Bids in a new mint, each bidder holding 10_000_000 tokens.
+    pub async fn new() -> Self {
+        Self::setup(false).await
+    }
+
+    /// Bids in lamports, each bidder holding 1 SOL.
+    pub async fn new_native() -> Self {
+        Self::setup(true).await
+    }
+
+    async fn setup(native: bool) -> Self {
+        let program_id = Pubkey::new_unique();
+        let mut program_test =
+            ProgramTest::new("spl_auction", program_id, processor!(process_instruction));
+        program_test.add_program(
+            "spl_token",
+            spl_token::id(),
+            processor!(spl_token::processor::Processor::process),
+        );
+
+        let context = program_test.start_with_context().await;
+        let resource = Pubkey::new_unique();
+        let (auction, _) = Pubkey::find_program_address(
+            &[PREFIX.as_bytes(), program_id.as_ref(), resource.as_ref()],
+            &program_id,
+        );
+        let mut test = AuctionTest {
+            context,
+            program_id,
+            resource,
+            auction,
+            mint: spl_token::native_mint::id(),
+            mint_authority: Keypair::new(),
+            bidders: vec![],
+            fee_accounts: (Pubkey::default(), Pubkey::default(), Pubkey::default()),
+            sent: HashSet::new(),
+        };
+
+        if !native {
+            let rent = test.context.banks_client.get_rent().await.unwrap();
+            let mint = Keypair::new();
+            let payer = test.context.payer.pubkey();
+            test.process(
+                &[
+                    system_instruction::create_account(
+                        &payer,
+                        &mint.pubkey(),
+                        rent.minimum_balance(spl_token::state::Mint::LEN),
+                        spl_token::state::Mint::LEN as u64,
+                        &spl_token::id(),
+                    ),
+                    spl_token::instruction::initialize_mint(
+                        &spl_token::id(),
+                        &mint.pubkey(),
+                        &test.mint_authority.pubkey(),
+                        None,
+                        MINT_DECIMALS,
+                    )
+                    .unwrap(),
+                ],
+                &[&mint],
+            )
+            .await
+            .unwrap();
+            test.mint = mint.pubkey();
+        }
+
+        for _ in 0..5 {
+            let wallet = Keypair::new();
+            let (pot, _) = Pubkey::find_program_address(
+                &[
+                    PREFIX.as_bytes(),
+                    program_id.as_ref(),
+                    auction.as_ref(),
+                    wallet.pubkey().as_ref(),
+                ],
+                &program_id,
+            );
+            let payer = test.context.payer.pubkey();
+            test.process(
+                &[system_instruction::transfer(
+                    &payer,
+                    &wallet.pubkey(),
+                    1_000_000_000,
+                )],
+                &[],
+            )
+            .await
+            .unwrap();
+
+            let bidder = if native {
+                // A native pot holds the bid itself.
+                Bidder {
+                    token: wallet.pubkey(),
+                    pot,
+                    pot_token: pot,
+                    wallet,
+                }
+            } else {
+                let token = test.create_token_account(&wallet.pubkey()).await;
+                test.mint_to(&token, 10_000_000).await;
+                Bidder {
+                    token,
+                    pot,
+                    pot_token: Pubkey::find_program_address(&[pot.as_ref()], &program_id).0,
+                    wallet,
+                }
+            };
+            test.bidders.push(bidder);
+        }
+        test
+    }
+
+    /// Sends the instructions paid for by the payer, on a new blockhash if the same transaction
+    /// was already sent.
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), TransportError> {
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let mut blockhash = self.context.banks_client.get_latest_blockhash().await?;
+        loop {
+            let transaction = Transaction::new_signed_with_payer(
+                instructions,
+                Some(&self.context.payer.pubkey()),
+                &all_signers,
+                blockhash,
+            );
+            if self.sent.insert(transaction.signatures[0]) {
+                self.context
+                    .banks_client
+                    .process_transaction(transaction)
+                    .await?;
+                return Ok(());
+            }
+            blockhash = self
+                .context
+                .banks_client
+                .get_new_latest_blockhash(&blockhash)
+                .await?;
+        }
+    }
+
+    pub async fn create_token_account(&mut self, owner: &Pubkey) -> Pubkey {
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let account = Keypair::new();
+        let payer = self.context.payer.pubkey();
+        self.process(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &account.pubkey(),
+                    rent.minimum_balance(spl_token::state::Account::LEN),
+                    spl_token::state::Account::LEN as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_account(
+                    &spl_token::id(),
+                    &account.pubkey(),
+                    &self.mint,
+                    owner,
+                )
+                .unwrap(),
+            ],
+            &[&account],
+        )
+        .await
+        .unwrap();
+        account.pubkey()
+    }
+
+    pub async fn mint_to(&mut self, account: &Pubkey, amount: u64) {
+        let mint_authority = Keypair::from_bytes(&self.mint_authority.to_bytes()).unwrap();
+        let instruction = spl_token::instruction::mint_to(
+            &spl_token::id(),
+            &self.mint,
+            account,
+            &mint_authority.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap();
+        self.process(&[instruction], &[&mint_authority])
+            .await
+            .unwrap();
+    }
+
+    /// Arguments of a capped single winner English auction on this test's resource and mint,
+    /// for tests to adjust.
+    pub fn args(&self) -> CreateAuctionArgs {
+        CreateAuctionArgs {
+            winners: WinnerLimit::Capped(1),
+            end_auction_at: None,
+            end_auction_gap: None,
+            max_gap_extension: None,
+            token_mint: self.mint,
+            resource: self.resource,
+            price_floor: PriceFloor::None([0; 32]),
+            max_price: None,
+            dutch_auction: None,
+            sealed_bid: None,
+            settlement: SettlementMode::FirstPrice,
+            bid_increment: BidIncrement::Default,
+            multi_unit: false,
+            native_bids: false,
+            start_at: None,
+            fee_config: None,
+            gate: None,
+        }
+    }
+
+    /// Creates the auction with the payer as its authority.
+    pub async fn create(&mut self, args: CreateAuctionArgs) -> Result<(), TransportError> {
+        let payer = self.context.payer.pubkey();
+        let instruction =
+            instruction::create_auction_instruction(self.program_id, payer, payer, args);
+        self.process(&[instruction], &[]).await
+    }
+
+    pub async fn start(&mut self) -> Result<(), TransportError> {
+        let instruction = instruction::start_auction_instruction(
+            self.program_id,
+            self.context.payer.pubkey(),
+            StartAuctionArgs {
+                resource: self.resource,
+            },
+        );
+        self.process(&[instruction], &[]).await
+    }
+
+    pub async fn end(&mut self, reveal: Option<(u64, u64)>) -> Result<(), TransportError> {
+        let instruction = instruction::end_auction_instruction(
+            self.program_id,
+            self.context.payer.pubkey(),
+            EndAuctionArgs {
+                resource: self.resource,
+                reveal,
+            },
+        );
+        self.process(&[instruction], &[]).await
+    }
+
+    pub async fn now(&mut self) -> UnixTimestamp {
+        self.context
+            .banks_client
+            .get_sysvar::<Clock>()
+            .await
+            .unwrap()
+            .unix_timestamp
+    }
+
+    /// Moves the clock `seconds` forward.
+    pub async fn warp(&mut self, seconds: UnixTimestamp) {
+        let mut clock = self
+            .context
+            .banks_client
+            .get_sysvar::<Clock>()
+            .await
+            .unwrap();
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+
+    pub async fn auction_data(&mut self) -> AuctionData {
+        let auction = self.auction;
+        AuctionData::from_slice(
+            &get_account(&mut self.context.banks_client, &auction)
+                .await
+                .data,
+        )
+        .unwrap()
+    }
+
+    pub async fn token_balance(&mut self, token: &Pubkey) -> u64 {
+        get_token_balance(&mut self.context.banks_client, token).await
+    }
+
+    pub async fn lamports(&mut self, key: &Pubkey) -> u64 {
+        self.context.banks_client.get_balance(*key).await.unwrap()
+    }
+
+    /// Balance the bidder bids out of, tokens or lamports.
+    pub async fn bidder_balance(&mut self, bidder: usize) -> u64 {
+        let token = self.bidders[bidder].token;
+        if self.mint == spl_token::native_mint::id() {
+            self.lamports(&token).await
+        } else {
+            self.token_balance(&token).await
+        }
+    }
+
+    /// Balance held in a bidder's pot, tokens or, for native bids, lamports above the rent.
+    pub async fn pot_balance(&mut self, bidder: usize) -> u64 {
+        let pot_token = self.bidders[bidder].pot_token;
+        if self.mint == spl_token::native_mint::id() {
+            let rent = self.context.banks_client.get_rent().await.unwrap();
+            self.lamports(&pot_token).await - rent.minimum_balance(BIDDER_POT_LEN)
+        } else {
+            self.token_balance(&pot_token).await
+        }
+    }
+
+    /// A plain bid by `bidder`, who moves the tokens out of their own account.
+    pub fn bid_instruction(&self, bidder: usize, amount: u64) -> Instruction {
+        let bidder = &self.bidders[bidder];
+        instruction::place_bid_instruction(
+            self.program_id,
+            bidder.wallet.pubkey(),
+            bidder.token,
+            bidder.pot_token,
+            self.mint,
+            bidder.wallet.pubkey(),
+            self.context.payer.pubkey(),
+            None,
+            None,
+            None,
+            PlaceBidArgs {
+                amount,
+                resource: self.resource,
+            },
+        )
+    }
+
+    /// Sends an instruction signed by `bidder`.
+    pub async fn process_as(
+        &mut self,
+        bidder: usize,
+        instruction: Instruction,
+    ) -> Result<(), TransportError> {
+        let wallet = Keypair::from_bytes(&self.bidders[bidder].wallet.to_bytes()).unwrap();
+        self.process(&[instruction], &[&wallet]).await
+    }
+
+    pub async fn bid(&mut self, bidder: usize, amount: u64) -> Result<(), TransportError> {
+        let instruction = self.bid_instruction(bidder, amount);
+        self.process_as(bidder, instruction).await
+    }
+
+    /// Claims a winning bid into `destination` as the auction authority, refunding what the
+    /// winner doesn't pay to `bidder_token`.
+    pub async fn claim(
+        &mut self,
+        bidder: usize,
+        destination: &Pubkey,
+        bidder_token: Option<Pubkey>,
+        referrer: Option<Pubkey>,
+    ) -> Result<(), TransportError> {
+        let instruction = self.claim_instruction(bidder, destination, bidder_token, referrer);
+        self.process(&[instruction], &[]).await
+    }
+
+    pub fn claim_instruction(
+        &self,
+        bidder: usize,
+        destination: &Pubkey,
+        bidder_token: Option<Pubkey>,
+        referrer: Option<Pubkey>,
+    ) -> Instruction {
+        let (fee_recipient_token, fee_recipient, fee_config) = self.fee_accounts;
+        instruction::claim_bid_instruction(
+            self.program_id,
+            *destination,
+            self.context.payer.pubkey(),
+            self.bidders[bidder].wallet.pubkey(),
+            self.bidders[bidder].pot_token,
+            self.mint,
+            fee_recipient_token,
+            fee_recipient,
+            fee_config,
+            bidder_token,
+            referrer,
+            ClaimBidArgs {
+                resource: self.resource,
+            },
+        )
+    }
+}
+
+/// The auction error a transaction failed with.
+pub fn auction_error(err: TransportError) -> AuctionError {
+    match err.unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            AuctionError::from_u32(code).expect("auction error")
+        }
+        err => panic!("unexpected error {:?}", err),
+    }
+}
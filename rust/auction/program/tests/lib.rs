@@ -1,31 +1,41 @@
 #![allow(warnings)]
 
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::borsh::try_from_slice_unchecked;
+use num_traits::FromPrimitive;
+use solana_program::{borsh::try_from_slice_unchecked, hash::hashv};
 use solana_program_test::*;
 use solana_sdk::program_pack::Pack;
 use solana_sdk::{
-    account::Account,
+    account::{Account, AccountSharedData},
     hash::Hash,
-    instruction::{AccountMeta, Instruction},
+    instruction::{AccountMeta, Instruction, InstructionError},
     pubkey::Pubkey,
     signature::{Keypair, Signer},
     system_instruction, system_program,
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
     transport::TransportError,
 };
 use spl_auction::{
+    errors::AuctionError,
     instruction,
     processor::{
-        process_instruction, AuctionData, AuctionState, Bid, BidState, BidderPot, CancelBidArgs,
-        CreateAuctionArgs, PlaceBidArgs, PriceFloor, StartAuctionArgs, WinnerLimit,
+        process_instruction, AuctionData, AuctionState, Bid, BidEventKind, BidHistory,
+        BidIncrement, BidIncrementTier, BidState, BidderGate, BidderPot, BuyNowData, CancelBidArgs,
+        ClaimForfeitedBidArgs, ClearingRule, CreateAuctionArgs, CreateBidHistoryArgs,
+        CreateFeeConfigArgs, DutchAuctionConfig, DutchDecay, FeeSchedule, JoinAllowlistArgs,
+        PlaceBidArgs, PlaceMultiUnitBidArgs, PlaceSealedBidArgs, PriceFloor, ReferralRegistry,
+        RefundLosersArgs, RevealBidArgs, SealedBidConfig, SettleAuctionArgs, SettlementMode,
+        StartAuctionArgs, UpdateAuctionArgs, UpdateFeeConfigArgs, WinnerLimit,
+        AUCTION_DATA_EXTENSION_SIZE, REFERRAL_REGISTRY_KEY,
     },
-    PREFIX,
+    MINT_DECIMALS, PREFIX,
 };
 use std::mem;
 
 mod helpers;
 
+use helpers::{auction_error, AuctionTest};
+
 /// Initialize an auction with a random resource, and generate bidders with tokens that can be used
 /// for testing.
 async fn setup_auction(
@@ -34,7 +44,28 @@ async fn setup_auction(
 ) -> (
     Pubkey,
     BanksClient,
-    Vec<(Keypair, Keypair, Pubkey)>,
+    Vec<(Keypair, Pubkey, Pubkey)>,
+    Keypair,
+    Pubkey,
+    Pubkey,
+    Pubkey,
+    Pubkey,
+    Hash,
+) {
+    setup_auction_with_decimals(start, max_winners, MINT_DECIMALS, None).await
+}
+
+/// Same as setup_auction, bidding in a mint with the given decimals and buying now at
+/// `max_price` if set.
+async fn setup_auction_with_decimals(
+    start: bool,
+    max_winners: usize,
+    decimals: u8,
+    max_price: Option<u64>,
+) -> (
+    Pubkey,
+    BanksClient,
+    Vec<(Keypair, Pubkey, Pubkey)>,
     Keypair,
     Pubkey,
     Pubkey,
@@ -46,13 +77,18 @@ async fn setup_auction(
     let program_id = Pubkey::new_unique();
     let mut program_test =
         ProgramTest::new("spl_auction", program_id, processor!(process_instruction));
+    program_test.add_program(
+        "spl_token",
+        spl_token::id(),
+        processor!(spl_token::processor::Processor::process),
+    );
 
     // Start executing test.
     let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
 
     // Create a Token mint to mint some test tokens with.
     let (mint_keypair, mint_manager) =
-        helpers::create_mint(&mut banks_client, &payer, &recent_blockhash)
+        helpers::create_mint(&mut banks_client, &payer, &recent_blockhash, decimals)
             .await
             .unwrap();
 
@@ -70,6 +106,7 @@ async fn setup_auction(
         &resource,
         &mint_keypair.pubkey(),
         max_winners,
+        max_price,
     )
     .await
     .unwrap();
@@ -79,8 +116,6 @@ async fn setup_auction(
     for n in 0..5 {
         // Bidder SPL Account, with Minted Tokens
         let bidder = Keypair::new();
-        // PDA in the auction for the Bidder to deposit their funds to.
-        let auction_spl_pot = Keypair::new();
 
         // Generate User SPL Wallet Account
         helpers::create_token_account(
@@ -105,17 +140,9 @@ async fn setup_auction(
             &program_id,
         );
 
-        // Generate Auction SPL Pot to Transfer to.
-        helpers::create_token_account(
-            &mut banks_client,
-            &payer,
-            &recent_blockhash,
-            &auction_spl_pot,
-            &mint_keypair.pubkey(),
-            &auction_pubkey,
-        )
-        .await
-        .unwrap();
+        // The SPL pot the Bidder deposits their funds to, created with their first bid.
+        let (auction_spl_pot, _) =
+            Pubkey::find_program_address(&[bid_pot_pubkey.as_ref()], &program_id);
 
         // Mint Tokens
         helpers::mint_tokens(
@@ -146,7 +173,7 @@ async fn setup_auction(
 
     assert_eq!(auction.authority, payer.pubkey());
     assert_eq!(auction.last_bid, None);
-    assert_eq!(auction.state as i32, AuctionState::create(false) as i32);
+    assert_eq!(auction.state as i32, AuctionState::Created as i32);
     assert_eq!(auction.end_auction_at, None);
 
     // Start Auction.
@@ -694,3 +721,1800 @@ async fn test_incorrect_runs() {
     }
 }
 */
+
+/// Places a bid from the first bidder of a fresh auction, after approving `allowance` to the
+/// transfer authority if any, and returns the auction error it fails with.
+async fn failing_bid(decimals: u8, allowance: Option<u64>, amount: u64) -> AuctionError {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        _mint_authority,
+        _auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction_with_decimals(true, 3, decimals, None).await;
+    let (bidder, bidder_pot_token, _) = &bidders[0];
+
+    let transfer_authority = Keypair::new();
+    if let Some(allowance) = allowance {
+        helpers::approve(
+            &mut banks_client,
+            &recent_blockhash,
+            &payer,
+            &transfer_authority.pubkey(),
+            bidder,
+            allowance,
+        )
+        .await
+        .unwrap();
+    }
+
+    let err = helpers::place_bid(
+        &mut banks_client,
+        &recent_blockhash,
+        &program_id,
+        &payer,
+        bidder,
+        bidder_pot_token,
+        &transfer_authority,
+        &resource,
+        &mint,
+        amount,
+    )
+    .await
+    .unwrap_err();
+
+    // Nothing may have been taken from the bidder.
+    assert_eq!(
+        helpers::get_token_balance(&mut banks_client, &bidder.pubkey()).await,
+        10_000_000
+    );
+
+    match err.unwrap() {
+        TransactionError::InstructionError(0, InstructionError::Custom(code)) => {
+            AuctionError::from_u32(code).expect("auction error")
+        }
+        err => panic!("unexpected error {:?}", err),
+    }
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_bid_balance_too_low() {
+    let err = failing_bid(MINT_DECIMALS, Some(20_000_000), 10_000_001).await;
+    assert_eq!(err, AuctionError::BalanceTooLow);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_bid_allowance_too_low() {
+    let err = failing_bid(MINT_DECIMALS, Some(999), 1000).await;
+    assert_eq!(err, AuctionError::AllowanceTooLow);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_bid_without_allowance() {
+    let err = failing_bid(MINT_DECIMALS, None, 1000).await;
+    assert_eq!(err, AuctionError::AllowanceTooLow);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_bid_invalid_mint_decimals() {
+    let err = failing_bid(0, Some(1000), 1000).await;
+    assert_eq!(err, AuctionError::InvalidMintDecimals);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_bid_within_balance_and_allowance() {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        _mint_authority,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction(true, 3).await;
+    let (bidder, bidder_pot_token, _) = &bidders[0];
+
+    let transfer_authority = Keypair::new();
+    helpers::approve(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        &transfer_authority.pubkey(),
+        bidder,
+        1000,
+    )
+    .await
+    .unwrap();
+
+    helpers::place_bid(
+        &mut banks_client,
+        &recent_blockhash,
+        &program_id,
+        &payer,
+        bidder,
+        bidder_pot_token,
+        &transfer_authority,
+        &resource,
+        &mint,
+        1000,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        helpers::get_token_balance(&mut banks_client, &bidder.pubkey()).await,
+        10_000_000 - 1000
+    );
+    assert_eq!(
+        helpers::get_token_balance(&mut banks_client, bidder_pot_token).await,
+        1000
+    );
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_cancel_auction_without_bids() {
+    let (
+        program_id,
+        mut banks_client,
+        _bidders,
+        payer,
+        resource,
+        _mint,
+        _mint_authority,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction(true, 3).await;
+
+    helpers::cancel_auction(
+        &mut banks_client,
+        &program_id,
+        &recent_blockhash,
+        &payer,
+        &resource,
+    )
+    .await
+    .unwrap();
+
+    let auction = AuctionData::from_slice(
+        &helpers::get_account(&mut banks_client, &auction_pubkey)
+            .await
+            .data,
+    )
+    .unwrap();
+    assert_eq!(auction.state, AuctionState::Cancelled);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_cancel_auction_with_bids() {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        _mint_authority,
+        _auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction(true, 3).await;
+    let (bidder, bidder_pot_token, _) = &bidders[0];
+
+    let transfer_authority = Keypair::new();
+    helpers::approve(
+        &mut banks_client,
+        &recent_blockhash,
+        &payer,
+        &transfer_authority.pubkey(),
+        bidder,
+        1000,
+    )
+    .await
+    .unwrap();
+    helpers::place_bid(
+        &mut banks_client,
+        &recent_blockhash,
+        &program_id,
+        &payer,
+        bidder,
+        bidder_pot_token,
+        &transfer_authority,
+        &resource,
+        &mint,
+        1000,
+    )
+    .await
+    .unwrap();
+
+    let err = helpers::cancel_auction(
+        &mut banks_client,
+        &program_id,
+        &recent_blockhash,
+        &payer,
+        &resource,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(AuctionError::AuctionHasBids as u32)
+        )
+    );
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_buy_now_ends_auction() {
+    let (
+        program_id,
+        mut banks_client,
+        bidders,
+        payer,
+        resource,
+        mint,
+        _mint_authority,
+        auction_pubkey,
+        recent_blockhash,
+    ) = setup_auction_with_decimals(true, 1, MINT_DECIMALS, Some(5000)).await;
+
+    // The second bid goes over the buy now price, and is only charged that price.
+    for ((bidder, bidder_pot_token, _), amount) in bidders.iter().zip(&[1000, 6000]) {
+        let transfer_authority = Keypair::new();
+        helpers::approve(
+            &mut banks_client,
+            &recent_blockhash,
+            &payer,
+            &transfer_authority.pubkey(),
+            bidder,
+            *amount,
+        )
+        .await
+        .unwrap();
+        helpers::place_bid(
+            &mut banks_client,
+            &recent_blockhash,
+            &program_id,
+            &payer,
+            bidder,
+            bidder_pot_token,
+            &transfer_authority,
+            &resource,
+            &mint,
+            *amount,
+        )
+        .await
+        .unwrap();
+    }
+    assert_eq!(
+        helpers::get_token_balance(&mut banks_client, &bidders[1].1).await,
+        5000
+    );
+
+    let auction = AuctionData::from_slice(
+        &helpers::get_account(&mut banks_client, &auction_pubkey)
+            .await
+            .data,
+    )
+    .unwrap();
+    assert_eq!(auction.state, AuctionState::Ended);
+    assert!(auction.is_winner(&bidders[0].2).is_none());
+    assert!(auction.is_winner(&bidders[1].2).is_some());
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_dutch_auction_sells_at_asking_price() {
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.dutch_auction = Some(DutchAuctionConfig {
+        start_price: 10000,
+        floor_price: 1000,
+        decay: DutchDecay::Linear { duration: 100 },
+    });
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+
+    // Halfway through the decay the asking price is 5500.
+    test.warp(50).await;
+    let err = test.bid(0, 5000).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::BidTooSmall);
+
+    // A bid over the asking price is only charged it, and wins outright.
+    test.bid(1, 6000).await.unwrap();
+    assert_eq!(test.pot_balance(1).await, 5500);
+    assert_eq!(test.bidder_balance(1).await, 10_000_000 - 5500);
+
+    let auction = test.auction_data().await;
+    assert_eq!(auction.state, AuctionState::Ended);
+    assert!(auction.is_winner(&test.bidders[1].pot).is_some());
+
+    // Nobody can bid on it any more.
+    let err = test.bid(2, 10000).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidState);
+}
+
+/// A started sealed bid auction for two winners, closing to bids 100 seconds in and open to
+/// reveals for 100 seconds after that.
+async fn sealed_auction(forfeit_basis_points: u16) -> AuctionTest {
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.winners = WinnerLimit::Capped(2);
+    args.end_auction_at = Some(100);
+    args.sealed_bid = Some(SealedBidConfig {
+        reveal_period: 100,
+        forfeit_basis_points,
+    });
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+    test
+}
+
+async fn place_sealed_bid(
+    test: &mut AuctionTest,
+    bidder: usize,
+    deposit: u64,
+    commitment: Hash,
+) -> Result<(), TransportError> {
+    let wallet = test.bidders[bidder].wallet.pubkey();
+    let instruction = instruction::place_sealed_bid_instruction(
+        test.program_id,
+        wallet,
+        test.bidders[bidder].token,
+        test.bidders[bidder].pot_token,
+        test.mint,
+        wallet,
+        test.context.payer.pubkey(),
+        None,
+        None,
+        None,
+        PlaceSealedBidArgs {
+            deposit,
+            commitment,
+            resource: test.resource,
+        },
+    );
+    test.process_as(bidder, instruction).await
+}
+
+/// Commits `bidder` to a bid of `amount` with `salt`.
+async fn commit_bid(
+    test: &mut AuctionTest,
+    bidder: usize,
+    deposit: u64,
+    amount: u64,
+    salt: u64,
+) -> Result<(), TransportError> {
+    let commitment = instruction::sealed_bid_commitment(
+        &test.program_id,
+        &test.resource,
+        &test.bidders[bidder].wallet.pubkey(),
+        amount,
+        salt,
+    );
+    place_sealed_bid(test, bidder, deposit, commitment).await
+}
+
+async fn reveal_bid(
+    test: &mut AuctionTest,
+    bidder: usize,
+    amount: u64,
+    salt: u64,
+) -> Result<(), TransportError> {
+    let instruction = instruction::reveal_bid_instruction(
+        test.program_id,
+        test.bidders[bidder].wallet.pubkey(),
+        test.bidders[bidder].token,
+        test.bidders[bidder].pot_token,
+        test.mint,
+        RevealBidArgs {
+            resource: test.resource,
+            amount,
+            salt,
+        },
+    );
+    test.process_as(bidder, instruction).await
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_sealed_bid_commit_and_reveal() {
+    let mut test = sealed_auction(0).await;
+    commit_bid(&mut test, 0, 5000, 3000, 42).await.unwrap();
+
+    // A commitment copied by another bidder doesn't reveal for them.
+    let copied = instruction::sealed_bid_commitment(
+        &test.program_id,
+        &test.resource,
+        &test.bidders[0].wallet.pubkey(),
+        3000,
+        42,
+    );
+    place_sealed_bid(&mut test, 1, 5000, copied).await.unwrap();
+
+    // Nothing is revealed while bidding is open.
+    let err = reveal_bid(&mut test, 0, 3000, 42).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::NotInRevealPhase);
+
+    test.warp(101).await;
+    let err = reveal_bid(&mut test, 1, 3000, 42).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidReveal);
+
+    // The deposit above the revealed bid goes straight back.
+    reveal_bid(&mut test, 0, 3000, 42).await.unwrap();
+    assert_eq!(test.pot_balance(0).await, 3000);
+    assert_eq!(test.bidder_balance(0).await, 10_000_000 - 3000);
+
+    let err = reveal_bid(&mut test, 0, 3000, 42).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::BidAlreadyRevealed);
+
+    let auction = test.auction_data().await;
+    assert_eq!(auction.bid_state.bids().len(), 1);
+    assert!(auction.is_winner(&test.bidders[0].pot).is_some());
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_sealed_bid_wrong_salt() {
+    let mut test = sealed_auction(0).await;
+    commit_bid(&mut test, 0, 5000, 3000, 42).await.unwrap();
+    test.warp(101).await;
+
+    let err = reveal_bid(&mut test, 0, 3000, 43).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidReveal);
+    assert_eq!(test.pot_balance(0).await, 5000);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_sealed_bid_reveal_above_deposit() {
+    let mut test = sealed_auction(0).await;
+    commit_bid(&mut test, 0, 5000, 6000, 42).await.unwrap();
+    test.warp(101).await;
+
+    let err = reveal_bid(&mut test, 0, 6000, 42).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::RevealExceedsDeposit);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_sealed_bid_forfeit_after_reveal_window() {
+    let mut test = sealed_auction(2000).await;
+    commit_bid(&mut test, 0, 5000, 3000, 42).await.unwrap();
+    let payer = test.context.payer.pubkey();
+    let destination = test.create_token_account(&payer).await;
+
+    let claim_forfeit = instruction::claim_forfeited_bid_instruction(
+        test.program_id,
+        destination,
+        test.bidders[0].token,
+        test.bidders[0].pot_token,
+        payer,
+        test.bidders[0].wallet.pubkey(),
+        test.mint,
+        ClaimForfeitedBidArgs {
+            resource: test.resource,
+        },
+    );
+
+    // The bidder can still reveal until the reveal period is over.
+    test.warp(101).await;
+    let err = test
+        .process(&[claim_forfeit.clone()], &[])
+        .await
+        .unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidState);
+
+    test.warp(100).await;
+    test.process(&[claim_forfeit], &[]).await.unwrap();
+    assert_eq!(test.token_balance(&destination).await, 1000);
+    assert_eq!(test.pot_balance(0).await, 0);
+    assert_eq!(test.bidder_balance(0).await, 10_000_000 - 1000);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_sealed_bid_cancel() {
+    let mut test = sealed_auction(0).await;
+    commit_bid(&mut test, 0, 5000, 3000, 42).await.unwrap();
+    commit_bid(&mut test, 1, 5000, 3000, 42).await.unwrap();
+
+    let cancel = |test: &AuctionTest, bidder: usize| {
+        instruction::cancel_sealed_bid_instruction(
+            test.program_id,
+            test.bidders[bidder].wallet.pubkey(),
+            test.bidders[bidder].token,
+            test.bidders[bidder].pot_token,
+            test.mint,
+            None,
+            CancelBidArgs {
+                resource: test.resource,
+            },
+        )
+    };
+
+    // Bids can be pulled while bidding is open.
+    let instruction = cancel(&test, 0);
+    test.process_as(0, instruction).await.unwrap();
+    assert_eq!(test.bidder_balance(0).await, 10_000_000);
+
+    // Once bidding closes, an unrevealed bid can't be pulled.
+    test.warp(101).await;
+    let instruction = cancel(&test, 1);
+    let err = test.process_as(1, instruction).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::SealedBidNotRevealed);
+    assert_eq!(test.pot_balance(1).await, 5000);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_second_price_settlement() {
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.settlement = SettlementMode::SecondPrice;
+
+    // A Dutch auction has no losing bids to price its winner against.
+    let mut dutch = args.clone();
+    dutch.dutch_auction = Some(DutchAuctionConfig {
+        start_price: 10000,
+        floor_price: 1000,
+        decay: DutchDecay::Linear { duration: 100 },
+    });
+    let err = test.create(dutch).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidSettlementMode);
+
+    args.winners = WinnerLimit::Capped(2);
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+    for (bidder, amount) in [(0, 1000), (1, 2000), (2, 3000)] {
+        test.bid(bidder, amount).await.unwrap();
+    }
+    test.end(None).await.unwrap();
+    test.warp(1).await;
+
+    // The top winner pays the bid below theirs, the lowest winner pays the bid pushed out.
+    let payer = test.context.payer.pubkey();
+    let seller = test.create_token_account(&payer).await;
+    let refund = Some(test.bidders[2].token);
+    test.claim(2, &seller, refund, None).await.unwrap();
+    assert_eq!(test.token_balance(&seller).await, 2000);
+    assert_eq!(test.bidder_balance(2).await, 10_000_000 - 2000);
+
+    // The refund can't be sent anywhere but the winner's own account.
+    let err = test
+        .claim(1, &seller, Some(seller), None)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+    let refund = Some(test.bidders[1].token);
+    test.claim(1, &seller, refund, None).await.unwrap();
+    assert_eq!(test.token_balance(&seller).await, 3000);
+    assert_eq!(test.bidder_balance(1).await, 10_000_000 - 1000);
+
+    assert_eq!(test.auction_data().await.clearing_price, Some(2000));
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_gap_extends_end_up_to_hard_end() {
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.end_auction_at = Some(100);
+    args.end_auction_gap = Some(60);
+    args.max_gap_extension = Some(30);
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+    let start = test.now().await;
+    assert_eq!(test.auction_data().await.hard_end_at, Some(start + 130));
+
+    // A bid within the gap of the end moves it to a gap after the bid.
+    test.warp(50).await;
+    test.bid(0, 1000).await.unwrap();
+    assert_eq!(test.auction_data().await.ended_at, Some(start + 110));
+
+    // But never past the hard end.
+    test.warp(50).await;
+    test.bid(1, 2000).await.unwrap();
+    assert_eq!(test.auction_data().await.ended_at, Some(start + 130));
+
+    // Past it, a bid only ends the auction.
+    test.warp(31).await;
+    test.bid(2, 3000).await.unwrap();
+    let auction = test.auction_data().await;
+    assert_eq!(auction.state, AuctionState::Ended);
+    assert!(auction.is_winner(&test.bidders[1].pot).is_some());
+    assert!(auction.is_winner(&test.bidders[2].pot).is_none());
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_legacy_auction_keeps_hardcoded_gap() {
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.end_auction_at = Some(100);
+    args.end_auction_gap = Some(60);
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+    let start = test.now().await;
+
+    // Cut the auction back to the layout it had before fields were appended to it.
+    let auction = test.auction;
+    let mut account = helpers::get_account(&mut test.context.banks_client, &auction).await;
+    let legacy_len = account.data.len() - AUCTION_DATA_EXTENSION_SIZE;
+    account.data.truncate(legacy_len);
+    test.context
+        .set_account(&auction, &AccountSharedData::from(account));
+
+    // Bids don't move the end time of an old auction.
+    test.warp(90).await;
+    test.bid(0, 1000).await.unwrap();
+    assert_eq!(test.auction_data().await.ended_at, Some(start + 100));
+
+    // It stays open for the hardcoded gap after the last bid instead.
+    test.warp(500).await;
+    test.bid(1, 2000).await.unwrap();
+    let auction = test.auction_data().await;
+    assert_eq!(auction.state, AuctionState::Started);
+    assert_eq!(auction.ended_at, Some(start + 100));
+    assert!(auction.is_winner(&test.bidders[1].pot).is_some());
+
+    test.warp(601).await;
+    test.bid(2, 3000).await.unwrap();
+    let auction = test.auction_data().await;
+    assert_eq!(auction.state, AuctionState::Ended);
+    assert!(auction.is_winner(&test.bidders[2].pot).is_none());
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_bid_increment() {
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.bid_increment = BidIncrement::Tiered(vec![
+        BidIncrementTier {
+            from: 100,
+            increment: 10,
+        },
+        BidIncrementTier {
+            from: 1000,
+            increment: 500,
+        },
+    ]);
+    let err = test.create(args.clone()).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidBidIncrement);
+
+    args.bid_increment = BidIncrement::Tiered(vec![
+        BidIncrementTier {
+            from: 0,
+            increment: 10,
+        },
+        BidIncrementTier {
+            from: 1000,
+            increment: 500,
+        },
+    ]);
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+
+    // Below 1000 a bid has to beat the top bid by 10, from 1000 on by 500.
+    test.bid(0, 995).await.unwrap();
+    let err = test.bid(1, 1004).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::BidTooSmall);
+    test.bid(1, 1005).await.unwrap();
+
+    let err = test.bid(2, 1504).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::BidTooSmall);
+    test.bid(2, 1505).await.unwrap();
+
+    let auction = test.auction_data().await;
+    assert!(auction.is_winner(&test.bidders[2].pot).is_some());
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_bid_history() {
+    let mut test = AuctionTest::new().await;
+    test.create(test.args()).await.unwrap();
+    let payer = test.context.payer.pubkey();
+    let create_history = |capacity| {
+        instruction::create_bid_history_instruction(
+            test.program_id,
+            payer,
+            payer,
+            CreateBidHistoryArgs {
+                resource: test.resource,
+                capacity,
+            },
+        )
+    };
+    let too_large = create_history(10_000);
+    let create = create_history(2);
+    let err = test.process(&[too_large], &[]).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidBidHistoryCapacity);
+    test.process(&[create], &[]).await.unwrap();
+    test.start().await.unwrap();
+
+    // Bids have to be recorded once the auction keeps a history.
+    let err = test.bid(0, 1000).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::BidHistoryMissing);
+
+    let history = instruction::find_bid_history_address(&test.program_id, &test.auction);
+    for (bidder, amount) in [(0, 1000), (1, 2000)] {
+        let mut instruction = test.bid_instruction(bidder, amount);
+        instruction.accounts.push(AccountMeta::new(history, false));
+        test.process_as(bidder, instruction).await.unwrap();
+    }
+    let cancel = instruction::cancel_bid_instruction(
+        test.program_id,
+        test.bidders[0].wallet.pubkey(),
+        test.bidders[0].token,
+        test.bidders[0].pot_token,
+        test.mint,
+        Some(history),
+        CancelBidArgs {
+            resource: test.resource,
+        },
+    );
+    test.process_as(0, cancel).await.unwrap();
+
+    // The buffer only holds the last two events.
+    let data = helpers::get_account(&mut test.context.banks_client, &history)
+        .await
+        .data;
+    let events = BidHistory::events(&data).unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].bidder, test.bidders[1].wallet.pubkey());
+    assert_eq!(events[0].amount, 2000);
+    assert_eq!(events[0].kind, BidEventKind::Placed);
+    assert_eq!(events[1].bidder, test.bidders[0].wallet.pubkey());
+    assert_eq!(events[1].amount, 1000);
+    assert_eq!(events[1].kind, BidEventKind::Cancelled);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_scheduled_start() {
+    let mut test = AuctionTest::new().await;
+    let start_at = test.now().await + 100;
+    let mut args = test.args();
+    args.start_at = Some(start_at);
+    args.end_auction_at = Some(50);
+    test.create(args).await.unwrap();
+
+    let crank = instruction::start_scheduled_auction_instruction(
+        test.program_id,
+        StartAuctionArgs {
+            resource: test.resource,
+        },
+    );
+    let err = test.process(&[crank.clone()], &[]).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::ScheduledStartNotReached);
+    let err = test.bid(0, 1000).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidState);
+
+    // Anyone can start it once the time comes, and it runs from its scheduled start.
+    test.warp(110).await;
+    test.process(&[crank], &[]).await.unwrap();
+    let auction = test.auction_data().await;
+    assert_eq!(auction.state, AuctionState::Started);
+    assert_eq!(auction.ended_at, Some(start_at + 50));
+
+    test.bid(0, 1000).await.unwrap();
+    assert!(test
+        .auction_data()
+        .await
+        .is_winner(&test.bidders[0].pot)
+        .is_some());
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_scheduled_start_on_first_bid() {
+    let mut test = AuctionTest::new().await;
+    let start_at = test.now().await + 100;
+    let mut args = test.args();
+    args.start_at = Some(start_at);
+    args.end_auction_at = Some(50);
+    test.create(args).await.unwrap();
+
+    // Without a crank, the first bid past the start time starts the auction.
+    test.warp(110).await;
+    test.bid(0, 1000).await.unwrap();
+    let auction = test.auction_data().await;
+    assert_eq!(auction.state, AuctionState::Started);
+    assert_eq!(auction.ended_at, Some(start_at + 50));
+    assert!(auction.is_winner(&test.bidders[0].pot).is_some());
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_settle_auction() {
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.end_auction_at = Some(100);
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+    test.bid(0, 1000).await.unwrap();
+
+    let settle = instruction::settle_auction_instruction(
+        test.program_id,
+        SettleAuctionArgs {
+            resource: test.resource,
+        },
+    );
+    let err = test.process(&[settle.clone()], &[]).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::AuctionNotEnded);
+
+    // Once the end time passes, anyone can move the auction to its ended state.
+    test.warp(101).await;
+    test.process(&[settle], &[]).await.unwrap();
+    let auction = test.auction_data().await;
+    assert_eq!(auction.state, AuctionState::Ended);
+    assert!(auction.is_winner(&test.bidders[0].pot).is_some());
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_settle_auction_with_blinded_floor() {
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.end_auction_at = Some(100);
+    args.price_floor =
+        PriceFloor::BlindedPrice(hashv(&[&500u64.to_be_bytes(), &7u64.to_be_bytes()]));
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+    let start = test.now().await;
+    test.bid(0, 1000).await.unwrap();
+    test.warp(101).await;
+
+    // A blinded floor has to be revealed by the authority through EndAuction.
+    let settle = instruction::settle_auction_instruction(
+        test.program_id,
+        SettleAuctionArgs {
+            resource: test.resource,
+        },
+    );
+    let err = test.process(&[settle], &[]).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::MustReveal);
+
+    // Ending it late keeps the end time it lapsed at.
+    test.end(Some((500, 7))).await.unwrap();
+    let auction = test.auction_data().await;
+    assert_eq!(auction.state, AuctionState::Ended);
+    assert_eq!(auction.ended_at, Some(start + 100));
+}
+
+async fn multi_unit_bid(
+    test: &mut AuctionTest,
+    bidder: usize,
+    amount: u64,
+    quantity: u64,
+) -> Result<(), TransportError> {
+    let wallet = test.bidders[bidder].wallet.pubkey();
+    let instruction = instruction::place_multi_unit_bid_instruction(
+        test.program_id,
+        wallet,
+        test.bidders[bidder].token,
+        test.bidders[bidder].pot_token,
+        test.mint,
+        wallet,
+        test.context.payer.pubkey(),
+        None,
+        None,
+        None,
+        PlaceMultiUnitBidArgs {
+            amount,
+            quantity,
+            resource: test.resource,
+        },
+    );
+    test.process_as(bidder, instruction).await
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_multi_unit_uniform_price() {
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.winners = WinnerLimit::Capped(3);
+    args.multi_unit = true;
+    args.settlement = SettlementMode::UniformPrice(ClearingRule::HighestLosingBid);
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+
+    let err = multi_unit_bid(&mut test, 0, 1000, 4).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidBidQuantity);
+
+    // The second bid takes one of the first bidder's two units.
+    multi_unit_bid(&mut test, 0, 1000, 2).await.unwrap();
+    multi_unit_bid(&mut test, 1, 1500, 2).await.unwrap();
+    assert_eq!(test.pot_balance(0).await, 2000);
+    assert_eq!(test.pot_balance(1).await, 3000);
+    test.end(None).await.unwrap();
+    test.warp(1).await;
+
+    // Every unit sells at the highest losing bid, the rest of each pot is refunded.
+    let payer = test.context.payer.pubkey();
+    let seller = test.create_token_account(&payer).await;
+    for bidder in 0..2 {
+        let refund = Some(test.bidders[bidder].token);
+        test.claim(bidder, &seller, refund, None).await.unwrap();
+    }
+    assert_eq!(test.token_balance(&seller).await, 3000);
+    assert_eq!(test.bidder_balance(0).await, 10_000_000 - 1000);
+    assert_eq!(test.bidder_balance(1).await, 10_000_000 - 2000);
+    assert_eq!(test.auction_data().await.clearing_price, Some(1000));
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_native_bids() {
+    let mut test = AuctionTest::new_native().await;
+    let mut args = test.args();
+    args.native_bids = true;
+
+    let mut not_native = args.clone();
+    not_native.token_mint = Pubkey::new_unique();
+    let err = test.create(not_native).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidNativeBidConfig);
+
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+
+    // Bids are held as lamports by the bidder pots themselves.
+    test.bid(0, 2_000_000).await.unwrap();
+    test.bid(1, 3_000_000).await.unwrap();
+    assert_eq!(test.pot_balance(0).await, 2_000_000);
+    assert_eq!(test.pot_balance(1).await, 3_000_000);
+    assert_eq!(test.bidder_balance(1).await, 1_000_000_000 - 3_000_000);
+
+    // The outbid bidder gets their lamports back in their wallet.
+    let cancel = instruction::cancel_bid_instruction(
+        test.program_id,
+        test.bidders[0].wallet.pubkey(),
+        test.bidders[0].token,
+        test.bidders[0].pot_token,
+        test.mint,
+        None,
+        CancelBidArgs {
+            resource: test.resource,
+        },
+    );
+    test.process_as(0, cancel).await.unwrap();
+    assert_eq!(test.bidder_balance(0).await, 1_000_000_000);
+    assert_eq!(test.pot_balance(0).await, 0);
+
+    // The winner is paid out in lamports to a wallet.
+    test.end(None).await.unwrap();
+    test.warp(1).await;
+    let seller = Pubkey::new_unique();
+    test.claim(1, &seller, None, None).await.unwrap();
+    assert_eq!(test.lamports(&seller).await, 3_000_000);
+    assert_eq!(test.pot_balance(1).await, 0);
+}
+
+/// Creates a fee config with the payer as its admin, paying fees to a new wallet that holds a
+/// token account of the auction mint. Claims are then sent with these fee accounts.
+async fn create_fee_config(test: &mut AuctionTest, schedule: FeeSchedule) -> Pubkey {
+    let payer = test.context.payer.pubkey();
+    let recipient = Pubkey::new_unique();
+    let recipient_token = test.create_token_account(&recipient).await;
+    let create = instruction::create_fee_config_instruction(
+        test.program_id,
+        payer,
+        payer,
+        CreateFeeConfigArgs {
+            recipient,
+            schedule,
+        },
+    );
+    test.process(&[create], &[]).await.unwrap();
+    let fee_config = instruction::find_fee_config_address(&test.program_id, &payer);
+    test.fee_accounts = (recipient_token, recipient, fee_config);
+    fee_config
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_fee_config() {
+    let mut test = AuctionTest::new().await;
+    let payer = test.context.payer.pubkey();
+    let invalid = instruction::create_fee_config_instruction(
+        test.program_id,
+        payer,
+        payer,
+        CreateFeeConfigArgs {
+            recipient: payer,
+            schedule: FeeSchedule {
+                fee_basis_points: 10001,
+                referral_share_basis_points: 0,
+                max_fee: None,
+            },
+        },
+    );
+    let err = test.process(&[invalid], &[]).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidFeeSchedule);
+
+    let fee_config = create_fee_config(
+        &mut test,
+        FeeSchedule {
+            fee_basis_points: 100,
+            referral_share_basis_points: 0,
+            max_fee: None,
+        },
+    )
+    .await;
+
+    // Only the admin can change the config.
+    let (recipient_token, recipient, _) = test.fee_accounts;
+    let update = |admin| {
+        instruction::update_fee_config_instruction(
+            test.program_id,
+            admin,
+            fee_config,
+            UpdateFeeConfigArgs {
+                admin: payer,
+                recipient,
+                schedule: FeeSchedule {
+                    fee_basis_points: 500,
+                    referral_share_basis_points: 0,
+                    max_fee: None,
+                },
+            },
+        )
+    };
+    let by_bidder = update(test.bidders[0].wallet.pubkey());
+    let by_admin = update(payer);
+    let err = test.process_as(0, by_bidder).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidAuthority);
+    test.process(&[by_admin], &[]).await.unwrap();
+
+    let mut args = test.args();
+    args.fee_config = Some(fee_config);
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+    test.bid(0, 10000).await.unwrap();
+    test.end(None).await.unwrap();
+    test.warp(1).await;
+
+    // Fees can only go to the recipient of the config.
+    let seller = test.create_token_account(&payer).await;
+    test.fee_accounts.1 = payer;
+    let err = test.claim(0, &seller, None, None).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::FeeConfigMismatch);
+
+    test.fee_accounts.1 = recipient;
+    test.claim(0, &seller, None, None).await.unwrap();
+    assert_eq!(test.token_balance(&seller).await, 9500);
+    assert_eq!(test.token_balance(&recipient_token).await, 500);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_fee_schedule_fixed_at_creation() {
+    let mut test = AuctionTest::new().await;
+    let payer = test.context.payer.pubkey();
+    let fee_config = create_fee_config(
+        &mut test,
+        FeeSchedule {
+            fee_basis_points: 1000,
+            referral_share_basis_points: 0,
+            max_fee: Some(300),
+        },
+    )
+    .await;
+    let mut args = test.args();
+    args.fee_config = Some(fee_config);
+    test.create(args).await.unwrap();
+    assert_eq!(
+        test.auction_data().await.fee_schedule.unwrap().max_fee,
+        Some(300)
+    );
+
+    // Raising the fees once bids are in does not apply to this auction.
+    let (recipient_token, recipient, _) = test.fee_accounts;
+    let raise = instruction::update_fee_config_instruction(
+        test.program_id,
+        payer,
+        fee_config,
+        UpdateFeeConfigArgs {
+            admin: payer,
+            recipient,
+            schedule: FeeSchedule {
+                fee_basis_points: 10000,
+                referral_share_basis_points: 0,
+                max_fee: None,
+            },
+        },
+    );
+    test.start().await.unwrap();
+    test.bid(0, 10000).await.unwrap();
+    test.process(&[raise], &[]).await.unwrap();
+    test.end(None).await.unwrap();
+    test.warp(1).await;
+
+    let seller = test.create_token_account(&payer).await;
+    test.claim(0, &seller, None, None).await.unwrap();
+    assert_eq!(test.token_balance(&seller).await, 9700);
+    assert_eq!(test.token_balance(&recipient_token).await, 300);
+}
+
+/// Places a bid naming `referrer`'s registry.
+async fn referred_bid(
+    test: &mut AuctionTest,
+    bidder: usize,
+    amount: u64,
+    referrer: Pubkey,
+) -> Result<(), TransportError> {
+    let bidder_accounts = &test.bidders[bidder];
+    let place = instruction::place_bid_instruction(
+        test.program_id,
+        bidder_accounts.wallet.pubkey(),
+        bidder_accounts.token,
+        bidder_accounts.pot_token,
+        test.mint,
+        bidder_accounts.wallet.pubkey(),
+        test.context.payer.pubkey(),
+        None,
+        Some(referrer),
+        None,
+        PlaceBidArgs {
+            amount,
+            resource: test.resource,
+        },
+    );
+    test.process_as(bidder, place).await
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_referral() {
+    let mut test = AuctionTest::new().await;
+    let payer = test.context.payer.pubkey();
+    let fee_config = create_fee_config(
+        &mut test,
+        FeeSchedule {
+            fee_basis_points: 1000,
+            referral_share_basis_points: 5000,
+            max_fee: None,
+        },
+    )
+    .await;
+    let referrer = test.bidders[1].wallet.pubkey();
+    let register = instruction::register_referrer_instruction(test.program_id, referrer, payer);
+    test.process_as(1, register).await.unwrap();
+
+    let mut args = test.args();
+    args.fee_config = Some(fee_config);
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+
+    let err = referred_bid(&mut test, 1, 10000, referrer)
+        .await
+        .unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidReferralRegistry);
+
+    referred_bid(&mut test, 0, 10000, referrer).await.unwrap();
+    let registry = instruction::find_referral_registry_address(&test.program_id, &referrer);
+    let registry: ReferralRegistry = try_from_slice_unchecked(
+        &test
+            .context
+            .banks_client
+            .get_account(registry)
+            .await
+            .unwrap()
+            .unwrap()
+            .data,
+    )
+    .unwrap();
+    assert_eq!(registry.key, REFERRAL_REGISTRY_KEY);
+    assert_eq!(registry.referrer, referrer);
+    assert_eq!(registry.bids_referred, 1);
+    test.end(None).await.unwrap();
+    test.warp(1).await;
+
+    // Whoever claims the bid has to pay the referrer it was placed with.
+    let seller = test.create_token_account(&payer).await;
+    let err = test.claim(0, &seller, None, None).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::ReferrerMismatch);
+
+    let referrer_token = test.bidders[1].token;
+    let referrer_balance = test.token_balance(&referrer_token).await;
+    test.claim(0, &seller, None, Some(referrer_token))
+        .await
+        .unwrap();
+    assert_eq!(test.token_balance(&seller).await, 9000);
+    let recipient_token = test.fee_accounts.0;
+    assert_eq!(test.token_balance(&recipient_token).await, 500);
+    assert_eq!(
+        test.token_balance(&referrer_token).await,
+        referrer_balance + 500
+    );
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_rebid_drops_referrer() {
+    let mut test = AuctionTest::new().await;
+    let payer = test.context.payer.pubkey();
+    let fee_config = create_fee_config(
+        &mut test,
+        FeeSchedule {
+            fee_basis_points: 1000,
+            referral_share_basis_points: 5000,
+            max_fee: None,
+        },
+    )
+    .await;
+    let referrer = test.bidders[1].wallet.pubkey();
+    let register = instruction::register_referrer_instruction(test.program_id, referrer, payer);
+    test.process_as(1, register).await.unwrap();
+
+    let mut args = test.args();
+    args.fee_config = Some(fee_config);
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+
+    referred_bid(&mut test, 2, 5000, referrer).await.unwrap();
+    let pot = test.bidders[2].pot;
+    let data = helpers::get_account(&mut test.context.banks_client, &pot)
+        .await
+        .data;
+    assert_eq!(
+        BidderPot::from_slice(&data).unwrap().referrer,
+        Some(referrer)
+    );
+
+    // Once outbid the bidder cancels and bids again without the registry.
+    test.bid(0, 10000).await.unwrap();
+    let cancel = instruction::cancel_bid_instruction(
+        test.program_id,
+        test.bidders[2].wallet.pubkey(),
+        test.bidders[2].token,
+        test.bidders[2].pot_token,
+        test.mint,
+        None,
+        CancelBidArgs {
+            resource: test.resource,
+        },
+    );
+    test.process_as(2, cancel).await.unwrap();
+    test.bid(2, 20000).await.unwrap();
+    let data = helpers::get_account(&mut test.context.banks_client, &pot)
+        .await
+        .data;
+    assert_eq!(BidderPot::from_slice(&data).unwrap().referrer, None);
+    test.end(None).await.unwrap();
+    test.warp(1).await;
+
+    // The winning bid owes the referrer nothing, all fees go to the fee recipient.
+    let seller = test.create_token_account(&payer).await;
+    test.claim(2, &seller, None, None).await.unwrap();
+    assert_eq!(test.token_balance(&seller).await, 18000);
+    let recipient_token = test.fee_accounts.0;
+    assert_eq!(test.token_balance(&recipient_token).await, 2000);
+}
+
+async fn increase_bid(
+    test: &mut AuctionTest,
+    bidder: usize,
+    amount: u64,
+) -> Result<(), TransportError> {
+    let wallet = test.bidders[bidder].wallet.pubkey();
+    let instruction = instruction::increase_bid_instruction(
+        test.program_id,
+        wallet,
+        test.bidders[bidder].token,
+        test.bidders[bidder].pot_token,
+        test.mint,
+        wallet,
+        test.context.payer.pubkey(),
+        None,
+        None,
+        None,
+        PlaceBidArgs {
+            amount,
+            resource: test.resource,
+        },
+    );
+    test.process_as(bidder, instruction).await
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_increase_bid() {
+    let mut test = AuctionTest::new().await;
+    test.create(test.args()).await.unwrap();
+    test.start().await.unwrap();
+
+    let err = increase_bid(&mut test, 0, 1000).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::NoActiveBid);
+
+    // Raising a bid only charges what it adds.
+    test.bid(0, 1000).await.unwrap();
+    increase_bid(&mut test, 0, 1500).await.unwrap();
+    assert_eq!(test.pot_balance(0).await, 1500);
+    assert_eq!(test.bidder_balance(0).await, 10_000_000 - 1500);
+    let auction = test.auction_data().await;
+    assert_eq!(auction.bid_state.bids().len(), 1);
+    assert_eq!(auction.bid_state.bids()[0].1, 1500);
+
+    // Multi-unit bids can't be raised, they would lose their quantity.
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.winners = WinnerLimit::Capped(2);
+    args.multi_unit = true;
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+    multi_unit_bid(&mut test, 0, 1000, 2).await.unwrap();
+    let err = increase_bid(&mut test, 0, 1500).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::CannotIncreaseBid);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_raise_middle_bid() {
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.winners = WinnerLimit::Capped(3);
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+    test.bid(0, 1000).await.unwrap();
+    test.bid(1, 2000).await.unwrap();
+    test.bid(2, 3000).await.unwrap();
+
+    // A winner's raise only has to beat their own bid, not the top one.
+    increase_bid(&mut test, 0, 2500).await.unwrap();
+    let err = increase_bid(&mut test, 0, 2510).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::BidTooSmall);
+
+    // Once outbid, a raise has to beat the lowest winner it takes the place of.
+    test.bid(3, 4000).await.unwrap();
+    let err = increase_bid(&mut test, 1, 2550).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::BidTooSmall);
+    increase_bid(&mut test, 1, 2700).await.unwrap();
+
+    let bids: Vec<_> = test
+        .auction_data()
+        .await
+        .bid_state
+        .bids()
+        .iter()
+        .map(|bid| (bid.0, bid.1))
+        .collect();
+    assert_eq!(
+        bids,
+        vec![
+            (test.bidders[1].pot, 2700),
+            (test.bidders[2].pot, 3000),
+            (test.bidders[3].pot, 4000),
+        ]
+    );
+    assert_eq!(test.pot_balance(0).await, 2500);
+    assert_eq!(test.pot_balance(1).await, 2700);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_refund_losers() {
+    let mut test = AuctionTest::new().await;
+    test.create(test.args()).await.unwrap();
+    test.start().await.unwrap();
+    test.bid(0, 1000).await.unwrap();
+    test.bid(1, 2000).await.unwrap();
+
+    let refunds: Vec<_> = test
+        .bidders
+        .iter()
+        .take(2)
+        .map(|bidder| (bidder.wallet.pubkey(), bidder.pot_token, bidder.token))
+        .collect();
+    let refund = instruction::refund_losers_instruction(
+        test.program_id,
+        test.mint,
+        &refunds,
+        None,
+        RefundLosersArgs {
+            resource: test.resource,
+        },
+    );
+    let err = test.process(&[refund.clone()], &[]).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::AuctionNotEnded);
+    test.end(None).await.unwrap();
+    test.warp(1).await;
+
+    // The loser is refunded and the winner skipped, passing them again refunds nothing twice.
+    test.process(&[refund.clone()], &[]).await.unwrap();
+    test.warp(1).await;
+    test.process(&[refund], &[]).await.unwrap();
+    assert_eq!(test.bidder_balance(0).await, 10_000_000);
+    assert_eq!(test.pot_balance(0).await, 0);
+    assert_eq!(test.pot_balance(1).await, 2000);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_reserve_not_met() {
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.price_floor =
+        PriceFloor::BlindedPrice(hashv(&[&5000u64.to_be_bytes(), &7u64.to_be_bytes()]));
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+    test.bid(0, 1000).await.unwrap();
+    test.end(Some((5000, 7))).await.unwrap();
+    assert_eq!(test.auction_data().await.state, AuctionState::ReserveNotMet);
+    test.warp(1).await;
+
+    // Nobody won, so the top bid can't be claimed and goes back to its bidder.
+    let payer = test.context.payer.pubkey();
+    let seller = test.create_token_account(&payer).await;
+    let err = test.claim(0, &seller, None, None).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidState);
+
+    let cancel = instruction::cancel_bid_instruction(
+        test.program_id,
+        test.bidders[0].wallet.pubkey(),
+        test.bidders[0].token,
+        test.bidders[0].pot_token,
+        test.mint,
+        None,
+        CancelBidArgs {
+            resource: test.resource,
+        },
+    );
+    test.process_as(0, cancel).await.unwrap();
+    assert_eq!(test.bidder_balance(0).await, 10_000_000);
+    assert_eq!(test.token_balance(&seller).await, 0);
+}
+
+/// Places a bid passing `gate_pass` to show the bidder may bid.
+async fn gated_bid(
+    test: &mut AuctionTest,
+    bidder: usize,
+    amount: u64,
+    gate_pass: Pubkey,
+) -> Result<(), TransportError> {
+    let bidder_accounts = &test.bidders[bidder];
+    let place = instruction::place_bid_instruction(
+        test.program_id,
+        bidder_accounts.wallet.pubkey(),
+        bidder_accounts.token,
+        bidder_accounts.pot_token,
+        test.mint,
+        bidder_accounts.wallet.pubkey(),
+        test.context.payer.pubkey(),
+        Some(gate_pass),
+        None,
+        None,
+        PlaceBidArgs {
+            amount,
+            resource: test.resource,
+        },
+    );
+    test.process_as(bidder, place).await
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_allowlist() {
+    let mut test = AuctionTest::new().await;
+    let leaves: Vec<_> = test
+        .bidders
+        .iter()
+        .take(2)
+        .map(|bidder| hashv(&[bidder.wallet.pubkey().as_ref()]))
+        .collect();
+    let (low, high) = if leaves[0] <= leaves[1] {
+        (leaves[0], leaves[1])
+    } else {
+        (leaves[1], leaves[0])
+    };
+    let root = hashv(&[low.as_ref(), high.as_ref()]);
+    assert!(BidderGate::verify_proof(
+        &root,
+        &test.bidders[0].wallet.pubkey(),
+        &[leaves[1]]
+    ));
+
+    let mut args = test.args();
+    args.gate = Some(BidderGate::Allowlist(root));
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+
+    let err = test.bid(0, 1000).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::BidderNotAllowed);
+
+    // Only wallets in the tree can join.
+    let payer = test.context.payer.pubkey();
+    let join = |bidder: Pubkey| {
+        instruction::join_allowlist_instruction(
+            test.program_id,
+            bidder,
+            payer,
+            JoinAllowlistArgs {
+                resource: test.resource,
+                proof: vec![leaves[1]],
+            },
+        )
+    };
+    let outsider = join(test.bidders[2].wallet.pubkey());
+    let member = join(test.bidders[0].wallet.pubkey());
+    let err = test.process_as(2, outsider).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidAllowlistProof);
+    test.process_as(0, member).await.unwrap();
+
+    let pass = instruction::find_allowlist_pass_address(
+        &test.program_id,
+        &test.auction,
+        &test.bidders[0].wallet.pubkey(),
+    );
+    let err = gated_bid(&mut test, 2, 1000, pass).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::BidderNotAllowed);
+    gated_bid(&mut test, 0, 1000, pass).await.unwrap();
+    assert_eq!(test.pot_balance(0).await, 1000);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_token_gate() {
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.gate = Some(BidderGate::TokenHolder(test.mint));
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+
+    // An empty account of the gate mint shows nothing.
+    let wallet = test.bidders[1].wallet.pubkey();
+    let empty = test.create_token_account(&wallet).await;
+    let err = gated_bid(&mut test, 1, 1000, empty).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::BidderNotAllowed);
+
+    let holding = test.bidders[0].token;
+    gated_bid(&mut test, 0, 1000, holding).await.unwrap();
+    assert_eq!(test.pot_balance(0).await, 1000);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_buy_now_with_several_winners() {
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.winners = WinnerLimit::Capped(2);
+    args.max_price = Some(5000);
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+
+    // One buy now bid leaves the second slot open.
+    test.bid(0, 1000).await.unwrap();
+    test.bid(1, 6000).await.unwrap();
+    assert_eq!(test.pot_balance(1).await, 5000);
+    assert_eq!(test.auction_data().await.state, AuctionState::Started);
+
+    // The second one ends the auction, and the bid below the price loses.
+    test.bid(2, 5000).await.unwrap();
+    let auction = test.auction_data().await;
+    assert_eq!(auction.state, AuctionState::Ended);
+    assert!(auction.is_winner(&test.bidders[0].pot).is_none());
+    assert!(auction.is_winner(&test.bidders[1].pot).is_some());
+    assert!(auction.is_winner(&test.bidders[2].pot).is_some());
+}
+
+/// Turns the auction into one created before the buy now price moved into AuctionData: cut back to
+/// its old layout, in `state`, with its price in a separate buy now account.
+async fn legacy_buy_now(test: &mut AuctionTest, state: AuctionState, max_price: u64) -> Pubkey {
+    let auction = test.auction;
+    let mut account = helpers::get_account(&mut test.context.banks_client, &auction).await;
+    let legacy_len = account.data.len() - AUCTION_DATA_EXTENSION_SIZE;
+    let mut data = AuctionData {
+        state,
+        ..AuctionData::from_slice(&account.data).unwrap()
+    }
+    .try_to_vec()
+    .unwrap();
+    data.resize(legacy_len, 0);
+    account.data = data;
+    test.context
+        .set_account(&auction, &AccountSharedData::from(account));
+
+    let buy_now = instruction::find_buy_now_address(&test.program_id, &test.resource);
+    let rent = test.context.banks_client.get_rent().await.unwrap();
+    let data = BuyNowData { max_price }.try_to_vec().unwrap();
+    let account = Account {
+        lamports: rent.minimum_balance(data.len()),
+        data,
+        owner: test.program_id,
+        executable: false,
+        rent_epoch: 0,
+    };
+    test.context
+        .set_account(&buy_now, &AccountSharedData::from(account));
+    buy_now
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_legacy_buy_now_auction() {
+    let mut test = AuctionTest::new().await;
+    test.create(test.args()).await.unwrap();
+    test.start().await.unwrap();
+    let buy_now = legacy_buy_now(&mut test, AuctionState::BuyNowStarted, 5000).await;
+    let buy_now_rent = test.lamports(&buy_now).await;
+
+    // Bids read the price from the buy now account, and have to pass it.
+    let err = test.bid(0, 1000).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+    legacy_buy_now_bids(&mut test, buy_now, &[(0, 1000), (1, 6000)]).await;
+    assert_eq!(test.pot_balance(1).await, 5000);
+    let auction = test.auction_data().await;
+    assert_eq!(auction.state, AuctionState::BuyNowEnded);
+    assert_eq!(auction.buy_now_price, None);
+    assert!(auction.is_winner(&test.bidders[1].pot).is_some());
+    test.warp(1).await;
+
+    // Without a fee config the buy now account can't be swept to just any recipient.
+    let fee_recipient = Pubkey::new_unique();
+    test.fee_accounts.1 = fee_recipient;
+    let payer = test.context.payer.pubkey();
+    let seller = test.create_token_account(&payer).await;
+    let mut claim = test.claim_instruction(1, &seller, None, None);
+    claim.accounts.push(AccountMeta::new(buy_now, false));
+    let err = test.process(&[claim], &[]).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidAuthority);
+    assert_eq!(test.lamports(&fee_recipient).await, 0);
+
+    // Claiming the sale sweeps it to the auction authority instead, who also pays the fee.
+    test.fee_accounts.1 = payer;
+    let authority_lamports = test.lamports(&payer).await;
+    let mut claim = test.claim_instruction(1, &seller, None, None);
+    claim.accounts.push(AccountMeta::new(buy_now, false));
+    test.process(&[claim], &[]).await.unwrap();
+    assert_eq!(test.token_balance(&seller).await, 5000);
+    assert_eq!(
+        test.lamports(&payer).await + 5000,
+        authority_lamports + buy_now_rent
+    );
+    assert!(test
+        .context
+        .banks_client
+        .get_account(buy_now)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_update_legacy_buy_now_auction() {
+    let mut test = AuctionTest::new().await;
+    test.create(test.args()).await.unwrap();
+    let buy_now = legacy_buy_now(&mut test, AuctionState::BuyNowCreated, 5000).await;
+
+    let payer = test.context.payer.pubkey();
+    let update = |buy_now| {
+        instruction::update_auction_instruction(
+            test.program_id,
+            payer,
+            buy_now,
+            UpdateAuctionArgs {
+                resource: test.resource,
+                end_auction_at: None,
+                end_auction_gap: None,
+                price_floor: PriceFloor::None([0; 32]),
+                max_price: None,
+            },
+        )
+    };
+    let without_buy_now = update(None);
+    let with_buy_now = update(Some(buy_now));
+    let err = test.process(&[without_buy_now], &[]).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+
+    // The update closes the buy now account, the auction then runs as a regular one.
+    test.process(&[with_buy_now], &[]).await.unwrap();
+    assert_eq!(test.auction_data().await.state, AuctionState::Created);
+    assert!(test
+        .context
+        .banks_client
+        .get_account(buy_now)
+        .await
+        .unwrap()
+        .is_none());
+    test.start().await.unwrap();
+    test.bid(0, 1000).await.unwrap();
+}
+
+/// Places each `(bidder, amount)` bid passing the legacy buy now account.
+async fn legacy_buy_now_bids(test: &mut AuctionTest, buy_now: Pubkey, bids: &[(usize, u64)]) {
+    for (bidder, amount) in bids.iter().copied() {
+        let mut place = test.bid_instruction(bidder, amount);
+        place
+            .accounts
+            .push(AccountMeta::new_readonly(buy_now, false));
+        test.process_as(bidder, place).await.unwrap();
+    }
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_legacy_buy_now_single_purchase() {
+    // A legacy buy now account sells once, even with winner slots left over.
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.winners = WinnerLimit::Capped(2);
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+    let buy_now = legacy_buy_now(&mut test, AuctionState::BuyNowStarted, 5000).await;
+    legacy_buy_now_bids(&mut test, buy_now, &[(0, 1000), (1, 5000)]).await;
+    let auction = test.auction_data().await;
+    assert_eq!(auction.state, AuctionState::BuyNowEnded);
+    assert!(auction.is_winner(&test.bidders[0].pot).is_none());
+    assert!(auction.is_winner(&test.bidders[1].pot).is_some());
+
+    // And ends an open edition on its first purchase as well.
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.winners = WinnerLimit::Unlimited(0);
+    test.create(args).await.unwrap();
+    test.start().await.unwrap();
+    let buy_now = legacy_buy_now(&mut test, AuctionState::BuyNowStarted, 5000).await;
+    legacy_buy_now_bids(&mut test, buy_now, &[(0, 1000)]).await;
+    assert_eq!(test.auction_data().await.state, AuctionState::BuyNowStarted);
+    legacy_buy_now_bids(&mut test, buy_now, &[(1, 6000)]).await;
+    assert_eq!(test.pot_balance(1).await, 5000);
+    assert_eq!(test.auction_data().await.state, AuctionState::BuyNowEnded);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_open_edition_buy_now() {
+    // Open editions have no winner slots to sell out, so they can't take a buy now price.
+    let mut test = AuctionTest::new().await;
+    let mut args = test.args();
+    args.winners = WinnerLimit::Unlimited(0);
+    args.max_price = Some(5000);
+    let err = test.create(args).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidBuyNowConfig);
+
+    // Nor be given one later, they only end by time.
+    let mut args = test.args();
+    args.winners = WinnerLimit::Unlimited(0);
+    test.create(args).await.unwrap();
+    let payer = test.context.payer.pubkey();
+    let update = instruction::update_auction_instruction(
+        test.program_id,
+        payer,
+        None,
+        UpdateAuctionArgs {
+            resource: test.resource,
+            end_auction_at: None,
+            end_auction_gap: None,
+            price_floor: PriceFloor::None([0; 32]),
+            max_price: Some(5000),
+        },
+    );
+    let err = test.process(&[update], &[]).await.unwrap_err();
+    assert_eq!(auction_error(err), AuctionError::InvalidBuyNowConfig);
+}
@@ -266,6 +266,18 @@ pub enum MetaplexError {
     /// Store given does not match store on auction manager!
     #[error("Store given does not match store on auction manager!")]
     AuctionManagerStoreMismatch,
+
+    /// The auction's reserve was not met, it has no winners!
+    #[error("The auction's reserve was not met, it has no winners!")]
+    AuctionReserveNotMet,
+
+    /// Prizes only go back to the authority when the auction's reserve was not met!
+    #[error("Prizes only go back to the authority when the auction's reserve was not met!")]
+    AuctionHasWinners,
+
+    /// An auction manager can't have more winning configs than MAX_WINNERS!
+    #[error("An auction manager can't have more winning configs than MAX_WINNERS!")]
+    TooManyWinningConfigs,
 }
 
 impl PrintProgramError for MetaplexError {
@@ -177,6 +177,7 @@ pub enum MetaplexInstruction {
     StartAuction,
 
     /// If the auction manager is in a Disbursing or Finished state, then this means Auction must be in Ended state.
+    /// An auction whose reserve was not met has no winners, so none of its bids can be claimed.
     /// Then this end point can be used as a signed proxy to use auction manager's authority over the auction to claim bid funds
     /// into the accept payment account on the auction manager for a given bid. Auction has no opinions on how bids are redeemed,
     /// only that they exist, have been paid, and have a winning place. It is up to the implementer of the auction to determine redemption,
@@ -198,6 +199,10 @@ pub enum MetaplexInstruction {
     ///   9. `[]` Auction program
     ///   10. `[]` Clock sysvar
     ///   11. `[]` Token program
+    ///   12. `[writable]` Fee recipient token account, optional when the auction has no fee config
+    ///   13. `[writable]` Fee recipient wallet, optional when the auction has no fee config
+    ///   14. `[]` The fee config of the auction, optional when it has none
+    ///   15. `[writable]` The referrer token account, required when the bid was placed with a referrer
     ClaimBid,
 
     /// At any time, the auction manager authority may empty whatever funds are in the accept payment account
@@ -256,6 +261,30 @@ pub enum MetaplexInstruction {
     ///   12. `[]` Token program
     ///   13. `[]` Token metadata program
     ValidateOpenEdition,
+
+    /// If the auction ended without meeting its reserve, nobody won it and the auction manager authority
+    /// can take back the prizes in a safety deposit box instead. Every token left in the box is returned,
+    /// and the winning configs using it are marked claimed.
+    ///
+    ///   0. `[writable]` Auction manager
+    ///   1. `[writable]` Safety deposit token storage account
+    ///   2. `[writable]` Destination account of the same mint as the safety deposit box
+    ///   3. `[writable]` Safety deposit box account
+    ///   4. `[writable]` Vault account
+    ///   5. `[writable]` Fraction mint of the vault
+    ///   6. `[]` Auction
+    ///   7. `[signer]` Authority OF the auction manager
+    ///   8. `[]` Token program
+    ///   9. `[]` Token Vault program
+    ///   10. `[]` Store
+    ///   11. `[]` Rent sysvar
+    ///   12. `[]` PDA-based Transfer authority to move the tokens from the store to the destination seed ['vault', program_id]
+    ///        but please note that this is a PDA relative to the Token Vault program, with the 'vault' prefix
+    ///   13. `[writable]` Master Metadata account (pda of ['metadata', program id, master mint id]), master edition prizes only
+    ///   14. `[]` Original authority lookup pda of ['metaplex', auction key, metadata key], master edition prizes only
+    ///   15. `[]` Original authority of the Master Metadata, which it goes back to, master edition prizes only
+    ///   16. `[]` Token metadata program, master edition prizes only
+    ReturnUnsoldItem,
 }
 
 /// Creates an InitAuctionManager instruction
@@ -558,6 +587,51 @@ pub fn create_start_auction_instruction(
     }
 }
 
+/// Creates an ReturnUnsoldItem instruction, `master_edition` holds the master metadata, original
+/// authority lookup and original authority of a master edition prize.
+#[allow(clippy::too_many_arguments)]
+pub fn create_return_unsold_item_instruction(
+    program_id: Pubkey,
+    auction_manager: Pubkey,
+    safety_deposit_token_store: Pubkey,
+    destination: Pubkey,
+    safety_deposit_box: Pubkey,
+    vault: Pubkey,
+    fraction_mint: Pubkey,
+    auction: Pubkey,
+    auction_manager_authority: Pubkey,
+    store: Pubkey,
+    transfer_authority: Pubkey,
+    master_edition: Option<(Pubkey, Pubkey, Pubkey)>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(auction_manager, false),
+        AccountMeta::new(safety_deposit_token_store, false),
+        AccountMeta::new(destination, false),
+        AccountMeta::new(safety_deposit_box, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new(fraction_mint, false),
+        AccountMeta::new_readonly(auction, false),
+        AccountMeta::new_readonly(auction_manager_authority, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_token_vault::id(), false),
+        AccountMeta::new_readonly(store, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(transfer_authority, false),
+    ];
+    if let Some((metadata, original_authority_lookup, original_authority)) = master_edition {
+        accounts.push(AccountMeta::new(metadata, false));
+        accounts.push(AccountMeta::new_readonly(original_authority_lookup, false));
+        accounts.push(AccountMeta::new_readonly(original_authority, false));
+        accounts.push(AccountMeta::new_readonly(spl_token_metadata::id(), false));
+    }
+    Instruction {
+        program_id,
+        accounts,
+        data: MetaplexInstruction::ReturnUnsoldItem.try_to_vec().unwrap(),
+    }
+}
+
 /// Creates an SetStore instruction
 pub fn create_set_store_instruction(
     program_id: Pubkey,
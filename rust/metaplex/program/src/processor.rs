@@ -14,6 +14,7 @@ use {
     empty_payment_account::process_empty_payment_account,
     init_auction_manager::process_init_auction_manager,
     redeem_open_edition_bid::process_redeem_open_edition_bid,
+    return_unsold_item::process_return_unsold_item,
     set_store::process_set_store,
     set_whitelisted_creator::process_set_whitelisted_creator,
     solana_program::{
@@ -31,6 +32,7 @@ pub mod claim_bid;
 pub mod empty_payment_account;
 pub mod init_auction_manager;
 pub mod redeem_open_edition_bid;
+pub mod return_unsold_item;
 pub mod set_store;
 pub mod set_whitelisted_creator;
 pub mod start_auction;
@@ -88,6 +90,10 @@ pub fn process_instruction<'a>(
             msg!("Instruction: Validate Open Edition");
             process_validate_open_edition(program_id, accounts)
         }
+        MetaplexInstruction::ReturnUnsoldItem => {
+            msg!("Instruction: Return Unsold Item");
+            process_return_unsold_item(program_id, accounts)
+        }
     }
 }
 
@@ -2,7 +2,7 @@ use {
     crate::{
         error::MetaplexError,
         state::{AuctionManager, AuctionManagerStatus, Store, PREFIX},
-        utils::{assert_derivation, assert_owned_by},
+        utils::{assert_auction_has_winners, assert_derivation, assert_owned_by},
     },
     borsh::BorshSerialize,
     solana_program::{
@@ -14,7 +14,7 @@ use {
     },
     spl_auction::{
         instruction::claim_bid_instruction,
-        processor::{claim_bid::ClaimBidArgs, AuctionData, AuctionState},
+        processor::{claim_bid::ClaimBidArgs, AuctionData},
     },
 };
 
@@ -30,9 +30,31 @@ pub fn issue_claim_bid<'a>(
     token_mint: AccountInfo<'a>,
     clock: AccountInfo<'a>,
     token_program: AccountInfo<'a>,
+    fee_recipient_token: AccountInfo<'a>,
+    fee_recipient: AccountInfo<'a>,
+    fee_config: AccountInfo<'a>,
+    referrer: Option<AccountInfo<'a>>,
     vault: Pubkey,
     signer_seeds: &[&[u8]],
 ) -> ProgramResult {
+    let mut accounts = vec![
+        auction_program.clone(),
+        authority.clone(),
+        auction,
+        clock,
+        token_mint.clone(),
+        bidder.clone(),
+        bidder_pot_token_acct.clone(),
+        bidder_pot,
+        accept_payment.clone(),
+        token_program,
+        fee_recipient_token.clone(),
+        fee_recipient.clone(),
+        fee_config.clone(),
+    ];
+    if let Some(referrer) = &referrer {
+        accounts.push(referrer.clone());
+    }
     invoke_signed(
         &claim_bid_instruction(
             *auction_program.key,
@@ -41,20 +63,14 @@ pub fn issue_claim_bid<'a>(
             *bidder.key,
             *bidder_pot_token_acct.key,
             *token_mint.key,
+            *fee_recipient_token.key,
+            *fee_recipient.key,
+            *fee_config.key,
+            None,
+            referrer.map(|referrer| *referrer.key),
             ClaimBidArgs { resource: vault },
         ),
-        &[
-            auction_program,
-            authority,
-            auction,
-            clock,
-            token_mint,
-            bidder,
-            bidder_pot_token_acct,
-            bidder_pot,
-            accept_payment,
-            token_program,
-        ],
+        &accounts,
         &[&signer_seeds],
     )?;
 
@@ -76,10 +92,17 @@ pub fn process_claim_bid(program_id: &Pubkey, accounts: &[AccountInfo]) -> Progr
     let clock_info = next_account_info(account_info_iter)?;
     let token_program_info = next_account_info(account_info_iter)?;
 
-    let mut auction_manager: AuctionManager =
-        try_from_slice_unchecked(&auction_manager_info.data.borrow_mut())?;
+    // Auctions without a fee config ignore the fee accounts, the accept payment account and the
+    // auction stand in for them.
+    let fee_recipient_token_info =
+        next_account_info(account_info_iter).unwrap_or(accept_payment_info);
+    let fee_recipient_info = next_account_info(account_info_iter).unwrap_or(accept_payment_info);
+    let fee_config_info = next_account_info(account_info_iter).unwrap_or(auction_info);
+    let referrer_info = next_account_info(account_info_iter).ok();
+
+    let mut auction_manager = AuctionManager::from_account_info(auction_manager_info)?;
     let store: Store = try_from_slice_unchecked(&store_info.data.borrow_mut())?;
-    let auction: AuctionData = try_from_slice_unchecked(&auction_info.data.borrow_mut())?;
+    let auction = AuctionData::from_account_info(auction_info)?;
 
     assert_owned_by(auction_info, &store.auction_program)?;
     assert_owned_by(auction_manager_info, program_id)?;
@@ -103,9 +126,7 @@ pub fn process_claim_bid(program_id: &Pubkey, accounts: &[AccountInfo]) -> Progr
     if auction_manager.vault != *vault_info.key {
         return Err(MetaplexError::AuctionManagerVaultMismatch.into());
     }
-    if auction.state != AuctionState::Ended {
-        return Err(MetaplexError::AuctionHasNotEnded.into());
-    }
+    assert_auction_has_winners(&auction)?;
 
     if auction_manager.state.status != AuctionManagerStatus::Disbursing
         && auction_manager.state.status != AuctionManagerStatus::Finished
@@ -136,6 +157,10 @@ pub fn process_claim_bid(program_id: &Pubkey, accounts: &[AccountInfo]) -> Progr
         token_mint_info.clone(),
         clock_info.clone(),
         token_program_info.clone(),
+        fee_recipient_token_info.clone(),
+        fee_recipient_info.clone(),
+        fee_config_info.clone(),
+        referrer_info.cloned(),
         *vault_info.key,
         authority_seeds,
     )?;
@@ -11,7 +11,6 @@ use {
     },
     solana_program::{
         account_info::{next_account_info, AccountInfo},
-        borsh::try_from_slice_unchecked,
         entrypoint::ProgramResult,
         pubkey::Pubkey,
         rent::Rent,
@@ -32,8 +31,7 @@ pub fn process_empty_payment_account(
     let rent_info = next_account_info(account_info_iter)?;
     let rent = &Rent::from_account_info(&rent_info)?;
 
-    let auction_manager: AuctionManager =
-        try_from_slice_unchecked(&auction_manager_info.data.borrow_mut())?;
+    let auction_manager = AuctionManager::from_account_info(auction_manager_info)?;
 
     let destination: Account = assert_initialized(destination_info)?;
     let accept_payment: Account = assert_initialized(accept_payment_info)?;
@@ -3,7 +3,7 @@ use {
         error::MetaplexError,
         state::{
             AuctionManager, AuctionManagerSettings, AuctionManagerStatus, Key, Store,
-            WinningConfigState, MAX_AUCTION_MANAGER_SIZE, PREFIX,
+            WinningConfigState, MAX_AUCTION_MANAGER_SIZE, MAX_WINNERS, PREFIX,
         },
         utils::{
             assert_derivation, assert_initialized, assert_owned_by, create_or_allocate_account_raw,
@@ -38,8 +38,8 @@ pub fn process_init_auction_manager(
     let system_info = next_account_info(account_info_iter)?;
     let rent_info = next_account_info(account_info_iter)?;
 
-    let vault: Vault = try_from_slice_unchecked(&vault_info.data.borrow_mut())?;
-    let auction: AuctionData = try_from_slice_unchecked(&auction_info.data.borrow_mut())?;
+    let vault = Vault::from_account_info(vault_info)?;
+    let auction = AuctionData::from_account_info(auction_info)?;
     let accept_payment: Account = assert_initialized(accept_payment_info)?;
     // Assert it is real
     let store: Store = try_from_slice_unchecked(&store_info.data.borrow_mut())?;
@@ -88,6 +88,10 @@ pub fn process_init_auction_manager(
         return Err(MetaplexError::VaultCannotEmpty.into());
     }
 
+    if auction_manager_settings.winning_configs.len() > MAX_WINNERS {
+        return Err(MetaplexError::TooManyWinningConfigs.into());
+    }
+
     let mut winning_config_states: Vec<WinningConfigState> = vec![];
     for n in 0..auction_manager_settings.winning_configs.len() {
         let winning_config = &auction_manager_settings.winning_configs[n];
@@ -117,7 +121,7 @@ pub fn process_init_auction_manager(
     let mut auction_manager: AuctionManager =
         try_from_slice_unchecked(&auction_manager_info.data.borrow_mut())?;
 
-    auction_manager.key = Key::AuctionManagerV1;
+    auction_manager.key = Key::AuctionManagerV2;
     auction_manager.store = *store_info.key;
     auction_manager.state.status = AuctionManagerStatus::Initialized;
     auction_manager.settings = auction_manager_settings;
@@ -127,7 +131,165 @@ pub fn process_init_auction_manager(
     auction_manager.accept_payment = *accept_payment_info.key;
     auction_manager.state.winning_configs_validated = 0;
     auction_manager.state.winning_config_states = winning_config_states;
-    auction_manager.serialize(&mut *auction_manager_info.data.borrow_mut())?;
+    auction_manager.serialize(&mut &mut auction_manager_info.data.borrow_mut()[..])?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            state::{EditionType, NonWinningConstraint, WinningConfig, WinningConstraint},
+            utils::tests::{account, auction_data, rent_account},
+        },
+        solana_program::{
+            entrypoint::MAX_PERMITTED_DATA_INCREASE, program_error::ProgramError,
+            program_pack::Pack,
+        },
+        spl_auction::processor::AuctionState,
+        spl_token::state::AccountState,
+        spl_token_vault::state::VAULT_V2_KEY,
+    };
+
+    /// Initializes an auction manager with a prize in its own box for each of `winners`.
+    fn init_auction_manager(winners: usize) -> Result<AuctionManager, ProgramError> {
+        let program_id = Pubkey::new_unique();
+        let auction_program = spl_auction::id();
+        let system = solana_program::system_program::id();
+        let store = Store {
+            key: Key::StoreV1,
+            public: true,
+            auction_program,
+            token_vault_program: spl_token_vault::id(),
+            token_metadata_program: spl_token_metadata::id(),
+            token_program: spl_token::id(),
+        };
+
+        let vault_key = Pubkey::new_unique();
+        let (auction_key, _) = Pubkey::find_program_address(
+            &[
+                spl_auction::PREFIX.as_bytes(),
+                auction_program.as_ref(),
+                vault_key.as_ref(),
+            ],
+            &auction_program,
+        );
+        let (auction_manager_key, _) =
+            Pubkey::find_program_address(&[PREFIX.as_bytes(), auction_key.as_ref()], &program_id);
+
+        let vault = Vault {
+            key: VAULT_V2_KEY,
+            token_program: spl_token::id(),
+            fraction_mint: Pubkey::new_unique(),
+            authority: auction_manager_key,
+            fraction_treasury: Pubkey::new_unique(),
+            redeem_treasury: Pubkey::new_unique(),
+            allow_further_share_creation: false,
+            pricing_lookup_address: Pubkey::new_unique(),
+            token_type_count: winners as u16,
+            state: VaultState::Combined,
+            locked_price_per_share: 0,
+            redemption_window: None,
+            redemption_deadline: None,
+            redemption_recipient: Pubkey::default(),
+            fee_basis_points: 0,
+            fee_recipient: Pubkey::default(),
+        };
+        let mint = Pubkey::new_unique();
+        let mut accept_payment = vec![0; Account::LEN];
+        Account {
+            mint,
+            owner: auction_manager_key,
+            state: AccountState::Initialized,
+            ..Account::default()
+        }
+        .pack_into_slice(&mut accept_payment);
+
+        let auction_manager_info = account(
+            auction_manager_key,
+            system,
+            vec![0; MAX_AUCTION_MANAGER_SIZE],
+            0,
+            false,
+        );
+        let accounts = [
+            auction_manager_info.clone(),
+            account(
+                vault_key,
+                spl_token_vault::id(),
+                vault.try_to_vec().unwrap(),
+                0,
+                false,
+            )
+            .clone(),
+            account(
+                auction_key,
+                auction_program,
+                auction_data(auction_manager_key, mint, AuctionState::Created),
+                0,
+                false,
+            )
+            .clone(),
+            account(Pubkey::new_unique(), system, vec![], 0, false).clone(),
+            account(Pubkey::new_unique(), system, vec![], 0, true).clone(),
+            account(
+                Pubkey::new_unique(),
+                spl_token::id(),
+                accept_payment,
+                0,
+                false,
+            )
+            .clone(),
+            account(
+                Pubkey::new_unique(),
+                program_id,
+                store.try_to_vec().unwrap(),
+                0,
+                false,
+            )
+            .clone(),
+            account(system, system, vec![], 0, false).clone(),
+            rent_account().clone(),
+        ];
+
+        let settings = AuctionManagerSettings {
+            open_edition_winner_constraint: WinningConstraint::NoOpenEdition,
+            open_edition_non_winning_constraint: NonWinningConstraint::NoOpenEdition,
+            winning_configs: (0..winners)
+                .map(|n| WinningConfig {
+                    safety_deposit_box_index: n as u16,
+                    amount: 1,
+                    edition_type: EditionType::Na,
+                })
+                .collect(),
+            open_edition_config: None,
+            open_edition_fixed_price: None,
+        };
+        process_init_auction_manager(&program_id, &accounts, settings)?;
+        AuctionManager::from_account_info(auction_manager_info)
+    }
+
+    // The manager is allocated through a CPI, which can't create accounts past 10KB.
+    const _: () = assert!(MAX_AUCTION_MANAGER_SIZE <= MAX_PERMITTED_DATA_INCREASE);
+
+    #[test]
+    fn test_init_auction_manager_with_many_winners() {
+        for winners in [201, 300, MAX_WINNERS] {
+            let auction_manager = init_auction_manager(winners).unwrap();
+            assert_eq!(auction_manager.key, Key::AuctionManagerV2);
+            assert_eq!(auction_manager.settings.winning_configs.len(), winners);
+            assert_eq!(auction_manager.state.winning_config_states.len(), winners);
+            assert_eq!(
+                auction_manager.settings.winning_configs[winners - 1].safety_deposit_box_index,
+                winners as u16 - 1
+            );
+        }
+
+        assert_eq!(
+            init_auction_manager(MAX_WINNERS + 1).err(),
+            Some(MetaplexError::TooManyWinningConfigs.into())
+        );
+    }
+}
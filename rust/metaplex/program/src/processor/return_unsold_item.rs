@@ -0,0 +1,207 @@
+use {
+    crate::{
+        error::MetaplexError,
+        state::{
+            AuctionManager, AuctionManagerStatus, EditionType, OriginalAuthorityLookup, Store,
+            PREFIX,
+        },
+        utils::{
+            assert_authority_correct, assert_derivation, assert_initialized, assert_owned_by,
+            assert_rent_exempt, assert_store_safety_vault_manager_match,
+            transfer_metadata_ownership, transfer_safety_deposit_box_items, unsold_winning_configs,
+        },
+    },
+    borsh::BorshSerialize,
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        borsh::try_from_slice_unchecked,
+        entrypoint::ProgramResult,
+        pubkey::Pubkey,
+        rent::Rent,
+        sysvar::Sysvar,
+    },
+    spl_auction::processor::AuctionData,
+    spl_token::state::Account,
+    spl_token_vault::state::SafetyDepositBox,
+};
+
+pub fn process_return_unsold_item<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let auction_manager_info = next_account_info(account_info_iter)?;
+    let safety_deposit_token_store_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let safety_deposit_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let fraction_mint_info = next_account_info(account_info_iter)?;
+    let auction_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let token_vault_program_info = next_account_info(account_info_iter)?;
+    let store_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let transfer_authority_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(rent_info)?;
+
+    let mut auction_manager = AuctionManager::from_account_info(auction_manager_info)?;
+    let safety_deposit = SafetyDepositBox::from_account_info(safety_deposit_info)?;
+    let auction = AuctionData::from_account_info(auction_info)?;
+    let store: Store = try_from_slice_unchecked(&store_info.data.borrow_mut())?;
+    let safety_deposit_token_store: Account = assert_initialized(safety_deposit_token_store_info)?;
+
+    // Is it initialized and an actual Account?
+    let _destination: Account = assert_initialized(destination_info)?;
+
+    assert_authority_correct(&auction_manager, authority_info)?;
+    assert_owned_by(auction_manager_info, program_id)?;
+    assert_owned_by(auction_info, &store.auction_program)?;
+    assert_owned_by(destination_info, token_program_info.key)?;
+    assert_rent_exempt(rent, destination_info)?;
+    assert_store_safety_vault_manager_match(
+        &auction_manager,
+        &safety_deposit,
+        vault_info,
+        safety_deposit_token_store_info,
+    )?;
+
+    if auction_manager.store != *store_info.key {
+        return Err(MetaplexError::AuctionManagerStoreMismatch.into());
+    }
+
+    if auction_manager.auction != *auction_info.key {
+        return Err(MetaplexError::AuctionManagerAuctionMismatch.into());
+    }
+
+    if store.token_program != *token_program_info.key {
+        return Err(MetaplexError::AuctionManagerTokenProgramMismatch.into());
+    }
+
+    if store.token_vault_program != *token_vault_program_info.key {
+        return Err(MetaplexError::AuctionManagerTokenVaultProgramMismatch.into());
+    }
+
+    let transfer_authority_seeds = [
+        spl_token_vault::state::PREFIX.as_bytes(),
+        store.token_vault_program.as_ref(),
+    ];
+    let (transfer_authority, _) =
+        Pubkey::find_program_address(&transfer_authority_seeds, &store.token_vault_program);
+    if transfer_authority != *transfer_authority_info.key {
+        return Err(MetaplexError::InvalidTransferAuthority.into());
+    }
+
+    // Nobody won, so every prize left in the box goes back, whatever its edition type.
+    let unsold = unsold_winning_configs(&auction_manager, &auction, &safety_deposit)?;
+    let edition_type = auction_manager.settings.winning_configs[unsold[0]].edition_type;
+
+    let bump_seed = assert_derivation(
+        program_id,
+        auction_manager_info,
+        &[PREFIX.as_bytes(), auction_manager.auction.as_ref()],
+    )?;
+    let authority_seeds = &[
+        PREFIX.as_bytes(),
+        auction_manager.auction.as_ref(),
+        &[bump_seed],
+    ];
+
+    transfer_safety_deposit_box_items(
+        token_vault_program_info.clone(),
+        destination_info.clone(),
+        safety_deposit_info.clone(),
+        safety_deposit_token_store_info.clone(),
+        vault_info.clone(),
+        fraction_mint_info.clone(),
+        auction_manager_info.clone(),
+        transfer_authority_info.clone(),
+        rent_info.clone(),
+        safety_deposit_token_store.amount,
+        authority_seeds,
+    )?;
+
+    // A master edition's metadata was handed to the auction manager when it was validated, and goes
+    // back to whoever held it then.
+    if edition_type == EditionType::MasterEdition {
+        let metadata_info = next_account_info(account_info_iter)?;
+        let original_authority_lookup_info = next_account_info(account_info_iter)?;
+        let original_authority_info = next_account_info(account_info_iter)?;
+        let token_metadata_program_info = next_account_info(account_info_iter)?;
+
+        if store.token_metadata_program != *token_metadata_program_info.key {
+            return Err(MetaplexError::AuctionManagerTokenMetadataProgramMismatch.into());
+        }
+
+        let metadata_seeds = &[
+            spl_token_metadata::state::PREFIX.as_bytes(),
+            store.token_metadata_program.as_ref(),
+            safety_deposit.token_mint.as_ref(),
+        ];
+        let (metadata_key, _) =
+            Pubkey::find_program_address(metadata_seeds, &store.token_metadata_program);
+        if metadata_key != *metadata_info.key {
+            return Err(MetaplexError::SafetyDepositBoxMetadataMismatch.into());
+        }
+
+        let original_authority_lookup_seeds = &[
+            PREFIX.as_bytes(),
+            auction_manager.auction.as_ref(),
+            metadata_info.key.as_ref(),
+        ];
+        let (expected_key, _) =
+            Pubkey::find_program_address(original_authority_lookup_seeds, program_id);
+        if expected_key != *original_authority_lookup_info.key {
+            return Err(MetaplexError::OriginalAuthorityLookupKeyMismatch.into());
+        }
+
+        let original_authority_lookup: OriginalAuthorityLookup =
+            try_from_slice_unchecked(&original_authority_lookup_info.data.borrow_mut())?;
+        if original_authority_lookup.original_authority != *original_authority_info.key {
+            return Err(MetaplexError::OriginalAuthorityMismatch.into());
+        }
+
+        transfer_metadata_ownership(
+            token_metadata_program_info.clone(),
+            metadata_info.clone(),
+            auction_manager_info.clone(),
+            original_authority_info.clone(),
+            authority_seeds,
+        )?;
+
+        auction_manager
+            .state
+            .master_editions_with_authorities_remaining_to_return = match auction_manager
+            .state
+            .master_editions_with_authorities_remaining_to_return
+            .checked_sub(1)
+        {
+            Some(val) => val,
+            None => return Err(MetaplexError::NumericalOverflowError.into()),
+        };
+    }
+
+    for n in unsold {
+        auction_manager.state.winning_config_states[n].claimed = true;
+    }
+
+    let open_claims = auction_manager
+        .state
+        .winning_config_states
+        .iter()
+        .any(|state| !state.claimed);
+    auction_manager.state.status = if !open_claims
+        && auction_manager
+            .state
+            .master_editions_with_authorities_remaining_to_return
+            == 0
+    {
+        AuctionManagerStatus::Finished
+    } else {
+        AuctionManagerStatus::Disbursing
+    };
+
+    auction_manager.serialize(&mut *auction_manager_info.data.borrow_mut())?;
+
+    Ok(())
+}
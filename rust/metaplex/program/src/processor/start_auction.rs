@@ -45,8 +45,7 @@ pub fn process_start_auction(program_id: &Pubkey, accounts: &[AccountInfo]) -> P
     let auction_program_info = next_account_info(account_info_iter)?;
     let clock_info = next_account_info(account_info_iter)?;
 
-    let mut auction_manager: AuctionManager =
-        try_from_slice_unchecked(&auction_manager_info.data.borrow_mut())?;
+    let mut auction_manager = AuctionManager::from_account_info(auction_manager_info)?;
 
     let store: Store = try_from_slice_unchecked(&store_info.data.borrow_mut())?;
     assert_authority_correct(&auction_manager, authority_info)?;
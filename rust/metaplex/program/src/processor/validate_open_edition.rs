@@ -38,12 +38,11 @@ pub fn process_validate_open_edition(
     let token_program_info = next_account_info(account_info_iter)?;
     let token_metadata_program_info = next_account_info(account_info_iter)?;
 
-    let mut auction_manager: AuctionManager =
-        try_from_slice_unchecked(&auction_manager_info.data.borrow_mut())?;
+    let mut auction_manager = AuctionManager::from_account_info(auction_manager_info)?;
 
     assert_authority_correct(&auction_manager, authority_info)?;
 
-    let vault: Vault = try_from_slice_unchecked(&vault_info.data.borrow_mut())?;
+    let vault = Vault::from_account_info(vault_info)?;
 
     assert_owned_by(auction_manager_info, program_id)?;
 
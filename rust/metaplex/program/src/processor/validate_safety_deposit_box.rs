@@ -49,15 +49,13 @@ pub fn process_validate_safety_deposit_box(
     let system_info = next_account_info(account_info_iter)?;
     let rent_info = next_account_info(account_info_iter)?;
 
-    let mut auction_manager: AuctionManager =
-        try_from_slice_unchecked(&auction_manager_info.data.borrow_mut())?;
-    let safety_deposit: SafetyDepositBox =
-        try_from_slice_unchecked(&safety_deposit_info.data.borrow_mut())?;
+    let mut auction_manager = AuctionManager::from_account_info(auction_manager_info)?;
+    let safety_deposit = SafetyDepositBox::from_account_info(safety_deposit_info)?;
     let safety_deposit_token_store: Account = assert_initialized(safety_deposit_token_store_info)?;
     let metadata: Metadata = try_from_slice_unchecked(&metadata_info.data.borrow_mut())?;
     let store: Store = try_from_slice_unchecked(&auction_manager_store_info.data.borrow_mut())?;
     // Is it a real vault?
-    let _vault: Vault = try_from_slice_unchecked(&vault_info.data.borrow_mut())?;
+    let _vault = Vault::from_account_info(vault_info)?;
     // Is it a real mint?
     let _mint: Mint = assert_initialized(mint_info)?;
 
@@ -263,14 +261,14 @@ pub fn process_validate_safety_deposit_box(
     auction_manager.state.winning_configs_validated = match auction_manager
         .state
         .winning_configs_validated
-        .checked_add(winning_configs.len() as u8)
+        .checked_add(winning_configs.len() as u16)
     {
         Some(val) => val,
         None => return Err(MetaplexError::NumericalOverflowError.into()),
     };
 
     if auction_manager.state.winning_configs_validated
-        == auction_manager.settings.winning_configs.len() as u8
+        == auction_manager.settings.winning_configs.len() as u16
     {
         auction_manager.state.status = AuctionManagerStatus::Validated
     }
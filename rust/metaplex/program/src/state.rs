@@ -1,15 +1,21 @@
 use {
     borsh::{BorshDeserialize, BorshSerialize},
-    solana_program::pubkey::Pubkey,
+    solana_program::{
+        account_info::AccountInfo, borsh::try_from_slice_unchecked, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
 };
 /// prefix used for PDAs to avoid certain collision attacks (https://en.wikipedia.org/wiki/Collision_attack#Chosen-prefix_collision_attack)
 pub const PREFIX: &str = "metaplex";
 
-pub const MAX_WINNERS: usize = 200;
+/// Auction managers are allocated at their maximum size through a CPI, which caps new accounts at
+/// 10KB. That cap, not the u16 box indices, is what limits the number of winners.
+pub const MAX_WINNERS: usize = 1000;
+/// Each winner has a 4 byte winning config and a 3 byte winning config state.
 pub const MAX_WINNER_SIZE: usize = 7 * MAX_WINNERS;
 // Add 150 padding for future keys and booleans
 pub const MAX_AUCTION_MANAGER_SIZE: usize =
-    1 + 32 + 32 + 32 + 32 + 1 + 1 + 1 + 1 + MAX_WINNER_SIZE + 2 + 9 + 150;
+    1 + 32 + 32 + 32 + 32 + 32 + 1 + 2 + 2 + 4 + 1 + 1 + 4 + MAX_WINNER_SIZE + 3 + 9 + 150;
 // Add padding for future booleans/enums
 pub const MAX_STORE_SIZE: usize = 2 + 32 + 32 + 32 + 32 + 100;
 pub const MAX_WHITELISTED_CREATOR_SIZE: usize = 2 + 32 + 10;
@@ -22,10 +28,11 @@ pub enum Key {
     BidRedemptionTicketV1,
     StoreV1,
     WhitelistedCreatorV1,
+    AuctionManagerV2,
 }
 
 /// An Auction Manager can support an auction that is an English auction and limited edition and open edition
-/// all at once. Need to support all at once. We use u8 keys to point to safety deposit indices in Vault
+/// all at once. Need to support all at once. We use u16 keys to point to safety deposit indices in Vault
 /// as opposed to the pubkeys to save on space. Ordering of safety deposits is guaranteed fixed by vault
 /// implementation.
 #[repr(C)]
@@ -48,18 +55,110 @@ pub struct AuctionManager {
     pub settings: AuctionManagerSettings,
 }
 
+impl AuctionManager {
+    /// Decodes an auction manager in either layout. AuctionManagerV1 managers are upgraded to V2, which
+    /// fits within their original allocation, so they are migrated in place the next time they are saved.
+    pub fn from_slice(data: &[u8]) -> Result<AuctionManager, ProgramError> {
+        if data.first() == Some(&(Key::AuctionManagerV1 as u8)) {
+            let legacy: AuctionManagerV1 = try_from_slice_unchecked(data)?;
+            return Ok(AuctionManager {
+                key: Key::AuctionManagerV2,
+                store: legacy.store,
+                authority: legacy.authority,
+                auction: legacy.auction,
+                vault: legacy.vault,
+                accept_payment: legacy.accept_payment,
+                state: AuctionManagerState {
+                    status: legacy.state.status,
+                    winning_configs_validated: legacy.state.winning_configs_validated as u16,
+                    master_editions_with_authorities_remaining_to_return: legacy
+                        .state
+                        .master_editions_with_authorities_remaining_to_return
+                        as u16,
+                    winning_config_states: legacy.state.winning_config_states,
+                },
+                settings: AuctionManagerSettings {
+                    open_edition_winner_constraint: legacy.settings.open_edition_winner_constraint,
+                    open_edition_non_winning_constraint: legacy
+                        .settings
+                        .open_edition_non_winning_constraint,
+                    winning_configs: legacy
+                        .settings
+                        .winning_configs
+                        .iter()
+                        .map(|c| WinningConfig {
+                            safety_deposit_box_index: c.safety_deposit_box_index as u16,
+                            amount: c.amount,
+                            edition_type: c.edition_type,
+                        })
+                        .collect(),
+                    open_edition_config: legacy.settings.open_edition_config.map(|i| i as u16),
+                    open_edition_fixed_price: legacy.settings.open_edition_fixed_price,
+                },
+            });
+        }
+        let auction_manager: AuctionManager = try_from_slice_unchecked(data)?;
+        Ok(auction_manager)
+    }
+
+    pub fn from_account_info(a: &AccountInfo) -> Result<AuctionManager, ProgramError> {
+        AuctionManager::from_slice(&a.data.borrow())
+    }
+}
+
+/// Original auction manager layout with u8 safety deposit box indices.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, Debug)]
+pub struct AuctionManagerV1 {
+    pub key: Key,
+    pub store: Pubkey,
+    pub authority: Pubkey,
+    pub auction: Pubkey,
+    pub vault: Pubkey,
+    pub accept_payment: Pubkey,
+    pub state: AuctionManagerStateV1,
+    pub settings: AuctionManagerSettingsV1,
+}
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, Debug)]
+pub struct AuctionManagerStateV1 {
+    pub status: AuctionManagerStatus,
+    pub winning_configs_validated: u8,
+    pub master_editions_with_authorities_remaining_to_return: u8,
+    pub winning_config_states: Vec<WinningConfigState>,
+}
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, Debug)]
+pub struct AuctionManagerSettingsV1 {
+    pub open_edition_winner_constraint: WinningConstraint,
+    pub open_edition_non_winning_constraint: NonWinningConstraint,
+    pub winning_configs: Vec<WinningConfigV1>,
+    pub open_edition_config: Option<u8>,
+    pub open_edition_fixed_price: Option<u64>,
+}
+
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize, Copy, Debug)]
+pub struct WinningConfigV1 {
+    pub safety_deposit_box_index: u8,
+    pub amount: u8,
+    pub edition_type: EditionType,
+}
+
 #[repr(C)]
 #[derive(Clone, BorshSerialize, BorshDeserialize, Debug)]
 pub struct AuctionManagerState {
     pub status: AuctionManagerStatus,
     /// When all configs are validated the auction is started and auction manager moves to Running
-    pub winning_configs_validated: u8,
+    pub winning_configs_validated: u16,
 
     /// Each master edition used as a template has to grant it's authority to the auction manager.
     /// This counter is incremented by one each time this is done. At the end of the auction, this is decremented
     /// each time authority is delegated back to the owner or the new owner and when it hits 0 another condition
     /// is met for going to Finished state.
-    pub master_editions_with_authorities_remaining_to_return: u8,
+    pub master_editions_with_authorities_remaining_to_return: u16,
 
     pub winning_config_states: Vec<WinningConfigState>,
 }
@@ -83,7 +182,7 @@ pub struct AuctionManagerSettings {
     pub winning_configs: Vec<WinningConfig>,
 
     /// The safety deposit box index in the vault containing the template for the open edition
-    pub open_edition_config: Option<u8>,
+    pub open_edition_config: Option<u16>,
 
     /// Setting this field disconnects the open edition's price from the bid. Any bid you submit, regardless
     /// of amount, charges you the same fixed price. NOTE: This field supersedes open_edition_reserve_price.
@@ -121,7 +220,7 @@ pub enum EditionType {
 #[repr(C)]
 #[derive(Clone, BorshSerialize, BorshDeserialize, Copy, Debug)]
 pub struct WinningConfig {
-    pub safety_deposit_box_index: u8,
+    pub safety_deposit_box_index: u16,
     pub amount: u8,
     pub edition_type: EditionType,
 }
@@ -180,3 +279,68 @@ pub struct WhitelistedCreator {
     pub address: Pubkey,
     pub activated: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_legacy_auction_manager() {
+        let legacy = AuctionManagerV1 {
+            key: Key::AuctionManagerV1,
+            store: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            auction: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            accept_payment: Pubkey::new_unique(),
+            state: AuctionManagerStateV1 {
+                status: AuctionManagerStatus::Running,
+                winning_configs_validated: 200,
+                master_editions_with_authorities_remaining_to_return: 3,
+                winning_config_states: vec![
+                    WinningConfigState {
+                        amount_minted: 0,
+                        validated: true,
+                        claimed: false,
+                    };
+                    200
+                ],
+            },
+            settings: AuctionManagerSettingsV1 {
+                open_edition_winner_constraint: WinningConstraint::NoOpenEdition,
+                open_edition_non_winning_constraint: NonWinningConstraint::NoOpenEdition,
+                winning_configs: (0..200)
+                    .map(|n| WinningConfigV1 {
+                        safety_deposit_box_index: n as u8,
+                        amount: 1,
+                        edition_type: EditionType::Na,
+                    })
+                    .collect(),
+                open_edition_config: Some(200),
+                open_edition_fixed_price: None,
+            },
+        };
+        let mut data = legacy.try_to_vec().unwrap();
+        // Legacy managers were allocated at the old, smaller maximum size.
+        data.resize(1895, 0);
+
+        let auction_manager = AuctionManager::from_slice(&data).unwrap();
+        assert_eq!(auction_manager.key, Key::AuctionManagerV2);
+        assert_eq!(auction_manager.vault, legacy.vault);
+        assert_eq!(auction_manager.state.status, AuctionManagerStatus::Running);
+        assert_eq!(auction_manager.state.winning_configs_validated, 200);
+        assert_eq!(
+            auction_manager
+                .state
+                .master_editions_with_authorities_remaining_to_return,
+            3
+        );
+        assert_eq!(auction_manager.state.winning_config_states.len(), 200);
+        assert_eq!(auction_manager.settings.winning_configs.len(), 200);
+        assert_eq!(
+            auction_manager.settings.winning_configs[199].safety_deposit_box_index,
+            199
+        );
+        assert_eq!(auction_manager.settings.open_edition_config, Some(200));
+    }
+}
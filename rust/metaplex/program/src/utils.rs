@@ -70,6 +70,16 @@ pub fn assert_signer(account_info: &AccountInfo) -> ProgramResult {
     }
 }
 
+/// Bids can only be redeemed once the auction has ended with winners. An auction whose reserve was
+/// not met refunds every bid, its prizes go back to the authority instead.
+pub fn assert_auction_has_winners(auction: &AuctionData) -> ProgramResult {
+    match auction.state {
+        AuctionState::Ended => Ok(()),
+        AuctionState::ReserveNotMet => Err(MetaplexError::AuctionReserveNotMet.into()),
+        _ => Err(MetaplexError::AuctionHasNotEnded.into()),
+    }
+}
+
 pub fn assert_store_safety_vault_manager_match(
     auction_manager: &AuctionManager,
     safety_deposit: &SafetyDepositBox,
@@ -442,11 +452,9 @@ pub fn common_redeem_checks(
         }
     }
 
-    let mut auction_manager: AuctionManager =
-        try_from_slice_unchecked(&auction_manager_info.data.borrow_mut())?;
-    let safety_deposit: SafetyDepositBox =
-        try_from_slice_unchecked(&safety_deposit_info.data.borrow_mut())?;
-    let auction: AuctionData = try_from_slice_unchecked(&auction_info.data.borrow_mut())?;
+    let mut auction_manager = AuctionManager::from_account_info(auction_manager_info)?;
+    let safety_deposit = SafetyDepositBox::from_account_info(safety_deposit_info)?;
+    let auction = AuctionData::from_account_info(auction_info)?;
     let store: Store = try_from_slice_unchecked(&store_info.data.borrow_mut())?;
     let bidder_metadata: BidderMetadata =
         try_from_slice_unchecked(&bidder_metadata_info.data.borrow_mut())?;
@@ -481,9 +489,7 @@ pub fn common_redeem_checks(
         return Err(MetaplexError::AuctionManagerTokenMetadataProgramMismatch.into());
     }
 
-    if auction.state != AuctionState::Ended {
-        return Err(MetaplexError::AuctionHasNotEnded.into());
-    }
+    assert_auction_has_winners(&auction)?;
 
     // No-op if already set.
     auction_manager.state.status = AuctionManagerStatus::Disbursing;
@@ -629,6 +635,37 @@ pub struct CommonWinningConfigCheckReturn {
     pub transfer_authority: Pubkey,
 }
 
+/// Indices of the winning configs whose prize sits in `safety_deposit` and hasn't been handed out.
+/// These are only unsold once the auction has ended without meeting its reserve.
+pub fn unsold_winning_configs(
+    auction_manager: &AuctionManager,
+    auction: &AuctionData,
+    safety_deposit: &SafetyDepositBox,
+) -> Result<Vec<usize>, ProgramError> {
+    if auction.state != AuctionState::ReserveNotMet {
+        return Err(MetaplexError::AuctionHasWinners.into());
+    }
+
+    let configs: Vec<usize> = (0..auction_manager.settings.winning_configs.len())
+        .filter(|n| {
+            auction_manager.settings.winning_configs[*n].safety_deposit_box_index
+                == safety_deposit.order
+        })
+        .collect();
+    if configs.is_empty() {
+        return Err(MetaplexError::SafetyDepositBoxNotUsedInAuction.into());
+    }
+
+    let unclaimed: Vec<usize> = configs
+        .into_iter()
+        .filter(|n| !auction_manager.state.winning_config_states[*n].claimed)
+        .collect();
+    if unclaimed.is_empty() {
+        return Err(MetaplexError::PrizeAlreadyClaimed.into());
+    }
+    Ok(unclaimed)
+}
+
 pub fn common_winning_config_checks(
     auction_manager: &AuctionManager,
     store: &Store,
@@ -821,3 +858,310 @@ pub fn assert_derivation(
     }
     Ok(bump)
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use {
+        super::*,
+        crate::state::{
+            AuctionManagerSettings, AuctionManagerState, EditionType, NonWinningConstraint,
+            WinningConstraint,
+        },
+        spl_auction::processor::{BidState, PriceFloor},
+        spl_token::state::AccountState,
+        spl_token_vault::state::SAFETY_DEPOSIT_V2_KEY,
+    };
+
+    /// An account that lives for the rest of the test run, as the redeem checks borrow theirs for
+    /// as long as their own lifetime.
+    pub(crate) fn account(
+        key: Pubkey,
+        owner: Pubkey,
+        data: Vec<u8>,
+        lamports: u64,
+        is_signer: bool,
+    ) -> &'static AccountInfo<'static> {
+        Box::leak(Box::new(AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            true,
+            Box::leak(Box::new(lamports)),
+            Box::leak(data.into_boxed_slice()),
+            Box::leak(Box::new(owner)),
+            false,
+            0,
+        )))
+    }
+
+    pub(crate) fn rent_account() -> &'static AccountInfo<'static> {
+        let rent_info = account(
+            solana_program::sysvar::rent::id(),
+            solana_program::sysvar::id(),
+            vec![0; Rent::size_of()],
+            0,
+            false,
+        );
+        Rent::default()
+            .to_account_info(&mut rent_info.clone())
+            .unwrap();
+        rent_info
+    }
+
+    /// Writes an auction in the layout that predates the fields appended after its bid state.
+    pub(crate) fn auction_data(
+        authority: Pubkey,
+        token_mint: Pubkey,
+        state: AuctionState,
+    ) -> Vec<u8> {
+        let mut data = (
+            authority,
+            Pubkey::new_unique(),
+            token_mint,
+            None::<i64>,
+            Some(0i64),
+            None::<i64>,
+            None::<i64>,
+            PriceFloor::None([0; 32]),
+        )
+            .try_to_vec()
+            .unwrap();
+        data.extend(state.try_to_vec().unwrap());
+        data.extend(BidState::new_english(1).try_to_vec().unwrap());
+        data
+    }
+
+    fn auction_manager(boxes: &[u16]) -> AuctionManager {
+        AuctionManager {
+            key: Key::AuctionManagerV2,
+            store: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            auction: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            accept_payment: Pubkey::new_unique(),
+            state: AuctionManagerState {
+                status: AuctionManagerStatus::Running,
+                winning_configs_validated: boxes.len() as u16,
+                master_editions_with_authorities_remaining_to_return: 0,
+                winning_config_states: boxes
+                    .iter()
+                    .map(|_| WinningConfigState {
+                        amount_minted: 0,
+                        validated: true,
+                        claimed: false,
+                    })
+                    .collect(),
+            },
+            settings: AuctionManagerSettings {
+                open_edition_winner_constraint: WinningConstraint::NoOpenEdition,
+                open_edition_non_winning_constraint: NonWinningConstraint::NoOpenEdition,
+                winning_configs: boxes
+                    .iter()
+                    .map(|index| WinningConfig {
+                        safety_deposit_box_index: *index,
+                        amount: 1,
+                        edition_type: EditionType::Na,
+                    })
+                    .collect(),
+                open_edition_config: None,
+                open_edition_fixed_price: None,
+            },
+        }
+    }
+
+    fn safety_deposit(auction_manager: &AuctionManager, order: u16) -> SafetyDepositBox {
+        SafetyDepositBox {
+            key: SAFETY_DEPOSIT_V2_KEY,
+            vault: auction_manager.vault,
+            token_mint: Pubkey::new_unique(),
+            store: Pubkey::new_unique(),
+            order,
+            amount: 1,
+        }
+    }
+
+    /// Runs the redeem checks for a bidder on an auction in `state`.
+    fn redeem_checks(state: AuctionState) -> Result<CommonRedeemReturn, ProgramError> {
+        let program_id = Pubkey::new_unique();
+        let auction_program = spl_auction::id();
+        let rent = Rent::default();
+        let mut auction_manager = auction_manager(&[0]);
+        let safety_deposit = safety_deposit(&auction_manager, 0);
+        let bidder = Pubkey::new_unique();
+        let store = Store {
+            key: Key::StoreV1,
+            public: true,
+            auction_program,
+            token_vault_program: spl_token_vault::id(),
+            token_metadata_program: spl_token_metadata::id(),
+            token_program: spl_token::id(),
+        };
+
+        let (auction_key, _) = Pubkey::find_program_address(
+            &[
+                spl_auction::PREFIX.as_bytes(),
+                Pubkey::new_unique().as_ref(),
+            ],
+            &auction_program,
+        );
+        auction_manager.auction = auction_key;
+        let (bidder_metadata_key, _) = Pubkey::find_program_address(
+            &[
+                spl_auction::PREFIX.as_bytes(),
+                auction_program.as_ref(),
+                auction_key.as_ref(),
+                bidder.as_ref(),
+                "metadata".as_bytes(),
+            ],
+            &auction_program,
+        );
+        let (bid_redemption_key, _) = Pubkey::find_program_address(
+            &[
+                PREFIX.as_bytes(),
+                auction_key.as_ref(),
+                bidder_metadata_key.as_ref(),
+            ],
+            &program_id,
+        );
+
+        let mut destination = vec![0; Account::LEN];
+        Account {
+            mint: safety_deposit.token_mint,
+            owner: bidder,
+            state: AccountState::Initialized,
+            ..Account::default()
+        }
+        .pack_into_slice(&mut destination);
+        let bidder_metadata = BidderMetadata {
+            bidder_pubkey: bidder,
+            auction_pubkey: auction_key,
+            last_bid: 1000,
+            last_bid_timestamp: 0,
+            cancelled: false,
+        };
+        let system = solana_program::system_program::id();
+        common_redeem_checks(CommonRedeemCheckArgs {
+            program_id: Box::leak(Box::new(program_id)),
+            auction_manager_info: account(
+                Pubkey::new_unique(),
+                program_id,
+                auction_manager.try_to_vec().unwrap(),
+                0,
+                false,
+            ),
+            safety_deposit_token_store_info: account(
+                safety_deposit.store,
+                system,
+                vec![],
+                0,
+                false,
+            ),
+            destination_info: account(
+                Pubkey::new_unique(),
+                spl_token::id(),
+                destination,
+                rent.minimum_balance(Account::LEN),
+                false,
+            ),
+            bid_redemption_info: account(bid_redemption_key, system, vec![], 0, false),
+            safety_deposit_info: account(
+                Pubkey::new_unique(),
+                spl_token_vault::id(),
+                safety_deposit.try_to_vec().unwrap(),
+                0,
+                false,
+            ),
+            vault_info: account(auction_manager.vault, system, vec![], 0, false),
+            auction_info: account(
+                auction_key,
+                auction_program,
+                auction_data(Pubkey::new_unique(), Pubkey::new_unique(), state),
+                0,
+                false,
+            ),
+            bidder_metadata_info: account(
+                bidder_metadata_key,
+                auction_program,
+                bidder_metadata.try_to_vec().unwrap(),
+                0,
+                false,
+            ),
+            bidder_info: account(bidder, system, vec![], 0, true),
+            token_program_info: account(spl_token::id(), system, vec![], 0, false),
+            token_vault_program_info: account(spl_token_vault::id(), system, vec![], 0, false),
+            token_metadata_program_info: account(
+                spl_token_metadata::id(),
+                system,
+                vec![],
+                0,
+                false,
+            ),
+            store_info: account(
+                Pubkey::new_unique(),
+                program_id,
+                store.try_to_vec().unwrap(),
+                0,
+                false,
+            ),
+            rent_info: rent_account(),
+            is_open_edition: false,
+        })
+    }
+
+    #[test]
+    fn test_redeem_checks_need_winners() {
+        assert!(redeem_checks(AuctionState::Ended).is_ok());
+        assert_eq!(
+            redeem_checks(AuctionState::ReserveNotMet).err(),
+            Some(MetaplexError::AuctionReserveNotMet.into())
+        );
+        assert_eq!(
+            redeem_checks(AuctionState::Started).err(),
+            Some(MetaplexError::AuctionHasNotEnded.into())
+        );
+    }
+
+    #[test]
+    fn test_unsold_winning_configs() {
+        let mut auction_manager = auction_manager(&[0, 1, 0]);
+        let first = safety_deposit(&auction_manager, 0);
+        let unused = safety_deposit(&auction_manager, 5);
+        let reserve_not_met = AuctionData::from_slice(&auction_data(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            AuctionState::ReserveNotMet,
+        ))
+        .unwrap();
+        let ended = AuctionData::from_slice(&auction_data(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            AuctionState::Ended,
+        ))
+        .unwrap();
+
+        // Every prize in the box goes back when nobody won.
+        assert_eq!(
+            unsold_winning_configs(&auction_manager, &reserve_not_met, &first),
+            Ok(vec![0, 2])
+        );
+        assert_eq!(
+            unsold_winning_configs(&auction_manager, &ended, &first),
+            Err(MetaplexError::AuctionHasWinners.into())
+        );
+        assert_eq!(
+            unsold_winning_configs(&auction_manager, &reserve_not_met, &unused),
+            Err(MetaplexError::SafetyDepositBoxNotUsedInAuction.into())
+        );
+
+        // Prizes already handed back are skipped, and a box with none left can't be emptied twice.
+        auction_manager.state.winning_config_states[0].claimed = true;
+        assert_eq!(
+            unsold_winning_configs(&auction_manager, &reserve_not_met, &first),
+            Ok(vec![2])
+        );
+        auction_manager.state.winning_config_states[2].claimed = true;
+        assert_eq!(
+            unsold_winning_configs(&auction_manager, &reserve_not_met, &first),
+            Err(MetaplexError::PrizeAlreadyClaimed.into())
+        );
+    }
+}
@@ -438,7 +438,7 @@ pub fn initialize_auction_manager(
     transaction.sign(&signers, recent_blockhash);
     client.send_and_confirm_transaction(&transaction).unwrap();
     let account = client.get_account(&auction_manager_key).unwrap();
-    let manager: AuctionManager = try_from_slice_unchecked(&account.data).unwrap();
+    let manager = AuctionManager::from_slice(&account.data).unwrap();
 
     (auction_manager_key, manager)
 }
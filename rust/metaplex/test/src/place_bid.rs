@@ -44,13 +44,13 @@ pub fn make_bid(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) {
     let auction_manager_key = pubkey_of(app_matches, "auction_manager").unwrap();
 
     let account = client.get_account(&auction_manager_key).unwrap();
-    let manager: AuctionManager = try_from_slice_unchecked(&account.data).unwrap();
+    let manager = AuctionManager::from_slice(&account.data).unwrap();
 
     let store_account = client.get_account(&manager.store).unwrap();
     let store: Store = try_from_slice_unchecked(&store_account.data).unwrap();
 
     let auction_account = client.get_account(&manager.auction).unwrap();
-    let auction: AuctionData = try_from_slice_unchecked(&auction_account.data).unwrap();
+    let auction = AuctionData::from_slice(&auction_account.data).unwrap();
     let wallet_key = wallet.pubkey();
     let bidder_pot_seeds = &[
         spl_auction::PREFIX.as_bytes(),
@@ -70,7 +70,7 @@ pub fn make_bid(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) {
     let new_bidder_pot = Keypair::new();
     match bidder_pot_account {
         Ok(val) => {
-            let bidder_pot: BidderPot = try_from_slice_unchecked(&val.data).unwrap();
+            let bidder_pot = BidderPot::from_slice(&val.data).unwrap();
             bidder_pot_token = bidder_pot.bidder_pot;
         }
         Err(_) => {
@@ -157,6 +157,9 @@ pub fn make_bid(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) {
         auction.token_mint,
         transfer_authority.pubkey(),
         payer.pubkey(),
+        None,
+        None,
+        None,
         PlaceBidArgs {
             amount,
             resource: manager.vault,
@@ -201,7 +201,7 @@ fn redeem_bid_open_edition_type<'a>(
 
     if app_matches.is_present("mint_it") {
         let auction_acct = client.get_account(&auction).unwrap();
-        let auction: AuctionData = try_from_slice_unchecked(&auction_acct.data).unwrap();
+        let auction = AuctionData::from_slice(&auction_acct.data).unwrap();
 
         instructions.push(
             mint_to(
@@ -393,7 +393,7 @@ pub fn redeem_bid_wrapper(app_matches: &ArgMatches, payer: Keypair, client: RpcC
     let mint_map = parse_metadata_keys(&(auction_manager_key.to_string() + ".json"));
 
     let account = client.get_account(&auction_manager_key).unwrap();
-    let manager: AuctionManager = try_from_slice_unchecked(&account.data).unwrap();
+    let manager = AuctionManager::from_slice(&account.data).unwrap();
 
     let store_account = client.get_account(&manager.store).unwrap();
     let store: Store = try_from_slice_unchecked(&store_account.data).unwrap();
@@ -410,7 +410,7 @@ pub fn redeem_bid_wrapper(app_matches: &ArgMatches, payer: Keypair, client: RpcC
             let pubkey_arr = array_ref![obj.data, 1, 32];
             let pubkey = Pubkey::new_from_array(*pubkey_arr);
             if pubkey == manager.vault {
-                let safety_deposit: SafetyDepositBox = try_from_slice_unchecked(&obj.data).unwrap();
+                let safety_deposit = SafetyDepositBox::from_slice(&obj.data).unwrap();
                 safety_deposits.insert(safety_deposit.order, (safety_deposit, obj_key));
             }
         }
@@ -428,9 +428,9 @@ pub fn redeem_bid_wrapper(app_matches: &ArgMatches, payer: Keypair, client: RpcC
     let bidding_metadata = client.get_account(&meta_key).unwrap();
     let auction_data = client.get_account(&manager.auction).unwrap();
     let vault_data = client.get_account(&manager.vault).unwrap();
-    let auction: AuctionData = try_from_slice_unchecked(&auction_data.data).unwrap();
+    let auction = AuctionData::from_slice(&auction_data.data).unwrap();
     let bid: BidderMetadata = try_from_slice_unchecked(&bidding_metadata.data).unwrap();
-    let vault: Vault = try_from_slice_unchecked(&vault_data.data).unwrap();
+    let vault = Vault::from_slice(&vault_data.data).unwrap();
 
     let redemption_path = [
         spl_metaplex::state::PREFIX.as_bytes(),
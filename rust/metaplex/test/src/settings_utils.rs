@@ -8,7 +8,7 @@ use {
 };
 #[derive(Serialize, Deserialize, Clone)]
 pub struct JsonWinningConfig {
-    pub safety_deposit_box_index: u8,
+    pub safety_deposit_box_index: u16,
     pub amount: u8,
     pub edition_type: u8,
     pub desired_supply: Option<u64>,
@@ -18,7 +18,7 @@ pub struct JsonWinningConfig {
 
 #[derive(Serialize, Deserialize)]
 pub struct JsonOpenEditionConfig {
-    pub safety_deposit_box_index: u8,
+    pub safety_deposit_box_index: u16,
     pub mint: Option<String>,
     pub account: Option<String>,
 }
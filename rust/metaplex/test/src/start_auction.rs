@@ -3,7 +3,6 @@ use {
     clap::ArgMatches,
     solana_clap_utils::input_parsers::pubkey_of,
     solana_client::rpc_client::RpcClient,
-    solana_program::borsh::try_from_slice_unchecked,
     solana_sdk::{
         pubkey::Pubkey,
         signature::{read_keypair_file, Keypair, Signer},
@@ -27,7 +26,7 @@ pub fn send_start_auction(app_matches: &ArgMatches, payer: Keypair, client: RpcC
     let auction_manager_key = pubkey_of(app_matches, "auction_manager").unwrap();
 
     let account = client.get_account(&auction_manager_key).unwrap();
-    let manager: AuctionManager = try_from_slice_unchecked(&account.data).unwrap();
+    let manager = AuctionManager::from_slice(&account.data).unwrap();
     let instructions = [create_start_auction_instruction(
         program_key,
         auction_manager_key,
@@ -54,7 +54,7 @@ pub fn validate_safety_deposits(app_matches: &ArgMatches, payer: Keypair, client
     let mint_map = parse_metadata_keys(&(auction_manager_key.to_string() + ".json"));
 
     let account = client.get_account(&auction_manager_key).unwrap();
-    let manager: AuctionManager = try_from_slice_unchecked(&account.data).unwrap();
+    let manager = AuctionManager::from_slice(&account.data).unwrap();
     let all_vault_accounts = client.get_program_accounts(&vault_program_key).unwrap();
 
     let mut safety_deposits = HashMap::new();
@@ -68,7 +68,7 @@ pub fn validate_safety_deposits(app_matches: &ArgMatches, payer: Keypair, client
             let pubkey_arr = array_ref![obj.data, 1, 32];
             let pubkey = Pubkey::new_from_array(*pubkey_arr);
             if pubkey == manager.vault {
-                let safety_deposit: SafetyDepositBox = try_from_slice_unchecked(&obj.data).unwrap();
+                let safety_deposit = SafetyDepositBox::from_slice(&obj.data).unwrap();
                 safety_deposits.insert(safety_deposit.order, (safety_deposit, *obj_key));
             }
         }
@@ -341,7 +341,7 @@ pub fn activate_vault(
 
     let number_of_shares: u64 = 0;
     let vault_account = client.get_account(&vault_key).unwrap();
-    let vault: Vault = try_from_slice_unchecked(&vault_account.data).unwrap();
+    let vault = Vault::from_slice(&vault_account.data).unwrap();
 
     let seeds = &[
         spl_token_vault::state::PREFIX.as_bytes(),
@@ -366,7 +366,7 @@ pub fn activate_vault(
     transaction.sign(&signers, recent_blockhash);
     client.send_and_confirm_transaction(&transaction).unwrap();
     let updated_vault_data = client.get_account(&vault_key).unwrap();
-    let updated_vault: Vault = try_from_slice_unchecked(&updated_vault_data.data).unwrap();
+    let updated_vault = Vault::from_slice(&updated_vault_data.data).unwrap();
     if updated_vault.state == VaultState::Active {
         println!("Activated vault.");
         Some(*vault_key)
@@ -388,7 +388,7 @@ pub fn combine_vault(
 
     let amount_of_money = 0;
     let vault_account = client.get_account(&vault_key).unwrap();
-    let vault: Vault = try_from_slice_unchecked(&vault_account.data).unwrap();
+    let vault = Vault::from_slice(&vault_account.data).unwrap();
     let external_price_account = client.get_account(&vault.pricing_lookup_address).unwrap();
     let external: ExternalPriceAccount =
         try_from_slice_unchecked(&external_price_account.data).unwrap();
@@ -495,6 +495,11 @@ pub fn combine_vault(
         transfer_authority.pubkey(),
         uncirculated_burn_authority,
         vault.pricing_lookup_address,
+        vault.redemption_window.map(|_| *new_vault_authority),
+        match vault.fee_basis_points {
+            0 => None,
+            _ => Some(vault.fee_recipient),
+        },
     ));
 
     let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
@@ -503,7 +508,7 @@ pub fn combine_vault(
     transaction.sign(&signers, recent_blockhash);
     client.send_and_confirm_transaction(&transaction).unwrap();
     let updated_vault_data = client.get_account(&vault_key).unwrap();
-    let updated_vault: Vault = try_from_slice_unchecked(&updated_vault_data.data).unwrap();
+    let updated_vault = Vault::from_slice(&updated_vault_data.data).unwrap();
     if updated_vault.state == VaultState::Combined {
         println!("Combined vault.");
         Some(*vault_key)
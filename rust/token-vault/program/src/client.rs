@@ -0,0 +1,592 @@
+//! Read-only helpers for off chain clients and indexers: account decoding, PDA derivation and
+//! labeling of instruction accounts. Only built with the `client` feature, and never for BPF.
+
+use {
+    crate::{
+        instruction::VaultInstruction,
+        state::{
+            ExternalPriceAccount, SafetyDepositBox, Vault, EXTERNAL_ACCOUNT_KEY, PREFIX,
+            SAFETY_DEPOSIT_KEY, SAFETY_DEPOSIT_V2_KEY, VAULT_KEY, VAULT_V2_KEY,
+        },
+    },
+    borsh::BorshDeserialize,
+    solana_program::{
+        borsh::try_from_slice_unchecked, program_error::ProgramError, pubkey::Pubkey,
+        system_program,
+    },
+};
+
+/// Any account owned by the vault program, discriminated by its key byte
+pub enum VaultAccount {
+    Vault(Vault),
+    SafetyDepositBox(SafetyDepositBox),
+    ExternalPriceAccount(ExternalPriceAccount),
+}
+
+/// Decodes account data owned by the vault program, legacy layouts included. A zeroed account
+/// allocated for a vault but not yet initialized also starts with VAULT_KEY, and is rejected.
+pub fn decode_account(data: &[u8]) -> Result<VaultAccount, ProgramError> {
+    match data.first() {
+        Some(&VAULT_KEY) | Some(&VAULT_V2_KEY) => {
+            let vault = Vault::from_slice(data)?;
+            // InitVault always records the token program
+            if vault.token_program == Pubkey::default() {
+                return Err(ProgramError::UninitializedAccount);
+            }
+            Ok(VaultAccount::Vault(vault))
+        }
+        Some(&SAFETY_DEPOSIT_KEY) | Some(&SAFETY_DEPOSIT_V2_KEY) => Ok(
+            VaultAccount::SafetyDepositBox(SafetyDepositBox::from_slice(data)?),
+        ),
+        Some(&EXTERNAL_ACCOUNT_KEY) => Ok(VaultAccount::ExternalPriceAccount(
+            try_from_slice_unchecked(data)?,
+        )),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// PDA that owns every store, treasury and fraction mint of the program, seed [PREFIX, program_id]
+pub fn find_vault_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref()], program_id)
+}
+
+/// Safety deposit box of a token mint in a vault, seed [PREFIX, vault, token_mint]
+pub fn find_safety_deposit_box_address(
+    program_id: &Pubkey,
+    vault: &Pubkey,
+    token_mint: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PREFIX.as_bytes(), vault.as_ref(), token_mint.as_ref()],
+        program_id,
+    )
+}
+
+/// Native safety deposit box of a vault, seed [PREFIX, vault, system_program_id]
+pub fn find_native_safety_deposit_box_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+    find_safety_deposit_box_address(program_id, vault, &system_program::id())
+}
+
+/// An instruction with each of its accounts labeled with the role it plays
+pub struct DecodedInstruction {
+    pub name: &'static str,
+    pub instruction: VaultInstruction,
+    /// Accounts past the ones the instruction knows about are labeled "remaining"
+    pub accounts: Vec<(&'static str, Pubkey)>,
+}
+
+/// Decodes vault instruction data and labels the accounts it was given, following the account
+/// orders documented on VaultInstruction.
+pub fn decode_instruction(
+    data: &[u8],
+    accounts: &[Pubkey],
+) -> Result<DecodedInstruction, ProgramError> {
+    let instruction = VaultInstruction::try_from_slice(data)?;
+    let (name, labels, required) = instruction_layout(&instruction, accounts.len());
+
+    if accounts.len() < required {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let accounts = accounts
+        .iter()
+        .enumerate()
+        .map(|(i, key)| (*labels.get(i).unwrap_or(&"remaining"), *key))
+        .collect();
+
+    Ok(DecodedInstruction {
+        name,
+        instruction,
+        accounts,
+    })
+}
+
+/// Name, account labels and the number of accounts that must be present for an instruction
+fn instruction_layout(
+    instruction: &VaultInstruction,
+    account_count: usize,
+) -> (&'static str, &'static [&'static str], usize) {
+    let (name, labels): (&'static str, &'static [&'static str]) = match instruction {
+        VaultInstruction::InitVault(_) => (
+            "InitVault",
+            &[
+                "fraction_mint",
+                "redeem_treasury",
+                "fraction_treasury",
+                "vault",
+                "authority",
+                "pricing_lookup_address",
+                "token_program",
+                "rent",
+            ],
+        ),
+        VaultInstruction::AddTokenToInactiveVault(_) => (
+            "AddTokenToInactiveVault",
+            &[
+                "safety_deposit_box",
+                "token_account",
+                "store",
+                "vault",
+                "vault_authority",
+                "payer",
+                "transfer_authority",
+                "token_program",
+                "rent",
+                "system_program",
+            ],
+        ),
+        VaultInstruction::ActivateVault(_) => (
+            "ActivateVault",
+            &[
+                "vault",
+                "fraction_mint",
+                "fraction_treasury",
+                "fraction_mint_authority",
+                "vault_authority",
+                "token_program",
+            ],
+        ),
+        VaultInstruction::CombineVault if account_count != 13 => (
+            "CombineVault",
+            &[
+                "vault",
+                "outstanding_shares",
+                "payment_account",
+                "fraction_mint",
+                "fraction_treasury",
+                "redeem_treasury",
+                "new_vault_authority",
+                "vault_authority",
+                "transfer_authority",
+                "fraction_burn_authority",
+                "external_pricing",
+                "token_program",
+                "clock",
+                "redemption_recipient",
+                "fee_recipient",
+            ],
+        ),
+        // Without a redemption window the fee recipient directly follows the token program
+        VaultInstruction::CombineVault => (
+            "CombineVault",
+            &[
+                "vault",
+                "outstanding_shares",
+                "payment_account",
+                "fraction_mint",
+                "fraction_treasury",
+                "redeem_treasury",
+                "new_vault_authority",
+                "vault_authority",
+                "transfer_authority",
+                "fraction_burn_authority",
+                "external_pricing",
+                "token_program",
+                "fee_recipient",
+            ],
+        ),
+        VaultInstruction::RedeemShares => (
+            "RedeemShares",
+            &[
+                "outstanding_shares",
+                "destination",
+                "fraction_mint",
+                "redeem_treasury",
+                "transfer_authority",
+                "burn_authority",
+                "vault",
+                "token_program",
+                "rent",
+                "fee_recipient",
+            ],
+        ),
+        VaultInstruction::WithdrawTokenFromSafetyDepositBox(_) => (
+            "WithdrawTokenFromSafetyDepositBox",
+            &[
+                "destination",
+                "safety_deposit_box",
+                "store",
+                "vault",
+                "fraction_mint",
+                "vault_authority",
+                "transfer_authority",
+                "token_program",
+                "rent",
+            ],
+        ),
+        VaultInstruction::MintFractionalShares(_) => (
+            "MintFractionalShares",
+            &[
+                "fraction_treasury",
+                "fraction_mint",
+                "vault",
+                "mint_authority",
+                "vault_authority",
+                "token_program",
+            ],
+        ),
+        VaultInstruction::WithdrawSharesFromTreasury(_) => (
+            "WithdrawSharesFromTreasury",
+            &[
+                "destination",
+                "fraction_treasury",
+                "vault",
+                "transfer_authority",
+                "vault_authority",
+                "token_program",
+                "rent",
+            ],
+        ),
+        VaultInstruction::AddSharesToTreasury(_) => (
+            "AddSharesToTreasury",
+            &[
+                "source",
+                "fraction_treasury",
+                "vault",
+                "transfer_authority",
+                "vault_authority",
+                "token_program",
+            ],
+        ),
+        VaultInstruction::UpdateExternalPriceAccount(_) => {
+            ("UpdateExternalPriceAccount", &["external_price_account"])
+        }
+        VaultInstruction::AddNativeToInactiveVault(_) => (
+            "AddNativeToInactiveVault",
+            &[
+                "safety_deposit_box",
+                "vault",
+                "vault_authority",
+                "payer",
+                "rent",
+                "system_program",
+            ],
+        ),
+        VaultInstruction::WithdrawNativeFromSafetyDepositBox(_) => (
+            "WithdrawNativeFromSafetyDepositBox",
+            &[
+                "destination",
+                "safety_deposit_box",
+                "vault",
+                "fraction_mint",
+                "vault_authority",
+            ],
+        ),
+        VaultInstruction::MigrateVault => ("MigrateVault", &["vault"]),
+        VaultInstruction::SetRedemptionWindow(_) => {
+            ("SetRedemptionWindow", &["vault", "vault_authority"])
+        }
+        VaultInstruction::SweepUnclaimedSafetyDepositBox => (
+            "SweepUnclaimedSafetyDepositBox",
+            &[
+                "destination",
+                "safety_deposit_box",
+                "store",
+                "vault",
+                "fraction_mint",
+                "caller",
+                "caller_shares",
+                "transfer_authority",
+                "token_program",
+                "clock",
+            ],
+        ),
+        VaultInstruction::SetVaultFee(_) => (
+            "SetVaultFee",
+            &["vault", "vault_authority", "fee_recipient", "token_program"],
+        ),
+    };
+
+    let required = match instruction {
+        // Redemption window and fee accounts are only passed for vaults that use them
+        VaultInstruction::CombineVault => 12,
+        VaultInstruction::RedeemShares => 9,
+        _ => labels.len(),
+    };
+
+    (name, labels, required)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            instruction::{create_combine_vault_instruction, create_set_vault_fee_instruction},
+            state::{
+                SafetyDepositBoxV1, VaultState, VaultV1, BASE_VAULT_SIZE,
+                MAX_EXTERNAL_ACCOUNT_SIZE, MAX_SAFETY_DEPOSIT_SIZE, MAX_SAFETY_DEPOSIT_V1_SIZE,
+                MAX_VAULT_SIZE,
+            },
+        },
+        borsh::BorshSerialize,
+        solana_program::sysvar,
+    };
+
+    fn legacy_vault() -> VaultV1 {
+        VaultV1 {
+            key: VAULT_KEY,
+            token_program: spl_token::id(),
+            fraction_mint: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            fraction_treasury: Pubkey::new_unique(),
+            redeem_treasury: Pubkey::new_unique(),
+            allow_further_share_creation: false,
+            pricing_lookup_address: Pubkey::new_unique(),
+            token_type_count: 3,
+            state: VaultState::Active,
+            locked_price_per_share: 0,
+        }
+    }
+
+    fn decoded_vault(data: &[u8]) -> Vault {
+        match decode_account(data).unwrap() {
+            VaultAccount::Vault(vault) => vault,
+            _ => panic!("expected a vault"),
+        }
+    }
+
+    fn decoded_box(data: &[u8]) -> SafetyDepositBox {
+        match decode_account(data).unwrap() {
+            VaultAccount::SafetyDepositBox(safety_deposit) => safety_deposit,
+            _ => panic!("expected a safety deposit box"),
+        }
+    }
+
+    #[test]
+    fn test_decode_vaults() {
+        let legacy = legacy_vault();
+        let mut data = legacy.try_to_vec().unwrap();
+        data.resize(MAX_VAULT_SIZE, 0);
+        let vault = decoded_vault(&data);
+        assert_eq!(vault.key, VAULT_V2_KEY);
+        assert_eq!(vault.fraction_mint, legacy.fraction_mint);
+        assert_eq!(vault.token_type_count, 3);
+        assert!(vault.state == VaultState::Active);
+
+        let mut current = vault;
+        current.token_type_count = 300;
+        current.fee_basis_points = 100;
+        let mut data = current.try_to_vec().unwrap();
+        data.resize(MAX_VAULT_SIZE, 0);
+        let vault = decoded_vault(&data);
+        assert_eq!(vault.token_type_count, 300);
+        assert_eq!(vault.fee_basis_points, 100);
+
+        // Vaults allocated before the redemption and fee fields read them back as unset.
+        let vault = decoded_vault(&data[..BASE_VAULT_SIZE]);
+        assert_eq!(vault.token_type_count, 300);
+        assert_eq!(vault.fee_basis_points, 0);
+        assert_eq!(vault.redemption_window, None);
+    }
+
+    #[test]
+    fn test_decode_uninitialized_accounts() {
+        assert_eq!(
+            decode_account(&[0; MAX_VAULT_SIZE]).err(),
+            Some(ProgramError::UninitializedAccount)
+        );
+        assert_eq!(
+            decode_account(&[0; BASE_VAULT_SIZE]).err(),
+            Some(ProgramError::UninitializedAccount)
+        );
+        assert_eq!(
+            decode_account(&[]).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            decode_account(&[SAFETY_DEPOSIT_V2_KEY + 1; 8]).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_decode_safety_deposit_boxes() {
+        let legacy = SafetyDepositBoxV1 {
+            key: SAFETY_DEPOSIT_KEY,
+            vault: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            store: Pubkey::new_unique(),
+            order: 7,
+            amount: 500,
+        };
+        let data = legacy.try_to_vec().unwrap();
+        assert_eq!(data.len(), MAX_SAFETY_DEPOSIT_V1_SIZE);
+        let safety_deposit = decoded_box(&data);
+        assert_eq!(safety_deposit.key, SAFETY_DEPOSIT_KEY);
+        assert_eq!(safety_deposit.token_mint, legacy.token_mint);
+        assert_eq!(safety_deposit.order, 7);
+        assert_eq!(safety_deposit.amount, 500);
+
+        let current = SafetyDepositBox {
+            key: SAFETY_DEPOSIT_V2_KEY,
+            order: 300,
+            ..safety_deposit
+        };
+        let data = current.try_to_vec().unwrap();
+        assert_eq!(data.len(), MAX_SAFETY_DEPOSIT_SIZE);
+        let safety_deposit = decoded_box(&data);
+        assert_eq!(safety_deposit.key, SAFETY_DEPOSIT_V2_KEY);
+        assert_eq!(safety_deposit.order, 300);
+        assert_eq!(safety_deposit.amount, 500);
+    }
+
+    #[test]
+    fn test_decode_external_price_account() {
+        let price_mint = Pubkey::new_unique();
+        let data = ExternalPriceAccount {
+            key: EXTERNAL_ACCOUNT_KEY,
+            price_per_share: 5,
+            price_mint,
+            allowed_to_combine: true,
+        }
+        .try_to_vec()
+        .unwrap();
+        assert_eq!(data.len(), MAX_EXTERNAL_ACCOUNT_SIZE);
+        match decode_account(&data).unwrap() {
+            VaultAccount::ExternalPriceAccount(external_price) => {
+                assert_eq!(external_price.price_per_share, 5);
+                assert_eq!(external_price.price_mint, price_mint);
+                assert!(external_price.allowed_to_combine);
+            }
+            _ => panic!("expected an external price account"),
+        }
+    }
+
+    #[test]
+    fn test_decode_instruction() {
+        let program_id = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let fee_recipient = Pubkey::new_unique();
+        let instruction =
+            create_set_vault_fee_instruction(program_id, vault, authority, fee_recipient, 250);
+        let keys: Vec<Pubkey> = instruction.accounts.iter().map(|a| a.pubkey).collect();
+
+        let decoded = decode_instruction(&instruction.data, &keys).unwrap();
+        assert_eq!(decoded.name, "SetVaultFee");
+        match &decoded.instruction {
+            VaultInstruction::SetVaultFee(args) => assert_eq!(args.fee_basis_points, 250),
+            _ => panic!("expected SetVaultFee"),
+        }
+        assert_eq!(
+            decoded.accounts,
+            vec![
+                ("vault", vault),
+                ("vault_authority", authority),
+                ("fee_recipient", fee_recipient),
+                ("token_program", spl_token::id()),
+            ]
+        );
+        assert_eq!(decoded.instruction.try_to_vec().unwrap(), instruction.data);
+
+        // Extra accounts are kept, missing ones are not.
+        let mut extra = keys.clone();
+        extra.push(Pubkey::new_unique());
+        let decoded = decode_instruction(&instruction.data, &extra).unwrap();
+        assert_eq!(decoded.accounts[4], ("remaining", extra[4]));
+        assert_eq!(
+            decode_instruction(&instruction.data, &keys[..3]).err(),
+            Some(ProgramError::NotEnoughAccountKeys)
+        );
+    }
+
+    #[test]
+    fn test_decode_combine_vault() {
+        let fee_recipient = Pubkey::new_unique();
+        let redemption_recipient = Pubkey::new_unique();
+        let combine = |redemption_recipient, fee_recipient| {
+            let instruction = create_combine_vault_instruction(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                redemption_recipient,
+                fee_recipient,
+            );
+            let keys: Vec<Pubkey> = instruction.accounts.iter().map(|a| a.pubkey).collect();
+            decode_instruction(&instruction.data, &keys)
+                .unwrap()
+                .accounts
+                .split_off(12)
+        };
+
+        assert_eq!(combine(None, None), vec![]);
+        assert_eq!(
+            combine(None, Some(fee_recipient)),
+            vec![("fee_recipient", fee_recipient)]
+        );
+        assert_eq!(
+            combine(Some(redemption_recipient), None),
+            vec![
+                ("clock", sysvar::clock::id()),
+                ("redemption_recipient", redemption_recipient)
+            ]
+        );
+        assert_eq!(
+            combine(Some(redemption_recipient), Some(fee_recipient)),
+            vec![
+                ("clock", sysvar::clock::id()),
+                ("redemption_recipient", redemption_recipient),
+                ("fee_recipient", fee_recipient)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pda_seeds() {
+        let program_id = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        let token_mint = Pubkey::new_unique();
+
+        // Same seeds the processor derives and signs with.
+        let (authority, bump) = find_vault_authority_address(&program_id);
+        assert_eq!(
+            Pubkey::create_program_address(
+                &[PREFIX.as_bytes(), program_id.as_ref(), &[bump]],
+                &program_id
+            )
+            .unwrap(),
+            authority
+        );
+
+        let (safety_deposit_box, bump) =
+            find_safety_deposit_box_address(&program_id, &vault, &token_mint);
+        assert_eq!(
+            Pubkey::create_program_address(
+                &[
+                    PREFIX.as_bytes(),
+                    vault.as_ref(),
+                    token_mint.as_ref(),
+                    &[bump]
+                ],
+                &program_id
+            )
+            .unwrap(),
+            safety_deposit_box
+        );
+
+        let (native_box, bump) = find_native_safety_deposit_box_address(&program_id, &vault);
+        assert_eq!(
+            Pubkey::create_program_address(
+                &[
+                    PREFIX.as_bytes(),
+                    vault.as_ref(),
+                    system_program::id().as_ref(),
+                    &[bump]
+                ],
+                &program_id
+            )
+            .unwrap(),
+            native_box
+        );
+    }
+}
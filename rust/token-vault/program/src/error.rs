@@ -212,6 +212,62 @@ pub enum VaultError {
     /// Store has less than amount desired
     #[error("Store has less than amount desired")]
     StoreLessThanAmount,
+
+    /// Amount must be greater than zero
+    #[error("Amount must be greater than zero")]
+    AmountMustBeGreaterThanZero,
+
+    /// This safety deposit box holds lamports, use the native withdrawal instruction
+    #[error("This safety deposit box holds lamports, use the native withdrawal instruction")]
+    SafetyDepositBoxIsNative,
+
+    /// This safety deposit box holds tokens, use the token withdrawal instruction
+    #[error("This safety deposit box holds tokens, use the token withdrawal instruction")]
+    SafetyDepositBoxIsNotNative,
+
+    /// Vault account is too small to hold these settings
+    #[error("Vault account is too small to hold these settings")]
+    VaultAccountTooSmall,
+
+    /// This vault has no redemption deadline
+    #[error("This vault has no redemption deadline")]
+    VaultHasNoRedemptionDeadline,
+
+    /// The redemption deadline has not passed yet
+    #[error("The redemption deadline has not passed yet")]
+    RedemptionDeadlineNotPassed,
+
+    /// Destination does not belong to the vault's redemption recipient
+    #[error("Destination does not belong to the vault's redemption recipient")]
+    DestinationMustBelongToRedemptionRecipient,
+
+    /// Caller must be the vault authority or hold fractional shares
+    #[error("Caller must be the vault authority or hold fractional shares")]
+    CallerMustBeAuthorityOrShareholder,
+
+    /// The redemption window can only be set while the vault is inactive
+    #[error("The redemption window can only be set while the vault is inactive")]
+    RedemptionWindowOnlyWhileInactive,
+
+    /// Redemption window must not be negative
+    #[error("Redemption window must not be negative")]
+    InvalidRedemptionWindow,
+
+    /// Fee basis points exceed the maximum
+    #[error("Fee basis points exceed the maximum")]
+    FeeTooHigh,
+
+    /// Fee recipient does not match the one set on the vault
+    #[error("Fee recipient does not match the one set on the vault")]
+    FeeRecipientMismatch,
+
+    /// Fee recipient needs to match the redeem treasury mint
+    #[error("Fee recipient needs to match the redeem treasury mint")]
+    FeeRecipientMintMismatch,
+
+    /// The fee can only be set while the vault is inactive
+    #[error("The fee can only be set while the vault is inactive")]
+    FeeOnlyWhileInactive,
 }
 
 impl PrintProgramError for VaultError {
@@ -2,6 +2,7 @@ use {
     crate::state::{ExternalPriceAccount, EXTERNAL_ACCOUNT_KEY},
     borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{
+        clock::UnixTimestamp,
         instruction::{AccountMeta, Instruction},
         pubkey::Pubkey,
         sysvar,
@@ -26,6 +27,20 @@ pub struct NumberOfShareArgs {
     pub number_of_shares: u64,
 }
 
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct RedemptionWindowArgs {
+    /// Seconds after combination before unclaimed safety deposit contents can be swept, None to disable
+    pub redemption_window: Option<UnixTimestamp>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct VaultFeeArgs {
+    /// Fee in basis points of the buyout and of redemptions, 0 to disable, at most MAX_FEE_BASIS_POINTS
+    pub fee_basis_points: u16,
+}
+
 /// Instructions supported by the Fraction program.
 #[derive(BorshSerialize, BorshDeserialize, Clone)]
 pub enum VaultInstruction {
@@ -79,6 +94,10 @@ pub enum VaultInstruction {
     ///   9. `[]` PDA-based Burn authority for the fraction treasury account containing the uncirculated shares seed [PREFIX, program_id]
     ///   10. `[]` External pricing lookup address
     ///   11. `[]` Token program
+    ///   12. `[]` Clock sysvar, only if the vault has a redemption window
+    ///   13. `[]` Redemption recipient that receives contents swept after the redemption deadline, only if the vault has a redemption window
+    ///   14. `[writable]` Fee recipient set on the vault, only if the vault charges a fee, which is charged to the payment account on top of the buyout.
+    ///       Comes right after the token program (12.) when the vault has no redemption window
     CombineVault,
 
     /// If in the combine state, shareholders can hit this endpoint to burn shares in exchange for monies from the treasury.
@@ -89,9 +108,10 @@ pub enum VaultInstruction {
     ///   3. `[writable]` Redeem treasury account
     ///   4. `[]` PDA-based Transfer authority for the transfer of proceeds from redeem treasury to destination seed [PREFIX, program_id]
     ///   5. `[signer]` Burn authority for the burning of your shares
-    ///   6. `[]` Combined token vault
+    ///   6. `[writable]` Combined token vault
     ///   7. `[]` Token program
     ///   8. `[]` Rent sysvar
+    ///   9. `[writable]` Fee recipient set on the vault, only if the vault charges a fee, which is taken out of the proceeds
     RedeemShares,
 
     /// If in combine state, authority on vault can hit this to withdrawal some of a token type from a safety deposit box.
@@ -140,6 +160,61 @@ pub enum VaultInstruction {
     /// Useful for testing purposes, and the CLI makes use of it as well so that you can verify logic.
     ///   0. `[writable]` External price account
     UpdateExternalPriceAccount(ExternalPriceAccount),
+
+    /// Add lamports to a inactive token vault. The lamports are held directly in the safety deposit box
+    /// account, which records the system program id as its token mint and itself as its store.
+    /// Its address is derived from the vault alone, so a vault holds at most one native box and all of
+    /// its lamports have to be added in a single call, a second call fails to allocate the box again.
+    ///   0. `[writable]` Uninitialized safety deposit box account address (will be created and allocated by this endpoint)
+    ///                   Address should be pda with seed of [PREFIX, vault_address, system_program_id]
+    ///   1. `[writable]` Initialized inactive fractionalized token vault
+    ///   2. `[signer]` Authority on the vault
+    ///   3. `[writable, signer]` Payer, funds both the box rent and the deposited lamports
+    ///   4. `[]` Rent sysvar
+    ///   5. `[]` System account sysvar
+    AddNativeToInactiveVault(AmountArgs),
+
+    /// If in combine state, authority on vault can hit this to withdrawal lamports from a native safety deposit box.
+    /// Once fractional supply is zero and all boxes have been emptied this action will take vault to Deactivated
+    ///   0. `[writable]` Destination account for the lamports being withdrawn
+    ///   1. `[writable]` The native safety deposit box account
+    ///   2. `[writable]` The initialized combined token vault
+    ///   3. `[]` Fraction mint
+    ///   4. `[signer]` Authority of vault
+    WithdrawNativeFromSafetyDepositBox(AmountArgs),
+
+    /// Rewrites a vault created with the original u8 token_type_count layout into the current layout.
+    /// Permissionless, and a no-op for vaults already on the current layout.
+    ///   0. `[writable]` Vault
+    MigrateVault,
+
+    /// Sets how long after combination the new authority has to withdraw the safety deposit contents
+    /// before anyone holding shares can sweep them to the redemption recipient chosen at combination.
+    ///   0. `[writable]` Initialized inactive fractionalized token vault
+    ///   1. `[signer]` Authority on the vault
+    SetRedemptionWindow(RedemptionWindowArgs),
+
+    /// Once the redemption deadline of a combined vault has passed, the vault authority or any shareholder
+    /// can hit this to move the entire contents of a safety deposit box to the redemption recipient.
+    /// Sweeping the last box leaves the vault Combined until the last share is redeemed, as with a withdrawal.
+    ///   0. `[writable]` Destination token account owned by the redemption recipient, or the recipient itself for a native box
+    ///   1. `[writable]` The safety deposit box account
+    ///   2. `[writable]` The store key on the safety deposit box account, the box itself for a native box
+    ///   3. `[writable]` The initialized combined token vault
+    ///   4. `[]` Fraction mint
+    ///   5. `[signer]` Caller, either the authority of the vault or a shareholder
+    ///   6. `[]` Token account holding the caller's fractional shares, ignored if the caller is the vault authority
+    ///   7. `[]` PDA-based Transfer authority to move the tokens from the store to the destination seed [PREFIX, program_id]
+    ///   8. `[]` Token program
+    ///   9. `[]` Clock sysvar
+    SweepUnclaimedSafetyDepositBox,
+
+    /// Sets the fee charged on combination and share redemption, and the token account it is paid to.
+    ///   0. `[writable]` Initialized inactive fractionalized token vault
+    ///   1. `[signer]` Authority on the vault
+    ///   2. `[]` Fee recipient token account, of the redeem treasury mint
+    ///   3. `[]` Token program
+    SetVaultFee(VaultFeeArgs),
 }
 
 /// Creates an InitVault instruction
@@ -272,23 +347,33 @@ pub fn create_combine_vault_instruction(
     paying_transfer_authority: Pubkey,
     uncirculated_burn_authority: Pubkey,
     external_pricing_account: Pubkey,
+    redemption_recipient: Option<Pubkey>,
+    fee_recipient: Option<Pubkey>,
 ) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(vault, false),
+        AccountMeta::new(outstanding_share_token_account, false),
+        AccountMeta::new(paying_token_account, false),
+        AccountMeta::new(fraction_mint, false),
+        AccountMeta::new(fraction_treasury, false),
+        AccountMeta::new(redeem_treasury, false),
+        AccountMeta::new(new_authority, false),
+        AccountMeta::new_readonly(vault_authority, true),
+        AccountMeta::new_readonly(paying_transfer_authority, true),
+        AccountMeta::new_readonly(uncirculated_burn_authority, false),
+        AccountMeta::new_readonly(external_pricing_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    if let Some(redemption_recipient) = redemption_recipient {
+        accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+        accounts.push(AccountMeta::new_readonly(redemption_recipient, false));
+    }
+    if let Some(fee_recipient) = fee_recipient {
+        accounts.push(AccountMeta::new(fee_recipient, false));
+    }
     Instruction {
         program_id,
-        accounts: vec![
-            AccountMeta::new(vault, false),
-            AccountMeta::new(outstanding_share_token_account, false),
-            AccountMeta::new(paying_token_account, false),
-            AccountMeta::new(fraction_mint, false),
-            AccountMeta::new(fraction_treasury, false),
-            AccountMeta::new(redeem_treasury, false),
-            AccountMeta::new(new_authority, false),
-            AccountMeta::new_readonly(vault_authority, true),
-            AccountMeta::new_readonly(paying_transfer_authority, true),
-            AccountMeta::new_readonly(uncirculated_burn_authority, false),
-            AccountMeta::new_readonly(external_pricing_account, false),
-            AccountMeta::new_readonly(spl_token::id(), false),
-        ],
+        accounts,
         data: VaultInstruction::CombineVault.try_to_vec().unwrap(),
     }
 }
@@ -304,21 +389,51 @@ pub fn create_redeem_shares_instruction(
     transfer_authority: Pubkey,
     burn_authority: Pubkey,
     vault: Pubkey,
+    fee_recipient: Option<Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(outstanding_shares_account, false),
+        AccountMeta::new(proceeds_account, false),
+        AccountMeta::new(fraction_mint, false),
+        AccountMeta::new(redeem_treasury, false),
+        AccountMeta::new_readonly(transfer_authority, false),
+        AccountMeta::new_readonly(burn_authority, true),
+        AccountMeta::new(vault, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+    if let Some(fee_recipient) = fee_recipient {
+        accounts.push(AccountMeta::new(fee_recipient, false));
+    }
+    Instruction {
+        program_id,
+        accounts,
+        data: VaultInstruction::RedeemShares.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates an AddNativeToInactiveVault instruction, only one native box can be added per vault
+pub fn create_add_native_to_inactive_vault_instruction(
+    program_id: Pubkey,
+    safety_deposit_box: Pubkey,
+    vault: Pubkey,
+    vault_authority: Pubkey,
+    payer: Pubkey,
+    amount: u64,
 ) -> Instruction {
     Instruction {
         program_id,
         accounts: vec![
-            AccountMeta::new(outstanding_shares_account, false),
-            AccountMeta::new(proceeds_account, false),
-            AccountMeta::new(fraction_mint, false),
-            AccountMeta::new(redeem_treasury, false),
-            AccountMeta::new_readonly(transfer_authority, false),
-            AccountMeta::new_readonly(burn_authority, true),
-            AccountMeta::new_readonly(vault, false),
-            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(safety_deposit_box, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(vault_authority, true),
+            AccountMeta::new(payer, true),
             AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
         ],
-        data: VaultInstruction::RedeemShares.try_to_vec().unwrap(),
+        data: VaultInstruction::AddNativeToInactiveVault(AmountArgs { amount })
+            .try_to_vec()
+            .unwrap(),
     }
 }
 
@@ -353,6 +468,113 @@ pub fn create_withdraw_tokens_instruction(
     }
 }
 
+pub fn create_withdraw_native_instruction(
+    program_id: Pubkey,
+    destination: Pubkey,
+    safety_deposit_box: Pubkey,
+    vault: Pubkey,
+    fraction_mint: Pubkey,
+    vault_authority: Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(destination, false),
+            AccountMeta::new(safety_deposit_box, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(fraction_mint, false),
+            AccountMeta::new_readonly(vault_authority, true),
+        ],
+        data: VaultInstruction::WithdrawNativeFromSafetyDepositBox(AmountArgs { amount })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Creates a MigrateVault instruction
+pub fn create_migrate_vault_instruction(program_id: Pubkey, vault: Pubkey) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![AccountMeta::new(vault, false)],
+        data: VaultInstruction::MigrateVault.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a SetRedemptionWindow instruction
+pub fn create_set_redemption_window_instruction(
+    program_id: Pubkey,
+    vault: Pubkey,
+    vault_authority: Pubkey,
+    redemption_window: Option<UnixTimestamp>,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(vault_authority, true),
+        ],
+        data: VaultInstruction::SetRedemptionWindow(RedemptionWindowArgs { redemption_window })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Creates a SetVaultFee instruction
+pub fn create_set_vault_fee_instruction(
+    program_id: Pubkey,
+    vault: Pubkey,
+    vault_authority: Pubkey,
+    fee_recipient: Pubkey,
+    fee_basis_points: u16,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(vault_authority, true),
+            AccountMeta::new_readonly(fee_recipient, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: VaultInstruction::SetVaultFee(VaultFeeArgs { fee_basis_points })
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Creates a SweepUnclaimedSafetyDepositBox instruction
+#[allow(clippy::too_many_arguments)]
+pub fn create_sweep_unclaimed_safety_deposit_box_instruction(
+    program_id: Pubkey,
+    destination: Pubkey,
+    safety_deposit_box: Pubkey,
+    store: Pubkey,
+    vault: Pubkey,
+    fraction_mint: Pubkey,
+    caller: Pubkey,
+    caller_shares: Pubkey,
+    transfer_authority: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(destination, false),
+            AccountMeta::new(safety_deposit_box, false),
+            AccountMeta::new(store, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(fraction_mint, false),
+            AccountMeta::new_readonly(caller, true),
+            AccountMeta::new_readonly(caller_shares, false),
+            AccountMeta::new_readonly(transfer_authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: VaultInstruction::SweepUnclaimedSafetyDepositBox
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn create_mint_shares_instruction(
     program_id: Pubkey,
@@ -1,5 +1,7 @@
 //! A Token Fraction program for the Solana blockchain.
 
+#[cfg(all(feature = "client", not(target_arch = "bpf")))]
+pub mod client;
 pub mod entrypoint;
 pub mod error;
 pub mod instruction;
@@ -3,8 +3,8 @@ use {
         error::VaultError,
         instruction::VaultInstruction,
         state::{
-            ExternalPriceAccount, SafetyDepositBox, Vault, VaultState, MAX_SAFETY_DEPOSIT_SIZE,
-            PREFIX, SAFETY_DEPOSIT_KEY, VAULT_KEY,
+            ExternalPriceAccount, SafetyDepositBox, Vault, VaultState, MAX_FEE_BASIS_POINTS,
+            MAX_SAFETY_DEPOSIT_SIZE, PREFIX, SAFETY_DEPOSIT_V2_KEY, VAULT_V2_KEY,
         },
         utils::{
             assert_initialized, assert_owned_by, assert_rent_exempt, assert_token_matching,
@@ -17,10 +17,13 @@ use {
     solana_program::{
         account_info::{next_account_info, AccountInfo},
         borsh::try_from_slice_unchecked,
+        clock::{Clock, UnixTimestamp},
         entrypoint::ProgramResult,
         msg,
+        program::invoke,
         pubkey::Pubkey,
         rent::Rent,
+        system_instruction, system_program,
         sysvar::Sysvar,
     },
     spl_token::state::{Account, Mint},
@@ -84,7 +87,266 @@ pub fn process_instruction(
                 args.allowed_to_combine,
             )
         }
+        VaultInstruction::AddNativeToInactiveVault(args) => {
+            msg!("Instruction: Add native to vault");
+            process_add_native_to_inactivated_vault(program_id, accounts, args.amount)
+        }
+        VaultInstruction::WithdrawNativeFromSafetyDepositBox(args) => {
+            msg!("Instruction: Withdraw native from safety deposit box");
+            process_withdraw_native_from_safety_deposit_box(program_id, accounts, args.amount)
+        }
+        VaultInstruction::MigrateVault => {
+            msg!("Instruction: Migrate Vault");
+            process_migrate_vault(program_id, accounts)
+        }
+        VaultInstruction::SetRedemptionWindow(args) => {
+            msg!("Instruction: Set Redemption Window");
+            process_set_redemption_window(program_id, accounts, args.redemption_window)
+        }
+        VaultInstruction::SweepUnclaimedSafetyDepositBox => {
+            msg!("Instruction: Sweep Unclaimed Safety Deposit Box");
+            process_sweep_unclaimed_safety_deposit_box(program_id, accounts)
+        }
+        VaultInstruction::SetVaultFee(args) => {
+            msg!("Instruction: Set Vault Fee");
+            process_set_vault_fee(program_id, accounts, args.fee_basis_points)
+        }
+    }
+}
+
+pub fn process_set_vault_fee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_basis_points: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let vault_info = next_account_info(account_info_iter)?;
+    let vault_authority_info = next_account_info(account_info_iter)?;
+    let fee_recipient_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    let mut vault = Vault::from_account_info(vault_info)?;
+
+    assert_owned_by(vault_info, program_id)?;
+    assert_token_matching(&vault, token_program_info)?;
+    assert_vault_authority_correct(&vault, vault_authority_info)?;
+
+    // Shareholders buy in knowing the fee, so it is fixed once the vault is activated.
+    if vault.state != VaultState::Inactive {
+        return Err(VaultError::FeeOnlyWhileInactive.into());
+    }
+
+    if fee_basis_points > MAX_FEE_BASIS_POINTS {
+        return Err(VaultError::FeeTooHigh.into());
+    }
+
+    if fee_basis_points > 0 {
+        assert_owned_by(fee_recipient_info, token_program_info.key)?;
+        let _fee_recipient: Account = assert_initialized(fee_recipient_info)?;
+    }
+
+    vault.fee_basis_points = fee_basis_points;
+    vault.fee_recipient = match fee_basis_points {
+        0 => Pubkey::default(),
+        _ => *fee_recipient_info.key,
+    };
+    vault.save(vault_info)?;
+
+    Ok(())
+}
+
+fn assert_fee_recipient_correct(
+    vault: &Vault,
+    fee_recipient_info: &AccountInfo,
+    redeem_treasury: &Account,
+) -> ProgramResult {
+    if *fee_recipient_info.key != vault.fee_recipient {
+        return Err(VaultError::FeeRecipientMismatch.into());
+    }
+
+    let fee_recipient: Account = assert_initialized(fee_recipient_info)?;
+    if fee_recipient.mint != redeem_treasury.mint {
+        return Err(VaultError::FeeRecipientMintMismatch.into());
+    }
+
+    Ok(())
+}
+
+pub fn process_set_redemption_window(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    redemption_window: Option<UnixTimestamp>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let vault_info = next_account_info(account_info_iter)?;
+    let vault_authority_info = next_account_info(account_info_iter)?;
+
+    let mut vault = Vault::from_account_info(vault_info)?;
+
+    assert_owned_by(vault_info, program_id)?;
+    assert_vault_authority_correct(&vault, vault_authority_info)?;
+
+    // Shareholders buy in knowing the window, so it is fixed once the vault is activated.
+    if vault.state != VaultState::Inactive {
+        return Err(VaultError::RedemptionWindowOnlyWhileInactive.into());
+    }
+
+    if let Some(window) = redemption_window {
+        if window < 0 {
+            return Err(VaultError::InvalidRedemptionWindow.into());
+        }
+    }
+
+    vault.redemption_window = redemption_window;
+    vault.save(vault_info)?;
+
+    Ok(())
+}
+
+pub fn process_sweep_unclaimed_safety_deposit_box(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let destination_info = next_account_info(account_info_iter)?;
+    let safety_deposit_info = next_account_info(account_info_iter)?;
+    let store_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let fraction_mint_info = next_account_info(account_info_iter)?;
+    let caller_info = next_account_info(account_info_iter)?;
+    let caller_shares_info = next_account_info(account_info_iter)?;
+    let transfer_authority_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+
+    let clock = Clock::from_account_info(clock_info)?;
+    let mut vault = Vault::from_account_info(vault_info)?;
+    let mut safety_deposit = SafetyDepositBox::from_account_info(safety_deposit_info)?;
+    let fraction_mint: Mint = assert_initialized(fraction_mint_info)?;
+
+    assert_owned_by(safety_deposit_info, program_id)?;
+    assert_owned_by(vault_info, program_id)?;
+    assert_token_matching(&vault, token_program_info)?;
+
+    if !caller_info.is_signer {
+        return Err(VaultError::CallerMustBeAuthorityOrShareholder.into());
+    }
+
+    if *caller_info.key != vault.authority {
+        assert_owned_by(caller_shares_info, token_program_info.key)?;
+        let caller_shares: Account = assert_initialized(caller_shares_info)?;
+        if caller_shares.owner != *caller_info.key
+            || caller_shares.mint != vault.fraction_mint
+            || caller_shares.amount == 0
+        {
+            return Err(VaultError::CallerMustBeAuthorityOrShareholder.into());
+        }
+    }
+
+    if vault.state != VaultState::Combined {
+        return Err(VaultError::VaultShouldBeCombined.into());
+    }
+
+    if vault.redemption_deadline.is_none() {
+        return Err(VaultError::VaultHasNoRedemptionDeadline.into());
+    }
+
+    if !vault.redemption_deadline_passed(clock.unix_timestamp) {
+        return Err(VaultError::RedemptionDeadlineNotPassed.into());
+    }
+
+    if safety_deposit.vault != *vault_info.key {
+        return Err(VaultError::SafetyDepositBoxVaultMismatch.into());
+    }
+
+    if fraction_mint_info.key != &vault.fraction_mint {
+        return Err(VaultError::VaultMintNeedsToMatchVault.into());
+    }
+
+    if safety_deposit.is_native() {
+        if *destination_info.key != vault.redemption_recipient {
+            return Err(VaultError::DestinationMustBelongToRedemptionRecipient.into());
+        }
+
+        if safety_deposit.amount == 0 {
+            return Err(VaultError::StoreEmpty.into());
+        }
+
+        let box_lamports = match safety_deposit_info
+            .lamports()
+            .checked_sub(safety_deposit.amount)
+        {
+            Some(val) => val,
+            None => return Err(VaultError::NumericalOverflowError.into()),
+        };
+        let destination_lamports = match destination_info
+            .lamports()
+            .checked_add(safety_deposit.amount)
+        {
+            Some(val) => val,
+            None => return Err(VaultError::NumericalOverflowError.into()),
+        };
+        **safety_deposit_info.lamports.borrow_mut() = box_lamports;
+        **destination_info.lamports.borrow_mut() = destination_lamports;
+    } else {
+        let destination: Account = assert_initialized(destination_info)?;
+        let store: Account = assert_initialized(store_info)?;
+        assert_owned_by(destination_info, token_program_info.key)?;
+
+        if *store_info.key != safety_deposit.store {
+            return Err(VaultError::StoreDoesNotMatchSafetyDepositBox.into());
+        }
+
+        if store.amount == 0 {
+            return Err(VaultError::StoreEmpty.into());
+        }
+
+        if destination.mint != safety_deposit.token_mint {
+            return Err(VaultError::DestinationAccountNeedsToMatchTokenMint.into());
+        }
+
+        if destination.owner != vault.redemption_recipient {
+            return Err(VaultError::DestinationMustBelongToRedemptionRecipient.into());
+        }
+
+        let (authority, bump_seed) =
+            Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref()], program_id);
+        let authority_signer_seeds = &[PREFIX.as_bytes(), program_id.as_ref(), &[bump_seed]];
+
+        if authority != *transfer_authority_info.key {
+            return Err(VaultError::InvalidAuthority.into());
+        }
+
+        spl_token_transfer(TokenTransferParams {
+            source: store_info.clone(),
+            destination: destination_info.clone(),
+            amount: store.amount,
+            authority: transfer_authority_info.clone(),
+            authority_signer_seeds,
+            token_program: token_program_info.clone(),
+        })?;
     }
+
+    safety_deposit.amount = 0;
+    safety_deposit.save(safety_deposit_info)?;
+
+    close_out_safety_deposit_box(&mut vault, &fraction_mint)?;
+    vault.save(vault_info)?;
+
+    Ok(())
+}
+
+pub fn process_migrate_vault(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let vault_info = next_account_info(account_info_iter)?;
+
+    assert_owned_by(vault_info, program_id)?;
+
+    // Loading upgrades original layout vaults, saving writes them back in the current layout.
+    let vault = Vault::from_account_info(vault_info)?;
+    vault.save(vault_info)?;
+
+    Ok(())
 }
 
 pub fn process_update_external_price_account(
@@ -107,7 +369,7 @@ pub fn process_update_external_price_account(
     external_price_account.price_mint = price_mint;
     external_price_account.allowed_to_combine = allowed_to_combine;
 
-    external_price_account.serialize(&mut *account.data.borrow_mut())?;
+    external_price_account.serialize(&mut &mut account.data.borrow_mut()[..])?;
 
     Ok(())
 }
@@ -125,7 +387,7 @@ pub fn process_add_fractional_shares_to_treasury(
     let vault_authority_info = next_account_info(account_info_iter)?;
     let token_program_info = next_account_info(account_info_iter)?;
 
-    let vault: Vault = try_from_slice_unchecked(&vault_info.data.borrow_mut())?;
+    let vault = Vault::from_account_info(vault_info)?;
     let source: Account = assert_initialized(source_info)?;
 
     assert_owned_by(source_info, token_program_info.key)?;
@@ -180,7 +442,7 @@ pub fn process_withdraw_fractional_shares_from_treasury(
     let rent_info = next_account_info(account_info_iter)?;
 
     let rent = &Rent::from_account_info(rent_info)?;
-    let vault: Vault = try_from_slice_unchecked(&vault_info.data.borrow_mut())?;
+    let vault = Vault::from_account_info(vault_info)?;
     let destination: Account = assert_initialized(destination_info)?;
     let fraction_treasury: Account = assert_initialized(fraction_treasury_info)?;
 
@@ -240,7 +502,7 @@ pub fn process_mint_fractional_shares(
     let vault_authority_info = next_account_info(account_info_iter)?;
     let token_program_info = next_account_info(account_info_iter)?;
 
-    let vault: Vault = try_from_slice_unchecked(&vault_info.data.borrow_mut())?;
+    let vault = Vault::from_account_info(vault_info)?;
 
     assert_token_matching(&vault, token_program_info)?;
     assert_owned_by(vault_info, program_id)?;
@@ -299,9 +561,8 @@ pub fn process_withdraw_token_from_safety_deposit_box(
     let rent_info = next_account_info(account_info_iter)?;
 
     let rent = &Rent::from_account_info(rent_info)?;
-    let mut vault: Vault = try_from_slice_unchecked(&vault_info.data.borrow_mut())?;
-    let safety_deposit: SafetyDepositBox =
-        try_from_slice_unchecked(&safety_deposit_info.data.borrow_mut())?;
+    let mut vault = Vault::from_account_info(vault_info)?;
+    let mut safety_deposit = SafetyDepositBox::from_account_info(safety_deposit_info)?;
     let fraction_mint: Mint = assert_initialized(fraction_mint_info)?;
     let destination: Account = assert_initialized(destination_info)?;
     let store: Account = assert_initialized(store_info)?;
@@ -325,6 +586,10 @@ pub fn process_withdraw_token_from_safety_deposit_box(
         return Err(VaultError::SafetyDepositBoxVaultMismatch.into());
     }
 
+    if safety_deposit.is_native() {
+        return Err(VaultError::SafetyDepositBoxIsNative.into());
+    }
+
     if fraction_mint_info.key != &vault.fraction_mint {
         return Err(VaultError::VaultMintNeedsToMatchVault.into());
     }
@@ -362,22 +627,102 @@ pub fn process_withdraw_token_from_safety_deposit_box(
         token_program: token_program_info.clone(),
     })?;
 
-    match store.amount.checked_sub(amount) {
-        Some(val) => {
-            if val == 0 {
-                vault.token_type_count = match vault.token_type_count.checked_sub(1) {
-                    Some(val) => val,
-                    None => return Err(VaultError::NumericalOverflowError.into()),
-                };
-
-                if fraction_mint.supply == 0 && vault.token_type_count == 0 {
-                    vault.state = VaultState::Deactivated;
-                    vault.serialize(&mut *vault_info.data.borrow_mut())?;
-                }
-            }
-        }
+    safety_deposit.amount = match store.amount.checked_sub(amount) {
+        Some(val) => val,
         None => return Err(VaultError::NumericalOverflowError.into()),
     };
+    safety_deposit.save(safety_deposit_info)?;
+
+    if safety_deposit.amount == 0 {
+        close_out_safety_deposit_box(&mut vault, &fraction_mint)?;
+        vault.save(vault_info)?;
+    }
+
+    Ok(())
+}
+
+/// Called when a safety deposit box has been fully emptied during the Combined state, counts the box
+/// down and deactivates the vault if it was the last one and no shares remain.
+fn close_out_safety_deposit_box(vault: &mut Vault, fraction_mint: &Mint) -> ProgramResult {
+    vault.token_type_count = match vault.token_type_count.checked_sub(1) {
+        Some(val) => val,
+        None => return Err(VaultError::NumericalOverflowError.into()),
+    };
+
+    if fraction_mint.supply == 0 && vault.token_type_count == 0 {
+        vault.state = VaultState::Deactivated;
+    }
+
+    Ok(())
+}
+
+pub fn process_withdraw_native_from_safety_deposit_box(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let destination_info = next_account_info(account_info_iter)?;
+    let safety_deposit_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let fraction_mint_info = next_account_info(account_info_iter)?;
+    let vault_authority_info = next_account_info(account_info_iter)?;
+
+    let mut vault = Vault::from_account_info(vault_info)?;
+    let mut safety_deposit = SafetyDepositBox::from_account_info(safety_deposit_info)?;
+    let fraction_mint: Mint = assert_initialized(fraction_mint_info)?;
+
+    assert_owned_by(safety_deposit_info, program_id)?;
+    assert_owned_by(vault_info, program_id)?;
+    assert_vault_authority_correct(&vault, vault_authority_info)?;
+
+    if vault.state != VaultState::Combined {
+        return Err(VaultError::VaultShouldBeCombined.into());
+    }
+
+    if safety_deposit.vault != *vault_info.key {
+        return Err(VaultError::SafetyDepositBoxVaultMismatch.into());
+    }
+
+    if !safety_deposit.is_native() {
+        return Err(VaultError::SafetyDepositBoxIsNotNative.into());
+    }
+
+    if fraction_mint_info.key != &vault.fraction_mint {
+        return Err(VaultError::VaultMintNeedsToMatchVault.into());
+    }
+
+    if safety_deposit.amount == 0 {
+        return Err(VaultError::StoreEmpty.into());
+    }
+
+    if safety_deposit.amount < amount {
+        return Err(VaultError::StoreLessThanAmount.into());
+    }
+
+    // The box is owned by this program so we can move lamports out of it directly, anything above
+    // the recorded amount is rent and stays put.
+    let box_lamports = match safety_deposit_info.lamports().checked_sub(amount) {
+        Some(val) => val,
+        None => return Err(VaultError::NumericalOverflowError.into()),
+    };
+    let destination_lamports = match destination_info.lamports().checked_add(amount) {
+        Some(val) => val,
+        None => return Err(VaultError::NumericalOverflowError.into()),
+    };
+    **safety_deposit_info.lamports.borrow_mut() = box_lamports;
+    **destination_info.lamports.borrow_mut() = destination_lamports;
+
+    safety_deposit.amount = match safety_deposit.amount.checked_sub(amount) {
+        Some(val) => val,
+        None => return Err(VaultError::NumericalOverflowError.into()),
+    };
+    safety_deposit.save(safety_deposit_info)?;
+
+    if safety_deposit.amount == 0 {
+        close_out_safety_deposit_box(&mut vault, &fraction_mint)?;
+        vault.save(vault_info)?;
+    }
 
     Ok(())
 }
@@ -396,7 +741,11 @@ pub fn process_redeem_shares(program_id: &Pubkey, accounts: &[AccountInfo]) -> P
     let rent_info = next_account_info(account_info_iter)?;
 
     let rent = &Rent::from_account_info(rent_info)?;
-    let mut vault: Vault = try_from_slice_unchecked(&vault_info.data.borrow_mut())?;
+    let mut vault = Vault::from_account_info(vault_info)?;
+    let fee_recipient_info = match vault.fee_basis_points {
+        0 => None,
+        _ => Some(next_account_info(account_info_iter)?),
+    };
     let fraction_mint: Mint = assert_initialized(fraction_mint_info)?;
     let outstanding_shares: Account = assert_initialized(outstanding_shares_info)?;
     let destination: Account = assert_initialized(destination_info)?;
@@ -449,15 +798,35 @@ pub fn process_redeem_shares(program_id: &Pubkey, accounts: &[AccountInfo]) -> P
         Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref()], program_id);
     let authority_signer_seeds = &[PREFIX.as_bytes(), program_id.as_ref(), &[bump_seed]];
 
+    let fee = vault.fee_on(we_owe_you)?;
+    let proceeds = match we_owe_you.checked_sub(fee) {
+        Some(val) => val,
+        None => return Err(VaultError::NumericalOverflowError.into()),
+    };
+
     spl_token_transfer(TokenTransferParams {
         source: redeem_treasury_info.clone(),
         destination: destination_info.clone(),
-        amount: we_owe_you,
+        amount: proceeds,
         authority: transfer_authority_info.clone(),
         authority_signer_seeds,
         token_program: token_program_info.clone(),
     })?;
 
+    if let Some(fee_recipient_info) = fee_recipient_info {
+        assert_fee_recipient_correct(&vault, fee_recipient_info, &redeem_treasury)?;
+        if fee > 0 {
+            spl_token_transfer(TokenTransferParams {
+                source: redeem_treasury_info.clone(),
+                destination: fee_recipient_info.clone(),
+                amount: fee,
+                authority: transfer_authority_info.clone(),
+                authority_signer_seeds,
+                token_program: token_program_info.clone(),
+            })?;
+        }
+    }
+
     spl_token_burn(TokenBurnParams {
         mint: fraction_mint_info.clone(),
         amount: outstanding_shares.amount,
@@ -474,7 +843,7 @@ pub fn process_redeem_shares(program_id: &Pubkey, accounts: &[AccountInfo]) -> P
 
     if fractional_remaining == 0 && vault.token_type_count == 0 {
         vault.state = VaultState::Deactivated;
-        vault.serialize(&mut *vault_info.data.borrow_mut())?;
+        vault.save(vault_info)?;
     }
 
     Ok(())
@@ -496,7 +865,7 @@ pub fn process_combine_vault(program_id: &Pubkey, accounts: &[AccountInfo]) -> P
     let external_pricing_info = next_account_info(account_info_iter)?;
     let token_program_info = next_account_info(account_info_iter)?;
 
-    let mut vault: Vault = try_from_slice_unchecked(&vault_info.data.borrow_mut())?;
+    let mut vault = Vault::from_account_info(vault_info)?;
     let fraction_mint: Mint = assert_initialized(fraction_mint_info)?;
     let fraction_treasury: Account = assert_initialized(fraction_treasury_info)?;
     let redeem_treasury: Account = assert_initialized(redeem_treasury_info)?;
@@ -505,6 +874,20 @@ pub fn process_combine_vault(program_id: &Pubkey, accounts: &[AccountInfo]) -> P
     let external_pricing: ExternalPriceAccount =
         try_from_slice_unchecked(&external_pricing_info.data.borrow_mut())?;
 
+    // Trailing accounts, the clock and redemption recipient only for vaults with a redemption window,
+    // followed by the fee recipient only for vaults that charge a fee.
+    let redemption_infos = match vault.redemption_window {
+        Some(_) => Some((
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+        )),
+        None => None,
+    };
+    let fee_recipient_info = match vault.fee_basis_points {
+        0 => None,
+        _ => Some(next_account_info(account_info_iter)?),
+    };
+
     assert_token_matching(&vault, token_program_info)?;
     assert_owned_by(vault_info, program_id)?;
     assert_vault_authority_correct(&vault, vault_authority_info)?;
@@ -572,7 +955,13 @@ pub fn process_combine_vault(program_id: &Pubkey, accounts: &[AccountInfo]) -> P
         None => return Err(VaultError::NumericalOverflowError.into()),
     };
 
-    if your_payment_account.amount < what_you_owe {
+    let fee = vault.fee_on(what_you_owe)?;
+    let total_cost = match what_you_owe.checked_add(fee) {
+        Some(val) => val,
+        None => return Err(VaultError::NumericalOverflowError.into()),
+    };
+
+    if your_payment_account.amount < total_cost {
         return Err(VaultError::CannotAffordToCombineThisVault.into());
     }
 
@@ -593,6 +982,20 @@ pub fn process_combine_vault(program_id: &Pubkey, accounts: &[AccountInfo]) -> P
         token_program: token_program_info.clone(),
     })?;
 
+    if let Some(fee_recipient_info) = fee_recipient_info {
+        assert_fee_recipient_correct(&vault, fee_recipient_info, &redeem_treasury)?;
+        if fee > 0 {
+            spl_token_transfer(TokenTransferParams {
+                source: your_payment_info.clone(),
+                destination: fee_recipient_info.clone(),
+                amount: fee,
+                authority: transfer_authority_info.clone(),
+                authority_signer_seeds,
+                token_program: token_program_info.clone(),
+            })?;
+        }
+    }
+
     spl_token_burn(TokenBurnParams {
         mint: fraction_mint_info.clone(),
         amount: your_outstanding_shares.amount,
@@ -611,10 +1014,22 @@ pub fn process_combine_vault(program_id: &Pubkey, accounts: &[AccountInfo]) -> P
         source: fraction_treasury_info.clone(),
     })?;
 
+    if let (Some((clock_info, redemption_recipient_info)), Some(window)) =
+        (redemption_infos, vault.redemption_window)
+    {
+        let clock = Clock::from_account_info(clock_info)?;
+
+        vault.redemption_deadline = match clock.unix_timestamp.checked_add(window) {
+            Some(val) => Some(val),
+            None => return Err(VaultError::NumericalOverflowError.into()),
+        };
+        vault.redemption_recipient = *redemption_recipient_info.key;
+    }
+
     vault.state = VaultState::Combined;
     vault.authority = *new_vault_authority_info.key;
     vault.locked_price_per_share = external_pricing.price_per_share;
-    vault.serialize(&mut *vault_info.data.borrow_mut())?;
+    vault.save(vault_info)?;
 
     Ok(())
 }
@@ -633,7 +1048,7 @@ pub fn process_activate_vault(
     let vault_authority_info = next_account_info(account_info_iter)?;
     let token_program_info = next_account_info(account_info_iter)?;
 
-    let mut vault: Vault = try_from_slice_unchecked(&vault_info.data.borrow_mut())?;
+    let mut vault = Vault::from_account_info(vault_info)?;
     assert_owned_by(vault_info, program_id)?;
     assert_token_matching(&vault, token_program_info)?;
     assert_vault_authority_correct(&vault, vault_authority_info)?;
@@ -659,7 +1074,7 @@ pub fn process_activate_vault(
     })?;
 
     vault.state = VaultState::Active;
-    vault.serialize(&mut *vault_info.data.borrow_mut())?;
+    vault.save(vault_info)?;
 
     Ok(())
 }
@@ -690,7 +1105,7 @@ pub fn process_add_token_to_inactivated_vault(
 
     let token_account: Account = assert_initialized(token_account_info)?;
     let store: Account = assert_initialized(store_info)?;
-    let mut vault: Vault = try_from_slice_unchecked(&vault_info.data.borrow_mut())?;
+    let mut vault = Vault::from_account_info(vault_info)?;
     assert_token_matching(&vault, token_program_info)?;
     assert_vault_authority_correct(&vault, vault_authority_info)?;
 
@@ -745,20 +1160,22 @@ pub fn process_add_token_to_inactivated_vault(
 
     let mut safety_deposit_account: SafetyDepositBox =
         try_from_slice_unchecked(&safety_deposit_account_info.data.borrow_mut())?;
-    safety_deposit_account.key = SAFETY_DEPOSIT_KEY;
+    safety_deposit_account.key = SAFETY_DEPOSIT_V2_KEY;
     safety_deposit_account.vault = *vault_info.key;
     safety_deposit_account.token_mint = token_account.mint;
     safety_deposit_account.store = *store_info.key;
     safety_deposit_account.order = vault.token_type_count;
+    safety_deposit_account.amount = amount;
 
-    safety_deposit_account.serialize(&mut *safety_deposit_account_info.data.borrow_mut())?;
+    safety_deposit_account
+        .serialize(&mut &mut safety_deposit_account_info.data.borrow_mut()[..])?;
 
     vault.token_type_count = match vault.token_type_count.checked_add(1) {
         Some(val) => val,
         None => return Err(VaultError::NumericalOverflowError.into()),
     };
 
-    vault.serialize(&mut *vault_info.data.borrow_mut())?;
+    vault.save(vault_info)?;
 
     spl_token_transfer(TokenTransferParams {
         source: token_account_info.clone(),
@@ -772,6 +1189,92 @@ pub fn process_add_token_to_inactivated_vault(
     Ok(())
 }
 
+pub fn process_add_native_to_inactivated_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let safety_deposit_account_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let vault_authority_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_account_info = next_account_info(account_info_iter)?;
+
+    let rent = &Rent::from_account_info(rent_info)?;
+    assert_owned_by(vault_info, program_id)?;
+    assert_rent_exempt(rent, vault_info)?;
+
+    let mut vault = Vault::from_account_info(vault_info)?;
+    assert_vault_authority_correct(&vault, vault_authority_info)?;
+
+    if vault.state != VaultState::Inactive {
+        return Err(VaultError::VaultShouldBeInactive.into());
+    }
+
+    if amount == 0 {
+        return Err(VaultError::AmountMustBeGreaterThanZero.into());
+    }
+
+    let native_mint = system_program::id();
+    let seeds = &[
+        PREFIX.as_bytes(),
+        vault_info.key.as_ref(),
+        native_mint.as_ref(),
+    ];
+    let (safety_deposit_account_key, bump_seed) = Pubkey::find_program_address(seeds, program_id);
+
+    if safety_deposit_account_key != *safety_deposit_account_info.key {
+        return Err(VaultError::SafetyDepositAddressInvalid.into());
+    }
+    let authority_signer_seeds = &[
+        PREFIX.as_bytes(),
+        vault_info.key.as_ref(),
+        native_mint.as_ref(),
+        &[bump_seed],
+    ];
+    create_or_allocate_account_raw(
+        *program_id,
+        safety_deposit_account_info,
+        rent_info,
+        system_account_info,
+        payer_info,
+        MAX_SAFETY_DEPOSIT_SIZE,
+        authority_signer_seeds,
+    )?;
+
+    let mut safety_deposit_account: SafetyDepositBox =
+        try_from_slice_unchecked(&safety_deposit_account_info.data.borrow_mut())?;
+    safety_deposit_account.key = SAFETY_DEPOSIT_V2_KEY;
+    safety_deposit_account.vault = *vault_info.key;
+    safety_deposit_account.token_mint = native_mint;
+    safety_deposit_account.store = *safety_deposit_account_info.key;
+    safety_deposit_account.order = vault.token_type_count;
+    safety_deposit_account.amount = amount;
+
+    safety_deposit_account
+        .serialize(&mut &mut safety_deposit_account_info.data.borrow_mut()[..])?;
+
+    vault.token_type_count = match vault.token_type_count.checked_add(1) {
+        Some(val) => val,
+        None => return Err(VaultError::NumericalOverflowError.into()),
+    };
+
+    vault.save(vault_info)?;
+
+    invoke(
+        &system_instruction::transfer(payer_info.key, safety_deposit_account_info.key, amount),
+        &[
+            payer_info.clone(),
+            safety_deposit_account_info.clone(),
+            system_account_info.clone(),
+        ],
+    )?;
+
+    Ok(())
+}
+
 pub fn process_init_vault(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -790,7 +1293,7 @@ pub fn process_init_vault(
     let fraction_mint: Mint = assert_initialized(fraction_mint_info)?;
     let redeem_treasury: Account = assert_initialized(redeem_treasury_info)?;
     let fraction_treasury: Account = assert_initialized(fraction_treasury_info)?;
-    let mut vault: Vault = try_from_slice_unchecked(&vault_info.data.borrow())?;
+    let mut vault = Vault::from_account_info(vault_info)?;
     let external_pricing_lookup: ExternalPriceAccount =
         try_from_slice_unchecked(&pricing_lookup_address.data.borrow_mut())?;
 
@@ -859,7 +1362,7 @@ pub fn process_init_vault(
         return Err(VaultError::VaultTreasuryMintDoesNotMatchVaultMint.into());
     }
 
-    vault.key = VAULT_KEY;
+    vault.key = VAULT_V2_KEY;
     vault.token_program = *token_program_info.key;
     vault.redeem_treasury = *redeem_treasury_info.key;
     vault.fraction_treasury = *fraction_treasury_info.key;
@@ -870,7 +1373,7 @@ pub fn process_init_vault(
     vault.token_type_count = 0;
     vault.state = VaultState::Inactive;
 
-    vault.serialize(&mut *vault_info.data.borrow_mut())?;
+    vault.save(vault_info)?;
 
     Ok(())
 }
@@ -1,17 +1,32 @@
 use {
+    crate::error::VaultError,
     borsh::{BorshDeserialize, BorshSerialize},
-    solana_program::pubkey::Pubkey,
+    solana_program::{
+        account_info::AccountInfo, borsh::try_from_slice_unchecked, clock::UnixTimestamp,
+        entrypoint::ProgramResult, program_error::ProgramError, pubkey::Pubkey, system_program,
+    },
+    std::convert::TryInto,
 };
 /// prefix used for PDAs to avoid certain collision attacks (https://en.wikipedia.org/wiki/Collision_attack#Chosen-prefix_collision_attack)
 pub const PREFIX: &str = "vault";
 
 /// Used to tell front end clients that this struct is a ledger struct
+/// VAULT_KEY and SAFETY_DEPOSIT_KEY mark the original layouts with u8 order indices, still readable
+/// through Vault::from_slice and SafetyDepositBox::from_slice.
 pub const VAULT_KEY: u8 = 0;
 pub const SAFETY_DEPOSIT_KEY: u8 = 1;
 pub const EXTERNAL_ACCOUNT_KEY: u8 = 2;
+pub const VAULT_V2_KEY: u8 = 3;
+pub const SAFETY_DEPOSIT_V2_KEY: u8 = 4;
 
-pub const MAX_SAFETY_DEPOSIT_SIZE: usize = 1 + 32 + 32 + 32 + 1;
-pub const MAX_VAULT_SIZE: usize = 1 + 32 + 32 + 32 + 32 + 1 + 32 + 1 + 32 + 1 + 1 + 8;
+pub const MAX_SAFETY_DEPOSIT_SIZE: usize = 1 + 32 + 32 + 32 + 2 + 8;
+pub const MAX_SAFETY_DEPOSIT_V1_SIZE: usize = 1 + 32 + 32 + 32 + 1 + 8;
+/// Size of vaults allocated before the redemption deadline fields were added. Such vaults can still be
+/// saved as long as those trailing fields are left unset.
+pub const BASE_VAULT_SIZE: usize = 1 + 32 + 32 + 32 + 32 + 32 + 1 + 32 + 2 + 1 + 8;
+pub const MAX_VAULT_SIZE: usize = BASE_VAULT_SIZE + 9 + 9 + 32 + 2 + 32;
+/// Highest fee a vault can charge on combination and share redemption, 10%
+pub const MAX_FEE_BASIS_POINTS: u16 = 1000;
 pub const MAX_EXTERNAL_ACCOUNT_SIZE: usize = 1 + 8 + 32 + 1;
 #[repr(C)]
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
@@ -47,12 +62,110 @@ pub struct Vault {
     /// The authority of the vault withdrawals a Safety Deposit contents to count down how many
     /// are left to be opened and closed down. Once this hits zero, and the fraction mint has zero shares,
     /// then we can deactivate the vault.
-    pub token_type_count: u8,
+    pub token_type_count: u16,
     pub state: VaultState,
 
     /// Once combination happens, we copy price per share to vault so that if something nefarious happens
     /// to external price account, like price change, we still have the math 'saved' for use in our calcs
     pub locked_price_per_share: u64,
+
+    /// Seconds after combination the authority has to withdraw the safety deposit contents. Once it lapses
+    /// any shareholder may sweep what is left to the redemption recipient.
+    pub redemption_window: Option<UnixTimestamp>,
+    /// Set on combination from the redemption window
+    pub redemption_deadline: Option<UnixTimestamp>,
+    /// Receives safety deposit contents swept after the redemption deadline, set on combination
+    pub redemption_recipient: Pubkey,
+
+    /// Fee charged to the combiner on top of the buyout, and taken out of shareholder proceeds on redemption
+    pub fee_basis_points: u16,
+    /// Token account of the redeem treasury mint that receives fees
+    pub fee_recipient: Pubkey,
+}
+
+impl Vault {
+    /// Decodes a vault in either layout. Original layout vaults are upgraded to VAULT_V2_KEY, and since
+    /// both layouts fit in MAX_VAULT_SIZE they are migrated in place the next time they are saved.
+    pub fn from_slice(data: &[u8]) -> Result<Vault, ProgramError> {
+        if data.first() == Some(&VAULT_KEY) {
+            let legacy: VaultV1 = try_from_slice_unchecked(data)?;
+            return Ok(Vault {
+                key: VAULT_V2_KEY,
+                token_program: legacy.token_program,
+                fraction_mint: legacy.fraction_mint,
+                authority: legacy.authority,
+                fraction_treasury: legacy.fraction_treasury,
+                redeem_treasury: legacy.redeem_treasury,
+                allow_further_share_creation: legacy.allow_further_share_creation,
+                pricing_lookup_address: legacy.pricing_lookup_address,
+                token_type_count: legacy.token_type_count as u16,
+                state: legacy.state,
+                locked_price_per_share: legacy.locked_price_per_share,
+                redemption_window: None,
+                redemption_deadline: None,
+                redemption_recipient: Pubkey::default(),
+                fee_basis_points: 0,
+                fee_recipient: Pubkey::default(),
+            });
+        }
+        // Pad out vaults allocated at BASE_VAULT_SIZE so the trailing fields read back as unset.
+        let mut padded = data.to_vec();
+        if padded.len() < MAX_VAULT_SIZE {
+            padded.resize(MAX_VAULT_SIZE, 0);
+        }
+        let vault: Vault = try_from_slice_unchecked(&padded)?;
+        Ok(vault)
+    }
+
+    pub fn from_account_info(a: &AccountInfo) -> Result<Vault, ProgramError> {
+        Vault::from_slice(&a.data.borrow())
+    }
+
+    /// Writes the vault back, truncating the trailing fields on vaults allocated at BASE_VAULT_SIZE.
+    /// That is only allowed while the truncated bytes are all zero, i.e. those fields are unset.
+    pub fn save(&self, a: &AccountInfo) -> ProgramResult {
+        let serialized = self.try_to_vec()?;
+        let mut data = a.data.borrow_mut();
+        let len = serialized.len().min(data.len());
+        if serialized[len..].iter().any(|b| *b != 0) {
+            return Err(VaultError::VaultAccountTooSmall.into());
+        }
+        data[..len].copy_from_slice(&serialized[..len]);
+        Ok(())
+    }
+
+    /// Fee owed on an amount of the redeem treasury mint
+    pub fn fee_on(&self, amount: u64) -> Result<u64, VaultError> {
+        match amount.checked_mul(self.fee_basis_points as u64) {
+            Some(val) => Ok(val / 10000),
+            None => Err(VaultError::NumericalOverflowError),
+        }
+    }
+
+    /// Whether the redemption deadline has been set and has passed
+    pub fn redemption_deadline_passed(&self, now: UnixTimestamp) -> bool {
+        match self.redemption_deadline {
+            Some(deadline) => now >= deadline,
+            None => false,
+        }
+    }
+}
+
+/// Original vault layout, kept so that vaults created before order indices were widened can still be read.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct VaultV1 {
+    pub key: u8,
+    pub token_program: Pubkey,
+    pub fraction_mint: Pubkey,
+    pub authority: Pubkey,
+    pub fraction_treasury: Pubkey,
+    pub redeem_treasury: Pubkey,
+    pub allow_further_share_creation: bool,
+    pub pricing_lookup_address: Pubkey,
+    pub token_type_count: u8,
+    pub state: VaultState,
+    pub locked_price_per_share: u64,
 }
 
 #[repr(C)]
@@ -67,7 +180,83 @@ pub struct SafetyDepositBox {
     /// Account that stores the tokens under management
     pub store: Pubkey,
     /// the order in the array of registries
+    pub order: u16,
+    /// Amount of the token (or lamports, for a native box) currently held in the store
+    pub amount: u64,
+}
+
+/// Original safety deposit box layout with a u8 order. Boxes are PDAs allocated at their original size,
+/// so they keep this layout for their whole life rather than being migrated.
+#[repr(C)]
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct SafetyDepositBoxV1 {
+    pub key: u8,
+    pub vault: Pubkey,
+    pub token_mint: Pubkey,
+    pub store: Pubkey,
     pub order: u8,
+    /// Not present on the earliest boxes, which read back as zero.
+    pub amount: u64,
+}
+
+impl SafetyDepositBox {
+    /// Decodes a safety deposit box in either layout.
+    pub fn from_slice(data: &[u8]) -> Result<SafetyDepositBox, ProgramError> {
+        if data.first() == Some(&SAFETY_DEPOSIT_KEY) {
+            // Pad out boxes that predate the amount field so it reads back as zero.
+            let mut padded = data.to_vec();
+            if padded.len() < MAX_SAFETY_DEPOSIT_V1_SIZE {
+                padded.resize(MAX_SAFETY_DEPOSIT_V1_SIZE, 0);
+            }
+            let legacy: SafetyDepositBoxV1 = try_from_slice_unchecked(&padded)?;
+            return Ok(SafetyDepositBox {
+                key: legacy.key,
+                vault: legacy.vault,
+                token_mint: legacy.token_mint,
+                store: legacy.store,
+                order: legacy.order as u16,
+                amount: legacy.amount,
+            });
+        }
+        let safety_deposit: SafetyDepositBox = try_from_slice_unchecked(data)?;
+        Ok(safety_deposit)
+    }
+
+    pub fn from_account_info(a: &AccountInfo) -> Result<SafetyDepositBox, ProgramError> {
+        SafetyDepositBox::from_slice(&a.data.borrow())
+    }
+
+    /// Writes the box back in the layout it was read with. The amount is dropped for the earliest boxes
+    /// that have no room for it, their store's token balance remains the source of truth.
+    pub fn save(&self, a: &AccountInfo) -> ProgramResult {
+        if self.key != SAFETY_DEPOSIT_KEY {
+            self.serialize(&mut &mut a.data.borrow_mut()[..])?;
+            return Ok(());
+        }
+
+        let legacy = SafetyDepositBoxV1 {
+            key: self.key,
+            vault: self.vault,
+            token_mint: self.token_mint,
+            store: self.store,
+            order: match self.order.try_into() {
+                Ok(val) => val,
+                Err(_) => return Err(VaultError::NumericalOverflowError.into()),
+            },
+            amount: self.amount,
+        }
+        .try_to_vec()?;
+        let mut data = a.data.borrow_mut();
+        let len = legacy.len().min(data.len());
+        data[..len].copy_from_slice(&legacy[..len]);
+        Ok(())
+    }
+
+    /// Native boxes hold lamports directly in the safety deposit box account instead of an SPL
+    /// token store, and use the system program id in place of a token mint.
+    pub fn is_native(&self) -> bool {
+        self.token_mint == system_program::id()
+    }
 }
 
 #[repr(C)]
@@ -81,3 +270,63 @@ pub struct ExternalPriceAccount {
     /// Whether or not combination has been allowed for this vault.
     pub allowed_to_combine: bool,
 }
+
+/// Breakdown of what a vault holds and what it is worth, computed off chain from the vault's
+/// safety deposit boxes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VaultValuation {
+    /// Token mint and amount held for each SPL safety deposit box, in box order
+    pub tokens: Vec<(Pubkey, u64)>,
+    /// Total lamports held across native safety deposit boxes
+    pub lamports: u64,
+    /// Price per share used for the valuation, locked price once the vault is combined
+    pub price_per_share: u64,
+    /// Fraction mint supply multiplied by the price per share
+    pub market_cap: u64,
+}
+
+impl Vault {
+    /// Builds a valuation view of this vault. Boxes belonging to other vaults are ignored.
+    pub fn valuation(
+        &self,
+        vault_key: &Pubkey,
+        boxes: &[SafetyDepositBox],
+        fraction_supply: u64,
+        external_price: &ExternalPriceAccount,
+    ) -> Result<VaultValuation, VaultError> {
+        let price_per_share = if self.state == VaultState::Combined {
+            self.locked_price_per_share
+        } else {
+            external_price.price_per_share
+        };
+
+        let mut ordered: Vec<&SafetyDepositBox> =
+            boxes.iter().filter(|b| b.vault == *vault_key).collect();
+        ordered.sort_by_key(|b| b.order);
+
+        let mut tokens = vec![];
+        let mut lamports: u64 = 0;
+        for safety_deposit in ordered {
+            if safety_deposit.is_native() {
+                lamports = match lamports.checked_add(safety_deposit.amount) {
+                    Some(val) => val,
+                    None => return Err(VaultError::NumericalOverflowError),
+                };
+            } else {
+                tokens.push((safety_deposit.token_mint, safety_deposit.amount));
+            }
+        }
+
+        let market_cap = match fraction_supply.checked_mul(price_per_share) {
+            Some(val) => val,
+            None => return Err(VaultError::NumericalOverflowError),
+        };
+
+        Ok(VaultValuation {
+            tokens,
+            lamports,
+            price_per_share,
+            market_cap,
+        })
+    }
+}
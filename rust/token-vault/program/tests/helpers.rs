@@ -0,0 +1,448 @@
+use solana_program::{hash::Hash, program_pack::Pack, pubkey::Pubkey, system_instruction};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+    transport::TransportError,
+};
+use spl_token_vault::{
+    instruction::{
+        create_activate_vault_instruction, create_add_native_to_inactive_vault_instruction,
+        create_add_token_to_inactive_vault_instruction, create_combine_vault_instruction,
+        create_init_vault_instruction, create_update_external_price_account_instruction,
+    },
+    state::{Vault, MAX_EXTERNAL_ACCOUNT_SIZE, MAX_VAULT_SIZE, PREFIX},
+};
+
+/// Accounts making up a vault, created by init_vault.
+pub struct TestVault {
+    pub vault: Pubkey,
+    pub fraction_mint: Pubkey,
+    pub fraction_treasury: Pubkey,
+    pub redeem_treasury: Pubkey,
+    pub external_price_account: Pubkey,
+}
+
+pub async fn get_account(banks_client: &mut BanksClient, pubkey: &Pubkey) -> Account {
+    banks_client
+        .get_account(*pubkey)
+        .await
+        .expect("account not found")
+        .expect("account empty")
+}
+
+pub async fn get_vault(banks_client: &mut BanksClient, vault: &Pubkey) -> Vault {
+    Vault::from_slice(&get_account(banks_client, vault).await.data).unwrap()
+}
+
+/// PDA that owns every store, treasury and fraction mint of the program.
+pub fn vault_authority(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[PREFIX.as_bytes(), program_id.as_ref()], program_id).0
+}
+
+pub async fn get_token_balance(banks_client: &mut BanksClient, token: &Pubkey) -> u64 {
+    let token_account = get_account(banks_client, token).await;
+    spl_token::state::Account::unpack_from_slice(token_account.data.as_slice())
+        .unwrap()
+        .amount
+}
+
+pub async fn process_instructions(
+    banks_client: &mut BanksClient,
+    recent_blockhash: &Hash,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransportError> {
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    transaction.sign(&all_signers, *recent_blockhash);
+    banks_client.process_transaction(transaction).await?;
+    Ok(())
+}
+
+pub async fn create_mint(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    authority: &Pubkey,
+    freeze_authority: Option<&Pubkey>,
+) -> Result<Pubkey, TransportError> {
+    let rent = banks_client.get_rent().await.unwrap();
+    let mint = Keypair::new();
+    process_instructions(
+        banks_client,
+        recent_blockhash,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint.pubkey(),
+                authority,
+                freeze_authority,
+                0,
+            )
+            .unwrap(),
+        ],
+        &[&mint],
+    )
+    .await?;
+    Ok(mint.pubkey())
+}
+
+pub async fn create_token_account(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Result<Pubkey, TransportError> {
+    let rent = banks_client.get_rent().await.unwrap();
+    let account = Keypair::new();
+    process_instructions(
+        banks_client,
+        recent_blockhash,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &account.pubkey(),
+                rent.minimum_balance(spl_token::state::Account::LEN),
+                spl_token::state::Account::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &account.pubkey(),
+                mint,
+                owner,
+            )
+            .unwrap(),
+        ],
+        &[&account],
+    )
+    .await?;
+    Ok(account.pubkey())
+}
+
+pub async fn mint_tokens(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    mint: &Pubkey,
+    account: &Pubkey,
+    amount: u64,
+) -> Result<(), TransportError> {
+    process_instructions(
+        banks_client,
+        recent_blockhash,
+        payer,
+        &[spl_token::instruction::mint_to(
+            &spl_token::id(),
+            mint,
+            account,
+            &payer.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap()],
+        &[],
+    )
+    .await
+}
+
+/// Creates an inactive vault with the payer as its authority, priced in `price_mint` which the
+/// payer must be the mint authority of.
+pub async fn init_vault(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    price_mint: &Pubkey,
+    price_per_share: u64,
+) -> Result<TestVault, TransportError> {
+    let rent = banks_client.get_rent().await.unwrap();
+    let authority = vault_authority(program_id);
+
+    let external_price_account = Keypair::new();
+    process_instructions(
+        banks_client,
+        recent_blockhash,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &external_price_account.pubkey(),
+                rent.minimum_balance(MAX_EXTERNAL_ACCOUNT_SIZE),
+                MAX_EXTERNAL_ACCOUNT_SIZE as u64,
+                program_id,
+            ),
+            create_update_external_price_account_instruction(
+                *program_id,
+                external_price_account.pubkey(),
+                price_per_share,
+                *price_mint,
+                true,
+            ),
+        ],
+        &[&external_price_account],
+    )
+    .await?;
+
+    let fraction_mint = create_mint(
+        banks_client,
+        payer,
+        recent_blockhash,
+        &authority,
+        Some(&authority),
+    )
+    .await?;
+    let fraction_treasury = create_token_account(
+        banks_client,
+        payer,
+        recent_blockhash,
+        &fraction_mint,
+        &authority,
+    )
+    .await?;
+    let redeem_treasury = create_token_account(
+        banks_client,
+        payer,
+        recent_blockhash,
+        price_mint,
+        &authority,
+    )
+    .await?;
+
+    let vault = Keypair::new();
+    process_instructions(
+        banks_client,
+        recent_blockhash,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &vault.pubkey(),
+                rent.minimum_balance(MAX_VAULT_SIZE),
+                MAX_VAULT_SIZE as u64,
+                program_id,
+            ),
+            create_init_vault_instruction(
+                *program_id,
+                fraction_mint,
+                redeem_treasury,
+                fraction_treasury,
+                vault.pubkey(),
+                payer.pubkey(),
+                external_price_account.pubkey(),
+                false,
+            ),
+        ],
+        &[&vault],
+    )
+    .await?;
+
+    Ok(TestVault {
+        vault: vault.pubkey(),
+        fraction_mint,
+        fraction_treasury,
+        redeem_treasury,
+        external_price_account: external_price_account.pubkey(),
+    })
+}
+
+pub async fn add_native(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    vault: &TestVault,
+    amount: u64,
+) -> Result<Pubkey, TransportError> {
+    let (safety_deposit_box, _) = Pubkey::find_program_address(
+        &[
+            PREFIX.as_bytes(),
+            vault.vault.as_ref(),
+            solana_program::system_program::id().as_ref(),
+        ],
+        program_id,
+    );
+    process_instructions(
+        banks_client,
+        recent_blockhash,
+        payer,
+        &[create_add_native_to_inactive_vault_instruction(
+            *program_id,
+            safety_deposit_box,
+            vault.vault,
+            payer.pubkey(),
+            payer.pubkey(),
+            amount,
+        )],
+        &[],
+    )
+    .await?;
+    Ok(safety_deposit_box)
+}
+
+/// Mints `amount` of a new token and adds it to the vault, returning the box and its store.
+pub async fn add_token(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    vault: &TestVault,
+    amount: u64,
+) -> Result<(Pubkey, Pubkey), TransportError> {
+    let mint = create_mint(banks_client, payer, recent_blockhash, &payer.pubkey(), None).await?;
+    let token_account = create_token_account(
+        banks_client,
+        payer,
+        recent_blockhash,
+        &mint,
+        &payer.pubkey(),
+    )
+    .await?;
+    mint_tokens(
+        banks_client,
+        payer,
+        recent_blockhash,
+        &mint,
+        &token_account,
+        amount,
+    )
+    .await?;
+    let store = create_token_account(
+        banks_client,
+        payer,
+        recent_blockhash,
+        &mint,
+        &vault_authority(program_id),
+    )
+    .await?;
+
+    let (safety_deposit_box, _) = Pubkey::find_program_address(
+        &[PREFIX.as_bytes(), vault.vault.as_ref(), mint.as_ref()],
+        program_id,
+    );
+    process_instructions(
+        banks_client,
+        recent_blockhash,
+        payer,
+        &[create_add_token_to_inactive_vault_instruction(
+            *program_id,
+            safety_deposit_box,
+            token_account,
+            store,
+            vault.vault,
+            payer.pubkey(),
+            payer.pubkey(),
+            payer.pubkey(),
+            amount,
+        )],
+        &[],
+    )
+    .await?;
+    Ok((safety_deposit_box, store))
+}
+
+pub async fn activate_vault(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    vault: &TestVault,
+    number_of_shares: u64,
+) -> Result<(), TransportError> {
+    process_instructions(
+        banks_client,
+        recent_blockhash,
+        payer,
+        &[create_activate_vault_instruction(
+            *program_id,
+            vault.vault,
+            vault.fraction_mint,
+            vault.fraction_treasury,
+            vault_authority(program_id),
+            payer.pubkey(),
+            number_of_shares,
+        )],
+        &[],
+    )
+    .await
+}
+
+/// Buys out the vault as the payer, who also stays its authority. The payer pays for every share
+/// in circulation out of a fresh account minted `payment` of the price mint.
+#[allow(clippy::too_many_arguments)]
+pub async fn combine_vault(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    vault: &TestVault,
+    price_mint: &Pubkey,
+    payment: u64,
+    redemption_recipient: Option<Pubkey>,
+    fee_recipient: Option<Pubkey>,
+) -> Result<(), TransportError> {
+    let payment_account = create_token_account(
+        banks_client,
+        payer,
+        recent_blockhash,
+        price_mint,
+        &payer.pubkey(),
+    )
+    .await?;
+    mint_tokens(
+        banks_client,
+        payer,
+        recent_blockhash,
+        price_mint,
+        &payment_account,
+        payment,
+    )
+    .await?;
+    let outstanding_shares = create_token_account(
+        banks_client,
+        payer,
+        recent_blockhash,
+        &vault.fraction_mint,
+        &payer.pubkey(),
+    )
+    .await?;
+
+    process_instructions(
+        banks_client,
+        recent_blockhash,
+        payer,
+        &[create_combine_vault_instruction(
+            *program_id,
+            vault.vault,
+            outstanding_shares,
+            payment_account,
+            vault.fraction_mint,
+            vault.fraction_treasury,
+            vault.redeem_treasury,
+            payer.pubkey(),
+            payer.pubkey(),
+            payer.pubkey(),
+            vault_authority(program_id),
+            vault.external_price_account,
+            redemption_recipient,
+            fee_recipient,
+        )],
+        &[],
+    )
+    .await
+}
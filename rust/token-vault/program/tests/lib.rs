@@ -0,0 +1,820 @@
+use borsh::BorshSerialize;
+use num_traits::FromPrimitive;
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, program_error::ProgramError, sysvar,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+    transport::TransportError,
+};
+use spl_token_vault::{
+    error::VaultError,
+    instruction::{
+        create_combine_vault_instruction, create_migrate_vault_instruction,
+        create_redeem_shares_instruction, create_set_redemption_window_instruction,
+        create_set_vault_fee_instruction, create_sweep_unclaimed_safety_deposit_box_instruction,
+        create_withdraw_native_instruction, create_withdraw_shares_instruction,
+    },
+    processor::process_instruction,
+    state::{
+        SafetyDepositBox, SafetyDepositBoxV1, Vault, VaultState, VaultV1, BASE_VAULT_SIZE,
+        MAX_SAFETY_DEPOSIT_SIZE, MAX_SAFETY_DEPOSIT_V1_SIZE, SAFETY_DEPOSIT_KEY,
+        SAFETY_DEPOSIT_V2_KEY, VAULT_KEY, VAULT_V2_KEY,
+    },
+};
+
+mod helpers;
+
+/// Starts a test validator running the vault program, and creates an inactive vault priced in a
+/// new mint with the payer as its authority.
+async fn setup_vault(
+    price_per_share: u64,
+) -> (ProgramTestContext, Pubkey, Pubkey, helpers::TestVault) {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "spl_token_vault",
+        program_id,
+        processor!(process_instruction),
+    );
+    program_test.add_program(
+        "spl_token",
+        spl_token::id(),
+        processor!(spl_token::processor::Processor::process),
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let price_mint = helpers::create_mint(
+        &mut context.banks_client,
+        &context.payer,
+        &context.last_blockhash,
+        &context.payer.pubkey(),
+        None,
+    )
+    .await
+    .unwrap();
+    let vault = helpers::init_vault(
+        &mut context.banks_client,
+        &context.payer,
+        &context.last_blockhash,
+        &program_id,
+        &price_mint,
+        price_per_share,
+    )
+    .await
+    .unwrap();
+
+    (context, program_id, price_mint, vault)
+}
+
+/// The vault error a failed transaction was rejected with.
+fn vault_error(err: TransportError) -> VaultError {
+    match err.unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            VaultError::from_u32(code).expect("vault error")
+        }
+        err => panic!("unexpected error {:?}", err),
+    }
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_native_deposit_and_withdraw() {
+    let (mut context, program_id, price_mint, vault) = setup_vault(0).await;
+    let payer = &context.payer;
+    let recent_blockhash = &context.last_blockhash;
+    let banks_client = &mut context.banks_client;
+
+    let safety_deposit_box = helpers::add_native(
+        banks_client,
+        payer,
+        recent_blockhash,
+        &program_id,
+        &vault,
+        10_000_000,
+    )
+    .await
+    .unwrap();
+
+    // The box address only depends on the vault, so there is no room for a second one.
+    helpers::add_native(
+        banks_client,
+        payer,
+        recent_blockhash,
+        &program_id,
+        &vault,
+        1,
+    )
+    .await
+    .unwrap_err();
+
+    // The box holds the deposit on top of its own rent.
+    let rent = banks_client.get_rent().await.unwrap();
+    let box_account = helpers::get_account(banks_client, &safety_deposit_box).await;
+    assert_eq!(
+        box_account.lamports,
+        rent.minimum_balance(MAX_SAFETY_DEPOSIT_SIZE) + 10_000_000
+    );
+    let safety_deposit = SafetyDepositBox::from_slice(&box_account.data).unwrap();
+    assert!(safety_deposit.is_native());
+    assert_eq!(safety_deposit.store, safety_deposit_box);
+    assert_eq!(safety_deposit.amount, 10_000_000);
+
+    helpers::activate_vault(
+        banks_client,
+        payer,
+        recent_blockhash,
+        &program_id,
+        &vault,
+        100,
+    )
+    .await
+    .unwrap();
+    helpers::combine_vault(
+        banks_client,
+        payer,
+        recent_blockhash,
+        &program_id,
+        &vault,
+        &price_mint,
+        0,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let destination = Keypair::new().pubkey();
+    let withdraw = |amount| {
+        create_withdraw_native_instruction(
+            program_id,
+            destination,
+            safety_deposit_box,
+            vault.vault,
+            vault.fraction_mint,
+            payer.pubkey(),
+            amount,
+        )
+    };
+
+    // Nothing past the recorded amount can be taken, the rest of the balance is the box rent.
+    let err = helpers::process_instructions(
+        banks_client,
+        recent_blockhash,
+        payer,
+        &[withdraw(10_000_001)],
+        &[],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(vault_error(err), VaultError::StoreLessThanAmount);
+
+    helpers::process_instructions(
+        banks_client,
+        recent_blockhash,
+        payer,
+        &[withdraw(4_000_000)],
+        &[],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        helpers::get_account(banks_client, &destination)
+            .await
+            .lamports,
+        4_000_000
+    );
+    let box_account = helpers::get_account(banks_client, &safety_deposit_box).await;
+    assert_eq!(
+        SafetyDepositBox::from_slice(&box_account.data)
+            .unwrap()
+            .amount,
+        6_000_000
+    );
+    assert!(helpers::get_vault(banks_client, &vault.vault).await.state == VaultState::Combined);
+
+    // Emptying the only box of a vault with no shares left deactivates it.
+    helpers::process_instructions(
+        banks_client,
+        recent_blockhash,
+        payer,
+        &[withdraw(6_000_000)],
+        &[],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        helpers::get_account(banks_client, &destination)
+            .await
+            .lamports,
+        10_000_000
+    );
+    let box_account = helpers::get_account(banks_client, &safety_deposit_box).await;
+    assert_eq!(
+        box_account.lamports,
+        rent.minimum_balance(MAX_SAFETY_DEPOSIT_SIZE)
+    );
+    assert!(helpers::get_vault(banks_client, &vault.vault).await.state == VaultState::Deactivated);
+}
+
+/// A vault as the original program wrote it, with a u8 token_type_count, in an account of the
+/// original 205 byte size.
+fn legacy_vault_data(authority: &Pubkey, state: VaultState) -> Vec<u8> {
+    let mut data = VaultV1 {
+        key: VAULT_KEY,
+        token_program: spl_token::id(),
+        fraction_mint: Pubkey::new_unique(),
+        authority: *authority,
+        fraction_treasury: Pubkey::new_unique(),
+        redeem_treasury: Pubkey::new_unique(),
+        allow_further_share_creation: true,
+        pricing_lookup_address: Pubkey::new_unique(),
+        token_type_count: 200,
+        state,
+        locked_price_per_share: 42,
+    }
+    .try_to_vec()
+    .unwrap();
+    data.resize(BASE_VAULT_SIZE, 0);
+    data
+}
+
+/// Starts a test validator with a vault program account holding `data`.
+async fn setup_vault_account(data: Vec<u8>) -> (ProgramTestContext, Pubkey, Pubkey) {
+    let program_id = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "spl_token_vault",
+        program_id,
+        processor!(process_instruction),
+    );
+    program_test.add_account(
+        vault,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    (program_test.start_with_context().await, program_id, vault)
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_migrate_legacy_vault() {
+    let authority = Pubkey::new_unique();
+    let legacy = legacy_vault_data(&authority, VaultState::Combined);
+    let (mut context, program_id, vault) = setup_vault_account(legacy.clone()).await;
+
+    helpers::process_instructions(
+        &mut context.banks_client,
+        &context.last_blockhash,
+        &context.payer,
+        &[create_migrate_vault_instruction(program_id, vault)],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // Every field survives the widening, in an account that keeps its original size.
+    let migrated = helpers::get_account(&mut context.banks_client, &vault).await;
+    assert_eq!(migrated.data.len(), BASE_VAULT_SIZE);
+    assert_eq!(migrated.data[0], VAULT_V2_KEY);
+    let before = Vault::from_slice(&legacy).unwrap();
+    let after = Vault::from_slice(&migrated.data).unwrap();
+    assert_eq!(after.try_to_vec().unwrap(), before.try_to_vec().unwrap());
+    assert_eq!(after.authority, authority);
+    assert_eq!(after.token_type_count, 200);
+    assert!(after.state == VaultState::Combined);
+    assert_eq!(after.locked_price_per_share, 42);
+    assert_eq!(after.redemption_window, None);
+    assert_eq!(after.fee_basis_points, 0);
+
+    // Migrating again leaves the vault untouched.
+    let recent_blockhash = context
+        .banks_client
+        .get_new_latest_blockhash(&context.last_blockhash)
+        .await
+        .unwrap();
+    helpers::process_instructions(
+        &mut context.banks_client,
+        &recent_blockhash,
+        &context.payer,
+        &[create_migrate_vault_instruction(program_id, vault)],
+        &[],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        helpers::get_account(&mut context.banks_client, &vault)
+            .await
+            .data,
+        migrated.data
+    );
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_legacy_vault_refuses_fields_it_has_no_room_for() {
+    let authority = Keypair::new();
+    let legacy = legacy_vault_data(&authority.pubkey(), VaultState::Inactive);
+    let (mut context, program_id, vault) = setup_vault_account(legacy).await;
+    let set_window = |window| {
+        create_set_redemption_window_instruction(program_id, vault, authority.pubkey(), window)
+    };
+
+    // The window lives past the original 205 bytes, and would be cut off on save.
+    let err = helpers::process_instructions(
+        &mut context.banks_client,
+        &context.last_blockhash,
+        &context.payer,
+        &[set_window(Some(60))],
+        &[&authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(vault_error(err), VaultError::VaultAccountTooSmall);
+
+    // Leaving it unset writes nothing past the end, so the vault is still saved.
+    helpers::process_instructions(
+        &mut context.banks_client,
+        &context.last_blockhash,
+        &context.payer,
+        &[set_window(None)],
+        &[&authority],
+    )
+    .await
+    .unwrap();
+    let data = helpers::get_account(&mut context.banks_client, &vault)
+        .await
+        .data;
+    assert_eq!(data.len(), BASE_VAULT_SIZE);
+    assert_eq!(data[0], VAULT_V2_KEY);
+}
+
+#[cfg(feature = "test-bpf")]
+#[test]
+fn test_legacy_safety_deposit_boxes() {
+    let legacy = SafetyDepositBoxV1 {
+        key: SAFETY_DEPOSIT_KEY,
+        vault: Pubkey::new_unique(),
+        token_mint: Pubkey::new_unique(),
+        store: Pubkey::new_unique(),
+        order: 7,
+        amount: 500,
+    }
+    .try_to_vec()
+    .unwrap();
+    assert_eq!(legacy.len(), MAX_SAFETY_DEPOSIT_V1_SIZE);
+
+    // The earliest boxes end before the amount, which reads back as zero.
+    let earliest = SafetyDepositBox::from_slice(&legacy[..MAX_SAFETY_DEPOSIT_V1_SIZE - 8]).unwrap();
+    assert_eq!(earliest.order, 7);
+    assert_eq!(earliest.amount, 0);
+
+    let mut data = legacy.clone();
+    let mut safety_deposit = SafetyDepositBox::from_slice(&data).unwrap();
+    assert_eq!(safety_deposit.key, SAFETY_DEPOSIT_KEY);
+    assert_eq!(safety_deposit.order, 7);
+    assert_eq!(safety_deposit.amount, 500);
+
+    // Saving keeps the original layout, boxes are never migrated.
+    let key = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let mut lamports = 0;
+    safety_deposit.amount = 200;
+    {
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        safety_deposit.save(&info).unwrap();
+
+        // A u16 order no longer fits the legacy layout.
+        safety_deposit.order = 256;
+        assert_eq!(
+            safety_deposit.save(&info).unwrap_err(),
+            ProgramError::from(VaultError::NumericalOverflowError)
+        );
+    }
+    assert_eq!(data.len(), MAX_SAFETY_DEPOSIT_V1_SIZE);
+    assert_eq!(
+        &data[..MAX_SAFETY_DEPOSIT_V1_SIZE - 8],
+        &legacy[..MAX_SAFETY_DEPOSIT_V1_SIZE - 8]
+    );
+    let saved = SafetyDepositBox::from_slice(&data).unwrap();
+    assert_eq!(saved.key, SAFETY_DEPOSIT_KEY);
+    assert_eq!(saved.order, 7);
+    assert_eq!(saved.amount, 200);
+
+    // Boxes created since then use the widened layout.
+    let current = SafetyDepositBox {
+        key: SAFETY_DEPOSIT_V2_KEY,
+        order: 300,
+        ..saved
+    };
+    let data = current.try_to_vec().unwrap();
+    assert_eq!(data.len(), MAX_SAFETY_DEPOSIT_SIZE);
+    assert_eq!(SafetyDepositBox::from_slice(&data).unwrap().order, 300);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_sweep_then_redeem() {
+    let (mut context, program_id, price_mint, vault) = setup_vault(5).await;
+    let payer = &context.payer;
+    let blockhash = context.last_blockhash;
+    let banks_client = &mut context.banks_client;
+    let (safety_deposit_box, store) =
+        helpers::add_token(banks_client, payer, &blockhash, &program_id, &vault, 100)
+            .await
+            .unwrap();
+    let token_mint = SafetyDepositBox::from_slice(
+        &helpers::get_account(banks_client, &safety_deposit_box)
+            .await
+            .data,
+    )
+    .unwrap()
+    .token_mint;
+    helpers::process_instructions(
+        banks_client,
+        &blockhash,
+        payer,
+        &[create_set_redemption_window_instruction(
+            program_id,
+            vault.vault,
+            payer.pubkey(),
+            Some(60),
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    helpers::activate_vault(banks_client, payer, &blockhash, &program_id, &vault, 100)
+        .await
+        .unwrap();
+
+    // A shareholder keeps 40 shares through the buyout.
+    let holder = Keypair::new();
+    let holder_shares = helpers::create_token_account(
+        banks_client,
+        payer,
+        &blockhash,
+        &vault.fraction_mint,
+        &holder.pubkey(),
+    )
+    .await
+    .unwrap();
+    helpers::process_instructions(
+        banks_client,
+        &blockhash,
+        payer,
+        &[create_withdraw_shares_instruction(
+            program_id,
+            holder_shares,
+            vault.fraction_treasury,
+            vault.vault,
+            helpers::vault_authority(&program_id),
+            payer.pubkey(),
+            40,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let recipient = Pubkey::new_unique();
+    helpers::combine_vault(
+        banks_client,
+        payer,
+        &blockhash,
+        &program_id,
+        &vault,
+        &price_mint,
+        200,
+        Some(recipient),
+        None,
+    )
+    .await
+    .unwrap();
+    let deadline = helpers::get_vault(banks_client, &vault.vault)
+        .await
+        .redemption_deadline
+        .unwrap();
+
+    let destination =
+        helpers::create_token_account(banks_client, payer, &blockhash, &token_mint, &recipient)
+            .await
+            .unwrap();
+    let sweep = create_sweep_unclaimed_safety_deposit_box_instruction(
+        program_id,
+        destination,
+        safety_deposit_box,
+        store,
+        vault.vault,
+        vault.fraction_mint,
+        holder.pubkey(),
+        holder_shares,
+        helpers::vault_authority(&program_id),
+    );
+    let err = helpers::process_instructions(
+        banks_client,
+        &blockhash,
+        payer,
+        std::slice::from_ref(&sweep),
+        &[&holder],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(vault_error(err), VaultError::RedemptionDeadlineNotPassed);
+
+    let mut clock: Clock = banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = deadline + 1;
+    context.set_sysvar(&clock);
+    let payer = &context.payer;
+    let banks_client = &mut context.banks_client;
+    let blockhash = banks_client
+        .get_new_latest_blockhash(&blockhash)
+        .await
+        .unwrap();
+
+    // Sweeping the only box leaves the shareholder's claim on the treasury intact.
+    helpers::process_instructions(banks_client, &blockhash, payer, &[sweep], &[&holder])
+        .await
+        .unwrap();
+    assert_eq!(
+        helpers::get_token_balance(banks_client, &destination).await,
+        100
+    );
+    let swept = helpers::get_vault(banks_client, &vault.vault).await;
+    assert_eq!(swept.token_type_count, 0);
+    assert!(swept.state == VaultState::Combined);
+
+    let proceeds = helpers::create_token_account(
+        banks_client,
+        payer,
+        &blockhash,
+        &price_mint,
+        &holder.pubkey(),
+    )
+    .await
+    .unwrap();
+    helpers::process_instructions(
+        banks_client,
+        &blockhash,
+        payer,
+        &[create_redeem_shares_instruction(
+            program_id,
+            holder_shares,
+            proceeds,
+            vault.fraction_mint,
+            vault.redeem_treasury,
+            helpers::vault_authority(&program_id),
+            holder.pubkey(),
+            vault.vault,
+            None,
+        )],
+        &[&holder],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        helpers::get_token_balance(banks_client, &proceeds).await,
+        200
+    );
+    assert!(helpers::get_vault(banks_client, &vault.vault).await.state == VaultState::Deactivated);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_combine_with_fee() {
+    let (mut context, program_id, price_mint, vault) = setup_vault(5).await;
+    let payer = &context.payer;
+    let blockhash = context.last_blockhash;
+    let banks_client = &mut context.banks_client;
+    let fee_recipient = helpers::create_token_account(
+        banks_client,
+        payer,
+        &blockhash,
+        &price_mint,
+        &payer.pubkey(),
+    )
+    .await
+    .unwrap();
+    helpers::process_instructions(
+        banks_client,
+        &blockhash,
+        payer,
+        &[create_set_vault_fee_instruction(
+            program_id,
+            vault.vault,
+            payer.pubkey(),
+            fee_recipient,
+            500,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    helpers::activate_vault(banks_client, payer, &blockhash, &program_id, &vault, 100)
+        .await
+        .unwrap();
+    let holder_shares = helpers::create_token_account(
+        banks_client,
+        payer,
+        &blockhash,
+        &vault.fraction_mint,
+        &Pubkey::new_unique(),
+    )
+    .await
+    .unwrap();
+    helpers::process_instructions(
+        banks_client,
+        &blockhash,
+        payer,
+        &[create_withdraw_shares_instruction(
+            program_id,
+            holder_shares,
+            vault.fraction_treasury,
+            vault.vault,
+            helpers::vault_authority(&program_id),
+            payer.pubkey(),
+            40,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // Without a redemption window the fee recipient directly follows the token program.
+    let instruction = create_combine_vault_instruction(
+        program_id,
+        vault.vault,
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        vault.fraction_mint,
+        vault.fraction_treasury,
+        vault.redeem_treasury,
+        payer.pubkey(),
+        payer.pubkey(),
+        payer.pubkey(),
+        helpers::vault_authority(&program_id),
+        vault.external_price_account,
+        None,
+        Some(fee_recipient),
+    );
+    assert_eq!(instruction.accounts.len(), 13);
+    assert_eq!(instruction.accounts[12].pubkey, fee_recipient);
+
+    // The fee is charged on top of the 200 owed for the 40 outstanding shares.
+    let err = helpers::combine_vault(
+        banks_client,
+        payer,
+        &blockhash,
+        &program_id,
+        &vault,
+        &price_mint,
+        200,
+        None,
+        Some(fee_recipient),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(vault_error(err), VaultError::CannotAffordToCombineThisVault);
+
+    let err = helpers::combine_vault(
+        banks_client,
+        payer,
+        &blockhash,
+        &program_id,
+        &vault,
+        &price_mint,
+        210,
+        None,
+        Some(holder_shares),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(vault_error(err), VaultError::FeeRecipientMismatch);
+
+    helpers::combine_vault(
+        banks_client,
+        payer,
+        &blockhash,
+        &program_id,
+        &vault,
+        &price_mint,
+        210,
+        None,
+        Some(fee_recipient),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        helpers::get_token_balance(banks_client, &fee_recipient).await,
+        10
+    );
+    assert_eq!(
+        helpers::get_token_balance(banks_client, &vault.redeem_treasury).await,
+        200
+    );
+    let combined = helpers::get_vault(banks_client, &vault.vault).await;
+    assert!(combined.state == VaultState::Combined);
+    assert_eq!(combined.redemption_deadline, None);
+}
+
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_combine_with_redemption_window() {
+    let (mut context, program_id, price_mint, vault) = setup_vault(5).await;
+    let payer = &context.payer;
+    let blockhash = context.last_blockhash;
+    let banks_client = &mut context.banks_client;
+    helpers::process_instructions(
+        banks_client,
+        &blockhash,
+        payer,
+        &[create_set_redemption_window_instruction(
+            program_id,
+            vault.vault,
+            payer.pubkey(),
+            Some(60),
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    helpers::activate_vault(banks_client, payer, &blockhash, &program_id, &vault, 100)
+        .await
+        .unwrap();
+
+    // Without a fee only the clock and redemption recipient follow the token program.
+    let recipient = Pubkey::new_unique();
+    let instruction = create_combine_vault_instruction(
+        program_id,
+        vault.vault,
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        vault.fraction_mint,
+        vault.fraction_treasury,
+        vault.redeem_treasury,
+        payer.pubkey(),
+        payer.pubkey(),
+        payer.pubkey(),
+        helpers::vault_authority(&program_id),
+        vault.external_price_account,
+        Some(recipient),
+        None,
+    );
+    assert_eq!(instruction.accounts.len(), 14);
+    assert_eq!(instruction.accounts[12].pubkey, sysvar::clock::id());
+    assert_eq!(instruction.accounts[13].pubkey, recipient);
+
+    let err = helpers::combine_vault(
+        banks_client,
+        payer,
+        &blockhash,
+        &program_id,
+        &vault,
+        &price_mint,
+        0,
+        None,
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+
+    helpers::combine_vault(
+        banks_client,
+        payer,
+        &blockhash,
+        &program_id,
+        &vault,
+        &price_mint,
+        0,
+        Some(recipient),
+        None,
+    )
+    .await
+    .unwrap();
+    let combined = helpers::get_vault(banks_client, &vault.vault).await;
+    assert!(combined.state == VaultState::Combined);
+    assert!(combined.redemption_deadline.is_some());
+    assert_eq!(combined.redemption_recipient, recipient);
+}
@@ -362,7 +362,7 @@ fn activate_vault(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) -
         .unwrap();
     let vault_key = pubkey_of(app_matches, "vault_address").unwrap();
     let vault_account = client.get_account(&vault_key).unwrap();
-    let vault: Vault = try_from_slice_unchecked(&vault_account.data).unwrap();
+    let vault = Vault::from_slice(&vault_account.data).unwrap();
 
     let seeds = &[PREFIX.as_bytes(), &program_key.as_ref()];
     let (mint_authority, _) = Pubkey::find_program_address(seeds, &program_key);
@@ -384,7 +384,7 @@ fn activate_vault(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) -
     transaction.sign(&signers, recent_blockhash);
     client.send_and_confirm_transaction(&transaction).unwrap();
     let updated_vault_data = client.get_account(&vault_key).unwrap();
-    let updated_vault: Vault = try_from_slice_unchecked(&updated_vault_data.data).unwrap();
+    let updated_vault = Vault::from_slice(&updated_vault_data.data).unwrap();
     if updated_vault.state == VaultState::Active {
         println!("Activated vault.");
         Some(vault_key)
@@ -416,7 +416,7 @@ fn combine_vault(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) ->
 
     let vault_key = pubkey_of(app_matches, "vault_address").unwrap();
     let vault_account = client.get_account(&vault_key).unwrap();
-    let vault: Vault = try_from_slice_unchecked(&vault_account.data).unwrap();
+    let vault = Vault::from_slice(&vault_account.data).unwrap();
     let external_price_account = client.get_account(&vault.pricing_lookup_address).unwrap();
     let external: ExternalPriceAccount =
         try_from_slice_unchecked(&external_price_account.data).unwrap();
@@ -530,6 +530,11 @@ fn combine_vault(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) ->
         transfer_authority.pubkey(),
         uncirculated_burn_authority,
         vault.pricing_lookup_address,
+        vault.redemption_window.map(|_| new_vault_authority),
+        match vault.fee_basis_points {
+            0 => None,
+            _ => Some(vault.fee_recipient),
+        },
     ));
 
     let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
@@ -538,7 +543,7 @@ fn combine_vault(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) ->
     transaction.sign(&signers, recent_blockhash);
     client.send_and_confirm_transaction(&transaction).unwrap();
     let updated_vault_data = client.get_account(&vault_key).unwrap();
-    let updated_vault: Vault = try_from_slice_unchecked(&updated_vault_data.data).unwrap();
+    let updated_vault = Vault::from_slice(&updated_vault_data.data).unwrap();
     if updated_vault.state == VaultState::Combined {
         println!("Combined vault.");
         Some(vault_key)
@@ -565,7 +570,7 @@ fn redeem_shares(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) ->
     let outstanding_shares: Account =
         Account::unpack_unchecked(&outstanding_shares_account.data).unwrap();
     let vault_account = client.get_account(&vault_key).unwrap();
-    let vault: Vault = try_from_slice_unchecked(&vault_account.data).unwrap();
+    let vault = Vault::from_slice(&vault_account.data).unwrap();
     let redeem_treasury_info = client.get_account(&vault.redeem_treasury).unwrap();
     let redeem_treasury: Account = Account::unpack_unchecked(&redeem_treasury_info.data).unwrap();
 
@@ -625,6 +630,10 @@ fn redeem_shares(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) ->
         transfer_authority,
         burn_authority.pubkey(),
         vault_key,
+        match vault.fee_basis_points {
+            0 => None,
+            _ => Some(vault.fee_recipient),
+        },
     ));
 
     let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
@@ -649,12 +658,11 @@ fn withdraw_tokens(app_matches: &ArgMatches, payer: Keypair, client: RpcClient)
 
     let safety_deposit_key = pubkey_of(app_matches, "safety_deposit_address").unwrap();
     let safety_deposit_account = client.get_account(&safety_deposit_key).unwrap();
-    let safety_deposit: SafetyDepositBox =
-        try_from_slice_unchecked(&safety_deposit_account.data).unwrap();
+    let safety_deposit = SafetyDepositBox::from_slice(&safety_deposit_account.data).unwrap();
     let store_account = client.get_account(&safety_deposit.store).unwrap();
     let store: Account = Account::unpack_unchecked(&store_account.data).unwrap();
     let vault_account = client.get_account(&safety_deposit.vault).unwrap();
-    let vault: Vault = try_from_slice_unchecked(&vault_account.data).unwrap();
+    let vault = Vault::from_slice(&vault_account.data).unwrap();
     let amount: u64 = app_matches
         .value_of("amount")
         .unwrap_or(&store.amount.to_string())
@@ -722,7 +730,7 @@ fn mint_shares(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) -> P
 
     let vault_key = pubkey_of(app_matches, "vault_address").unwrap();
     let vault_account = client.get_account(&vault_key).unwrap();
-    let vault: Vault = try_from_slice_unchecked(&vault_account.data).unwrap();
+    let vault = Vault::from_slice(&vault_account.data).unwrap();
 
     let signers = vec![&payer, &vault_authority];
     let seeds = &[PREFIX.as_bytes(), &program_key.as_ref()];
@@ -766,7 +774,7 @@ fn withdraw_shares(app_matches: &ArgMatches, payer: Keypair, client: RpcClient)
 
     let vault_key = pubkey_of(app_matches, "vault_address").unwrap();
     let vault_account = client.get_account(&vault_key).unwrap();
-    let vault: Vault = try_from_slice_unchecked(&vault_account.data).unwrap();
+    let vault = Vault::from_slice(&vault_account.data).unwrap();
     let number_of_shares: u64 = app_matches
         .value_of("number_of_shares")
         .unwrap_or("100")
@@ -838,7 +846,7 @@ fn add_shares(app_matches: &ArgMatches, payer: Keypair, client: RpcClient) -> Pu
 
     let vault_key = pubkey_of(app_matches, "vault_address").unwrap();
     let vault_account = client.get_account(&vault_key).unwrap();
-    let vault: Vault = try_from_slice_unchecked(&vault_account.data).unwrap();
+    let vault = Vault::from_slice(&vault_account.data).unwrap();
     let number_of_shares: u64 = app_matches
         .value_of("number_of_shares")
         .unwrap_or("100")